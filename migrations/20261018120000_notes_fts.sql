@@ -0,0 +1,12 @@
+-- Add migration script here
+
+CREATE VIRTUAL TABLE notes_fts USING fts5(
+  name UNINDEXED,
+  body
+);
+
+create table if not exists fts_index_state (
+	name text primary key,
+	mtime integer not null,
+
+    FOREIGN KEY(name) REFERENCES notes(name) on delete cascade on update cascade );
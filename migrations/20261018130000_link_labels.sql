@@ -0,0 +1,3 @@
+-- Add migration script here
+
+alter table linkx add column label text;
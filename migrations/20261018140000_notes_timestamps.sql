@@ -0,0 +1,13 @@
+-- Add migration script here
+
+alter table notes add column created_at integer;
+alter table notes add column updated_at integer;
+alter table notes add column opened_at integer;
+
+-- bodies created by `mds` are named `./<unix timestamp>_<suffix>.md`
+update notes set created_at = case
+	when filename glob './[0-9]*_*.md'
+		then cast(substr(filename, 3, instr(filename, '_') - 3) as integer)
+	else cast(strftime('%s', 'now') as integer)
+end;
+update notes set updated_at = created_at;
@@ -0,0 +1,8 @@
+-- Add migration script here
+
+create table if not exists journal (
+	id integer primary key autoincrement,
+	operation text not null,
+	undone integer not null default 0,
+	applied_at integer not null
+);
@@ -0,0 +1,9 @@
+-- Add migration script here
+
+create table if not exists trash (
+	id integer primary key autoincrement,
+	name text not null,
+	filename text,
+	snapshot text not null,
+	trashed_at integer not null
+);
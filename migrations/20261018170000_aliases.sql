@@ -0,0 +1,8 @@
+-- Add migration script here
+
+create table if not exists aliases (
+	alias text primary key,
+	note text not null,
+
+    FOREIGN KEY(note) REFERENCES notes(name) on delete cascade on update cascade );
+CREATE INDEX aliases_note_index ON aliases(note);
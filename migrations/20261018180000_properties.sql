@@ -0,0 +1,10 @@
+-- Add migration script here
+
+create table if not exists properties (
+	note text not null,
+	key text not null,
+	value text not null,
+	PRIMARY KEY (note, key),
+
+    FOREIGN KEY(note) REFERENCES notes(name) on delete cascade on update cascade );
+CREATE INDEX properties_key_index ON properties(key, value);
@@ -0,0 +1,9 @@
+-- Add migration script here
+
+-- links, created from `[[wikilinks]]` in bodies by `sync-links`
+create table if not exists synced_links (
+  _from text not null,
+  _to text not null,
+  PRIMARY KEY (_from, _to),
+
+    FOREIGN KEY(_from, _to) REFERENCES linkx(_from, _to) on delete cascade on update cascade );
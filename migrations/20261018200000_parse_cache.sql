@@ -0,0 +1,10 @@
+-- Add migration script here
+
+-- links and task items, parsed from bodies of notes, valid while file's mtime and size are the same
+create table if not exists parse_cache (
+  file_path text not null,
+  mtime integer not null,
+  size integer not null,
+  regexes text not null,
+  body text not null,
+  PRIMARY KEY (file_path) );
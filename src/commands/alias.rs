@@ -0,0 +1,39 @@
+use inquire::Text;
+
+use crate::{
+    config::color::ColorScheme, database::DatabaseAsyncHandle, highlight::MarkdownStatic,
+    note::Note, print::format_two_tokens,
+};
+
+pub(crate) async fn edit_aliases(
+    note: Note,
+    db: DatabaseAsyncHandle,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+) -> Result<Note, anyhow::Error> {
+    let input = Text::new(&format!("Enter aliases of `{}`:", note.name()))
+        .with_initial_value(&note.aliases().join(", "))
+        .with_help_message("comma-separated, empty input removes all aliases")
+        .prompt()?;
+    let aliases = input
+        .split(',')
+        .map(|alias| alias.trim().to_string())
+        .filter(|alias| !alias.is_empty())
+        .collect::<Vec<_>>();
+
+    db.lock().await.set_aliases(&note.name(), &aliases).await?;
+    let note = db
+        .lock()
+        .await
+        .get(&note.name(), md_static, color_scheme)
+        .await?;
+
+    eprintln!(
+        "{}",
+        format_two_tokens(
+            "aliases",
+            &format!("{}: [{}]", note.name(), note.aliases().join(", "))
+        )
+    );
+    Ok(note)
+}
@@ -1,11 +1,10 @@
 use futures::future::join_all;
-use syntect::easy::HighlightLines;
 
 use crate::{
     config::{color::ColorScheme, keymap, ExternalCommands, SurfParsing},
-    database::{Database, SqliteAsyncHandle},
+    database::DatabaseAsyncHandle,
     highlight::MarkdownStatic,
-    note::{Note, NoteTaskItemTerm, PreviewType},
+    note::{Note, NoteTaskItemTerm, PreviewCache, PreviewType},
     skim::checkmark::Action as TaskAction,
     skim::checkmark::Iteration as CheckmarkIteration,
     skim::explore::Action,
@@ -16,7 +15,7 @@ use crate::{
 use super::explore::iteration;
 
 pub(crate) async fn exec(
-    db: SqliteAsyncHandle,
+    db: DatabaseAsyncHandle,
     surf: SurfParsing,
     external_commands: ExternalCommands,
     md_static: MarkdownStatic,
@@ -28,6 +27,7 @@ pub(crate) async fn exec(
 
     let nested_threshold = 1;
     let mut preview_type = PreviewType::TaskStructure;
+    let previews = PreviewCache::new()?;
     let note = loop {
         let (next_items, opened, preview_type_after) = iteration(
             db.clone(),
@@ -40,6 +40,7 @@ pub(crate) async fn exec(
             true,
             nested_threshold,
             explore_bindings_map.clone(),
+            &previews,
         )
         .await?;
         preview_type = preview_type_after;
@@ -49,19 +50,21 @@ pub(crate) async fn exec(
             break opened;
         }
     };
-    let _note = checkmark_note(note, &external_commands, &surf, md_static, bindings_map).await?;
+    let _note =
+        checkmark_note(note, db, &external_commands, &surf, md_static, bindings_map).await?;
     Ok("success".to_string())
 }
 
 pub(crate) async fn checkmark_note(
     note: Note,
+    db: DatabaseAsyncHandle,
     external_commands: &ExternalCommands,
     surf: &SurfParsing,
     md_static: MarkdownStatic,
     bindings_map: keymap::checkmark::Bindings,
 ) -> Result<Note, anyhow::Error> {
     let mut next_tasks_window = None;
-    let mut tasks = read_tasks_from_file(&note, surf, md_static).await?;
+    let mut tasks = read_tasks_from_file(&note, &db, surf, md_static).await?;
     loop {
         let action = CheckmarkIteration::new(tasks, note.clone(), bindings_map.clone()).run()?;
         next_tasks_window = match action {
@@ -89,9 +92,9 @@ pub(crate) async fn checkmark_note(
             }
         };
         tasks = match next_tasks_window {
-            None => read_tasks_from_file(&note, surf, md_static).await?,
+            None => read_tasks_from_file(&note, &db, surf, md_static).await?,
             Some((start, end)) => {
-                let all = read_tasks_from_file(&note, surf, md_static).await?;
+                let all = read_tasks_from_file(&note, &db, surf, md_static).await?;
                 all[start..end].to_vec()
             }
         };
@@ -100,18 +103,15 @@ pub(crate) async fn checkmark_note(
 
 async fn read_tasks_from_file(
     note: &Note,
+    db: &DatabaseAsyncHandle,
     surf: &SurfParsing,
     md_static: MarkdownStatic,
 ) -> Result<Vec<TaskTreeWrapper>, anyhow::Error> {
-    let mut highlighter = HighlightLines::new(md_static.1, md_static.2);
-    let tasks = TaskItem::parse(note, surf, &mut highlighter, md_static)?;
+    let tasks = TaskItem::parse(note, surf, md_static, db).await?;
 
     let tasks_stereo = NoteTaskItemTerm::parse(&tasks, false, false);
     let tasks_mono = NoteTaskItemTerm::parse(&tasks, false, true);
-    let tasks = tasks_stereo
-        .into_iter()
-        .zip(tasks_mono.into_iter())
-        .collect::<Vec<_>>();
+    let tasks = tasks_stereo.into_iter().zip(tasks_mono).collect::<Vec<_>>();
 
     let compute_display_jh = tasks
         .into_iter()
@@ -1,16 +1,13 @@
 use syntect::easy::HighlightLines;
 
 use crate::{
-    config::color::ColorScheme,
-    database::{Database, SqliteAsyncHandle},
-    highlight::MarkdownStatic,
-    note::Note,
-    print::format_two_tokens,
+    config::color::ColorScheme, database::DatabaseAsyncHandle, highlight::MarkdownStatic,
+    note::Note, print::format_two_tokens,
 };
 
 pub(crate) async fn exec(
     title: &str,
-    db: SqliteAsyncHandle,
+    db: DatabaseAsyncHandle,
 
     is_tag: bool,
 
@@ -26,7 +23,7 @@ pub(crate) async fn exec(
 
 pub(crate) async fn create(
     title: &str,
-    db: SqliteAsyncHandle,
+    db: DatabaseAsyncHandle,
 
     is_tag: bool,
 
@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+
+use bidir_termtree::{Down, Tree};
+
+use crate::{
+    config::{color::ColorScheme, keymap, ExternalCommands, SurfParsing},
+    database::DatabaseAsyncHandle,
+    graph::{cyclic_components, elementary_cycles},
+    highlight::MarkdownStatic,
+    note::{Note, NoteLinkTerm},
+    print::format_two_tokens,
+};
+
+use super::path::chain_tree;
+
+/// elementary cycles listed at most, as their number may grow exponentially with links
+const MAX_CYCLES: usize = 1000;
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CyclesQuery {
+    /// every cycle, which visits no note twice, instead of strongly connected components
+    pub elementary: bool,
+    /// only links with this label are followed
+    pub label: Option<String>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn exec(
+    db: DatabaseAsyncHandle,
+    query: CyclesQuery,
+    external_commands: ExternalCommands,
+    surf_parsing: SurfParsing,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+    bindings_map: keymap::surf::Bindings,
+    chck_bindings_map: keymap::checkmark::Bindings,
+    stack_bindings_map: keymap::stack::Bindings,
+    explore_bindings_map: keymap::explore::Bindings,
+) -> Result<String, anyhow::Error> {
+    let (notes, mut labels) = labelled_links(db.clone(), md_static, color_scheme).await?;
+    if let Some(label) = &query.label {
+        labels.retain(|_link, link_label| link_label.as_ref() == Some(label));
+    }
+    let names = notes.keys().cloned().collect::<Vec<_>>();
+    let edges = labels.keys().cloned().collect::<Vec<_>>();
+
+    let (groups, truncated) = match query.elementary {
+        true => elementary_cycles(&names, &edges, MAX_CYCLES),
+        false => (cyclic_components(&names, &edges), false),
+    };
+    if groups.is_empty() {
+        let scope = match &query.label {
+            Some(label) => format!("along `{}` links", label),
+            None => "along links".to_string(),
+        };
+        return Ok(format_two_tokens("no cycles", &scope));
+    }
+
+    let mut in_cycles: Vec<String> = vec![];
+    for (index, group) in groups.iter().enumerate() {
+        let members = group
+            .iter()
+            .filter_map(|name| notes.get(name).cloned())
+            .collect::<Vec<_>>();
+        if query.elementary {
+            eprintln!(
+                "{}",
+                format_two_tokens(
+                    &format!("cycle {}:", index + 1),
+                    &format!("{} links", members.len())
+                )
+            );
+            println!("{}", cycle_tree(&members, &labels, color_scheme));
+        } else {
+            eprintln!(
+                "{}",
+                format_two_tokens(
+                    &format!("component {}:", index + 1),
+                    &format!("{} notes", members.len())
+                )
+            );
+            for note in &members {
+                println!("{}", note);
+            }
+        }
+        for name in group {
+            if !in_cycles.contains(name) {
+                in_cycles.push(name.clone());
+            }
+        }
+    }
+    if truncated {
+        eprintln!(
+            "{}",
+            format_two_tokens(
+                &format!("first {} cycles found are listed,", MAX_CYCLES),
+                "the rest are left out"
+            )
+        );
+    }
+
+    super::explore::exec(
+        db,
+        Some(in_cycles),
+        external_commands,
+        surf_parsing,
+        md_static,
+        color_scheme,
+        bindings_map,
+        chck_bindings_map,
+        stack_bindings_map,
+        explore_bindings_map,
+    )
+    .await
+}
+
+/// strongly connected component, containing `note`; empty if `note` is in no cycle
+pub(crate) async fn component_of(
+    note: &Note,
+    db: DatabaseAsyncHandle,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+) -> Result<Vec<Note>, anyhow::Error> {
+    let (notes, labels) = labelled_links(db, md_static, color_scheme).await?;
+    let names = notes.keys().cloned().collect::<Vec<_>>();
+    let edges = labels.into_keys().collect::<Vec<_>>();
+
+    let component = cyclic_components(&names, &edges)
+        .into_iter()
+        .find(|component| component.contains(&note.name()))
+        .unwrap_or_default();
+    Ok(component
+        .iter()
+        .filter_map(|name| notes.get(name).cloned())
+        .collect())
+}
+
+/// all notes by name and labels of all links by (from, to)
+async fn labelled_links(
+    db: DatabaseAsyncHandle,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+) -> Result<
+    (
+        HashMap<String, Note>,
+        HashMap<(String, String), Option<String>>,
+    ),
+    anyhow::Error,
+> {
+    let notes = db.lock().await.list(md_static, color_scheme).await?;
+    let labels = db
+        .lock()
+        .await
+        .all_labelled_links()
+        .await?
+        .into_iter()
+        .map(|link| ((link.from, link.to), link.label))
+        .collect();
+    let notes = notes.into_iter().map(|note| (note.name(), note)).collect();
+    Ok((notes, labels))
+}
+
+/// chain of notes in cycle, each labelled with the link, which leads to it from the
+/// previous one, closed by the first note
+fn cycle_tree(
+    cycle: &[Note],
+    labels: &HashMap<(String, String), Option<String>>,
+    color_scheme: ColorScheme,
+) -> Tree<NoteLinkTerm, Down> {
+    let first = cycle[0].name();
+    let terms = cycle.iter().enumerate().map(|(index, note)| {
+        let term = NoteLinkTerm::Note(note.clone());
+        if index == 0 {
+            return term;
+        }
+        let prev = cycle[index - 1].name();
+        labelled(term, labels.get(&(prev, note.name())), color_scheme)
+    });
+    let last = cycle[cycle.len() - 1].name();
+    let closing = labelled(
+        NoteLinkTerm::Cycle(first.clone(), color_scheme),
+        labels.get(&(last, first)),
+        color_scheme,
+    );
+    chain_tree(terms.chain(std::iter::once(closing)))
+}
+
+fn labelled(
+    term: NoteLinkTerm,
+    label: Option<&Option<String>>,
+    color_scheme: ColorScheme,
+) -> NoteLinkTerm {
+    match label.cloned().flatten() {
+        Some(label) => NoteLinkTerm::Labelled(Box::new(term), label, color_scheme),
+        None => term,
+    }
+}
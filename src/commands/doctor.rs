@@ -0,0 +1,213 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+use inquire::Confirm;
+
+use crate::{
+    config::color::ColorScheme,
+    database::DatabaseAsyncHandle,
+    front_matter,
+    highlight::MarkdownStatic,
+    note::{parse_heading_name, rewrite_heading, unique_name, Note},
+    print::format_two_tokens,
+};
+
+use super::import::markdown_files;
+
+/// `root` is notes' work dir, filenames of bodies are relative to it; front matter of
+/// bodies, which plain files storage keeps there, is neither checked nor touched
+pub(crate) async fn exec(
+    db: DatabaseAsyncHandle,
+    fix: bool,
+    root: &Path,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+) -> Result<String, anyhow::Error> {
+    let notes = db.lock().await.list(md_static, color_scheme).await?;
+    let mut taken_names = notes.iter().map(Note::name).collect::<HashSet<_>>();
+
+    let mut registered = HashSet::new();
+    let mut missing = vec![];
+    // e.g. not utf-8; such files are only reported, as no fix is safe for them
+    let mut unreadable = vec![];
+    let mut mismatched = vec![];
+    for note in &notes {
+        let Some(file_path) = note.file_path() else {
+            continue;
+        };
+        match front_matter::read_body(root.join(file_path)) {
+            Ok(body) => {
+                registered.insert(fs::canonicalize(root.join(file_path))?);
+                if parse_heading_name(&body).as_deref() != Some(note.name().as_str()) {
+                    mismatched.push((note.name(), file_path.clone(), body));
+                }
+            }
+            Err(err) if root.join(file_path).exists() => {
+                log::debug!("body of {} is unreadable: {:?}", note.name(), err);
+                registered.insert(fs::canonicalize(root.join(file_path))?);
+                unreadable.push(file_path.clone());
+            }
+            Err(err) => {
+                log::debug!("body of {} is missing: {:?}", note.name(), err);
+                missing.push(note.name());
+            }
+        }
+    }
+
+    let mut unregistered = vec![];
+    let mut empty = vec![];
+    let mut by_body: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    for file_path in markdown_files(root)? {
+        let canonical = fs::canonicalize(&file_path)?;
+        let body = match front_matter::read_body(&file_path) {
+            Ok(body) => body,
+            Err(err) => {
+                log::debug!("{:?} is unreadable: {:?}", file_path, err);
+                if !registered.contains(&canonical) {
+                    unreadable.push(registered_form(root, &file_path));
+                }
+                continue;
+            }
+        };
+        let file_path = registered_form(root, &file_path);
+        if !registered.contains(&canonical) {
+            unregistered.push((file_path.clone(), body.clone()));
+        }
+        if body.trim().is_empty() {
+            empty.push(file_path);
+        } else {
+            by_body.entry(body).or_default().push(file_path);
+        }
+    }
+    let mut duplicates = by_body
+        .into_values()
+        .filter(|paths| paths.len() > 1)
+        .collect::<Vec<_>>();
+    duplicates.sort();
+
+    let dangling = db.lock().await.find_dangling_stack_entries().await?;
+
+    for name in &missing {
+        eprintln!("{}", format_two_tokens("missing body:", name));
+    }
+    for file_path in &unreadable {
+        eprintln!(
+            "{}",
+            format_two_tokens("unreadable file:", &file_path.display().to_string())
+        );
+    }
+    for (file_path, _body) in &unregistered {
+        eprintln!(
+            "{}",
+            format_two_tokens("unregistered file:", &file_path.display().to_string())
+        );
+    }
+    for paths in &duplicates {
+        let paths = paths
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>();
+        eprintln!(
+            "{}",
+            format_two_tokens("duplicate files:", &paths.join(", "))
+        );
+    }
+    for file_path in &empty {
+        eprintln!(
+            "{}",
+            format_two_tokens("empty file:", &file_path.display().to_string())
+        );
+    }
+    for (stack, note) in &dangling {
+        eprintln!(
+            "{}",
+            format_two_tokens("dangling stack entry:", &format!("{} in {}", note, stack))
+        );
+    }
+    for (name, file_path, body) in &mismatched {
+        let heading = parse_heading_name(body).unwrap_or_default();
+        eprintln!(
+            "{}",
+            format_two_tokens(
+                "heading mismatch:",
+                &format!("{} ({}) has `{}`", name, file_path.display(), heading)
+            )
+        );
+    }
+
+    let issues = missing.len()
+        + unreadable.len()
+        + unregistered.len()
+        + duplicates.len()
+        + empty.len()
+        + dangling.len()
+        + mismatched.len();
+    if issues == 0 {
+        return Ok("no issues found".truecolor(0, 255, 255).to_string());
+    }
+
+    for name in missing {
+        if confirm(
+            fix,
+            &format!("convert `{}` with missing body to a tag", name),
+        )? {
+            db.lock().await.set_filename(&name, None).await?;
+            eprintln!("{}", format_two_tokens("converted to tag", &name));
+        }
+    }
+    for (file_path, body) in unregistered {
+        let name = parse_heading_name(&body).unwrap_or_else(|| {
+            file_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+        let name = unique_name(&name, &taken_names);
+        if confirm(
+            fix,
+            &format!("adopt `{}` as note `{}`", file_path.display(), name),
+        )? {
+            let note = Note::new(name.clone(), Some(file_path), color_scheme);
+            db.lock().await.save(&note).await?;
+            eprintln!("{}", format_two_tokens("adopted", &name));
+            taken_names.insert(name);
+        }
+    }
+    if !dangling.is_empty() && confirm(fix, "remove dangling stack entries")? {
+        db.lock().await.repair_stacks().await?;
+        eprintln!(
+            "{}",
+            format_two_tokens(
+                "removed dangling stack entries",
+                &dangling.len().to_string()
+            )
+        );
+    }
+    for (name, file_path, body) in mismatched {
+        if confirm(
+            fix,
+            &format!("rewrite heading of `{}` in {}", name, file_path.display()),
+        )? {
+            front_matter::write_body(root.join(&file_path), &rewrite_heading(&body, &name))?;
+            eprintln!("{}", format_two_tokens("rewritten heading of", &name));
+        }
+    }
+
+    Ok("success".truecolor(0, 255, 255).to_string())
+}
+
+/// `file_path` under `root` in the form bodies are registered with, e.g. `./dir/note.md`
+fn registered_form(root: &Path, file_path: &Path) -> PathBuf {
+    Path::new(".").join(file_path.strip_prefix(root).unwrap_or(file_path))
+}
+
+fn confirm(fix: bool, prompt: &str) -> Result<bool, anyhow::Error> {
+    if fix {
+        return Ok(true);
+    }
+    Ok(Confirm::new(prompt).with_default(false).prompt()?)
+}
@@ -0,0 +1,78 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+
+use crate::{
+    config::Storage,
+    database::{self, DatabaseAsyncHandle, Dump},
+    print::format_two_tokens,
+};
+
+pub(crate) async fn dump(
+    db: DatabaseAsyncHandle,
+    output: Option<PathBuf>,
+) -> Result<String, anyhow::Error> {
+    let dump = db.lock().await.dump().await?;
+    let serialized = serde_json::to_string_pretty(&dump)?;
+    match output {
+        Some(output) => {
+            fs::write(&output, serialized + "\n")?;
+            eprintln!(
+                "{}",
+                format_two_tokens(
+                    "dumped",
+                    &format!(
+                        "{} notes, {} links, {} stacks",
+                        dump.notes.len(),
+                        dump.links.len(),
+                        dump.stacks.len()
+                    )
+                )
+            );
+            Ok(format!("{}", output.display()))
+        }
+        None => Ok(serialized),
+    }
+}
+
+pub(crate) async fn restore(input: &Path, storage: Storage) -> Result<String, anyhow::Error> {
+    let dump: Dump = serde_json::from_str(&fs::read_to_string(input)?)?;
+    let problems = dump.validate();
+    if !problems.is_empty() {
+        for problem in &problems {
+            eprintln!("{}", format_two_tokens("invalid", problem));
+        }
+        return Err(anyhow::anyhow!(
+            "dump {} has {} integrity problems",
+            input.display(),
+            problems.len()
+        ));
+    }
+
+    let db = database::open(storage, true).await?;
+    db.lock().await.restore_dump(&dump).await?;
+
+    for note in &dump.notes {
+        if let Some(filename) = &note.filename {
+            if !Path::new(filename).exists() {
+                eprintln!("{}", format_two_tokens("missing body", filename));
+            }
+        }
+    }
+    eprintln!(
+        "{}",
+        format_two_tokens(
+            "restored",
+            &format!(
+                "{} notes, {} links, {} stacks",
+                dump.notes.len(),
+                dump.links.len(),
+                dump.stacks.len()
+            )
+        )
+    );
+    Ok("success".truecolor(0, 255, 255).to_string())
+}
@@ -1,19 +1,34 @@
 use std::time::Duration;
 
 use crate::{
-    commands::link::{link, link_noninteractive},
+    commands::link::{link, link_noninteractive, prompt_label},
     config::{color::ColorScheme, keymap, ExternalCommands, SurfParsing},
-    database::{Database, SqliteAsyncHandle},
+    database::DatabaseAsyncHandle,
     highlight::MarkdownStatic,
-    note::{Note, PreviewType},
+    note::{ListOrder, Note, PreviewCache, PreviewType},
     print::format_two_tokens,
     skim::explore::{Action, Iteration},
     Open,
 };
 
 use super::{
-    checkmark::checkmark_note, create, remove::remove, rename::rename, stack::stack_select,
-    surf::surf_note, unlink::unlink,
+    alias::edit_aliases,
+    checkmark::checkmark_note,
+    create,
+    cycles::component_of,
+    hygiene::{notes_in_report, Report},
+    journal::{redo_last, touched_notes, undo_last},
+    merge::merge,
+    path::path_to,
+    prop::filter_by_property,
+    remove::remove,
+    rename::rename,
+    search::search,
+    split::split,
+    stack::{pick_stack, stack_select},
+    surf::surf_note,
+    sync_links::sync_opened,
+    unlink::unlink,
 };
 use inquire::Select;
 use inquire::Text;
@@ -23,7 +38,7 @@ pub static GLOBAL_STACK: &str = "GLOBAL";
 
 #[allow(clippy::too_many_arguments)]
 pub(crate) async fn exec(
-    db: SqliteAsyncHandle,
+    db: DatabaseAsyncHandle,
     selected_note: Option<Vec<String>>,
     external_commands: ExternalCommands,
     surf_parsing: SurfParsing,
@@ -50,7 +65,14 @@ pub(crate) async fn exec(
     let mut preview_type = PreviewType::default();
 
     let mut nested_threshold = 1;
+    let mut stack = GLOBAL_STACK.to_string();
+    // lists keep order of their source, e.g. ranking of search, until one is picked
+    let mut list_order: Option<ListOrder> = None;
+    let previews = PreviewCache::new()?;
     loop {
+        if let Some(list_order) = list_order {
+            list_order.sort(&mut list, &db).await?;
+        }
         let (next_items, opened, preview_type_after) = iteration(
             db.clone(),
             list,
@@ -62,17 +84,31 @@ pub(crate) async fn exec(
             straight,
             nested_threshold,
             explore_bindings_map.clone(),
+            &previews,
         )
         .await?;
         preview_type = preview_type_after;
         list = next_items;
+        if opened.as_ref().is_some_and(Action::modifies_notes) {
+            previews.clear();
+        }
 
         match opened {
             Some(Action::Open(opened)) => {
+                db.lock().await.mark_opened(&opened.name()).await?;
                 opened.open(external_commands.open.clone())?;
                 eprintln!("{}", format_two_tokens("viewed", &opened.name()));
+                if surf_parsing.sync_wikilinks_on_open.0 {
+                    if let Err(err) =
+                        sync_opened(&opened, db.clone(), &surf_parsing, md_static, color_scheme)
+                            .await
+                    {
+                        eprintln!("sync links error: {:?}", err);
+                    }
+                }
             }
             Some(Action::OpenXDG(opened)) => {
+                db.lock().await.mark_opened(&opened.name()).await?;
                 opened.open_xdg()?;
                 eprintln!("{}", format_two_tokens("viewed xdg", &opened.name()));
             }
@@ -96,6 +132,7 @@ pub(crate) async fn exec(
             Some(Action::Checkmark(surfed)) => {
                 if let Err(err) = checkmark_note(
                     surfed,
+                    db.clone(),
                     &external_commands,
                     &surf_parsing,
                     md_static,
@@ -107,7 +144,8 @@ pub(crate) async fn exec(
                 }
             }
             Some(Action::Rename(opened)) => {
-                let note = rename(opened, db.clone(), md_static).await?;
+                let note =
+                    rename(opened, db.clone(), &surf_parsing, md_static, color_scheme).await?;
                 list = vec![note];
             }
 
@@ -177,7 +215,8 @@ pub(crate) async fn exec(
                 let to =
                     create::create(&new_name, db.clone(), is_tag, md_static, color_scheme).await?;
 
-                link_noninteractive(linked_from.clone(), to, db.clone(), straight).await?;
+                let label = prompt_label(db.clone()).await?;
+                link_noninteractive(linked_from.clone(), to, db.clone(), straight, label).await?;
                 // list = vec![linked_from];
             }
 
@@ -195,24 +234,28 @@ pub(crate) async fn exec(
 
             Some(Action::PushToStack(note)) => {
                 let name = &note.name();
-                if let Err(err) = db
-                    .lock()
-                    .await
-                    .push_note_to_stack(GLOBAL_STACK, &note.name())
-                    .await
-                {
-                    eprintln!("push to stack error: {:?}", err);
-                } else {
-                    println!(
-                        "{}",
-                        format_two_tokens("pushed ", &format!("{name} to {GLOBAL_STACK}"))
-                    );
+                match pick_stack(db.clone(), &stack, "push to stack").await {
+                    Ok(picked) => {
+                        stack = picked;
+                        if let Err(err) = db.lock().await.push_note_to_stack(&stack, name).await {
+                            eprintln!("push to stack error: {:?}", err);
+                        } else {
+                            println!(
+                                "{}",
+                                format_two_tokens("pushed ", &format!("{name} to {stack}"))
+                            );
+                        }
+                    }
+                    Err(err) => {
+                        eprintln!("push to stack error: {:?}", err);
+                    }
                 }
                 sleep(Duration::new(1, 0)).await;
             }
             Some(Action::SwitchToStack) => {
-                let next = stack_select(
+                let (next, stack_after) = stack_select(
                     db.clone(),
+                    stack.clone(),
                     list.clone(),
                     external_commands.clone(),
                     surf_parsing.clone(),
@@ -222,15 +265,248 @@ pub(crate) async fn exec(
                 )
                 .await?;
                 list = next;
+                stack = stack_after;
+            }
+            Some(Action::Search) => {
+                let query = Text::new("Enter full-text search query:").prompt()?;
+                match search(&query, db.clone(), md_static, color_scheme).await {
+                    Ok(found) if !found.is_empty() => {
+                        list = found;
+                    }
+                    Ok(_) => {
+                        println!("{}", format_two_tokens("no notes match", &query));
+                        sleep(Duration::new(1, 0)).await;
+                    }
+                    Err(err) => {
+                        eprintln!("search error: {:?}", err);
+                        sleep(Duration::new(1, 0)).await;
+                    }
+                }
+            }
+            Some(Action::FilterLinksByLabel(note)) => {
+                match links_by_label(&note, db.clone(), md_static, color_scheme, straight).await {
+                    Ok(Some(next)) => {
+                        list = next;
+                    }
+                    Ok(None) => {
+                        println!("{}", format_two_tokens("no links of", &note.name()));
+                        sleep(Duration::new(1, 0)).await;
+                    }
+                    Err(err) => {
+                        eprintln!("filter links by label error: {:?}", err);
+                    }
+                }
+            }
+            Some(Action::EditAliases(note)) => {
+                match edit_aliases(note, db.clone(), md_static, color_scheme).await {
+                    Ok(note) => {
+                        list = vec![note];
+                    }
+                    Err(err) => {
+                        eprintln!("edit aliases error: {:?}", err);
+                    }
+                }
+                sleep(Duration::new(1, 0)).await;
+            }
+            Some(Action::Merge(source)) => {
+                match merge(
+                    source,
+                    db.clone(),
+                    &external_commands,
+                    &surf_parsing,
+                    md_static,
+                    color_scheme,
+                )
+                .await
+                {
+                    Ok(target) => {
+                        list = vec![target];
+                    }
+                    Err(err) => {
+                        eprintln!("merge error: {:?}", err);
+                        sleep(Duration::new(1, 0)).await;
+                    }
+                }
+            }
+            Some(Action::Split(note)) => {
+                match split(note.clone(), db.clone(), md_static, color_scheme).await {
+                    Ok(children) if children.is_empty() => {}
+                    Ok(children) => {
+                        list = std::iter::once(note).chain(children).collect();
+                    }
+                    Err(err) => {
+                        eprintln!("split error: {:?}", err);
+                        sleep(Duration::new(1, 0)).await;
+                    }
+                }
+            }
+            Some(Action::Path(from)) => {
+                match path_to(
+                    from,
+                    db.clone(),
+                    &external_commands,
+                    &surf_parsing,
+                    md_static,
+                    color_scheme,
+                    straight,
+                    nested_threshold,
+                )
+                .await
+                {
+                    Ok(on_path) => {
+                        list = on_path;
+                    }
+                    Err(err) => {
+                        eprintln!("path error: {:?}", err);
+                    }
+                }
+                sleep(Duration::new(1, 0)).await;
+            }
+            Some(action @ (Action::Orphans | Action::Leaves | Action::Unreachable(..))) => {
+                let report = match action {
+                    Action::Orphans => Report::Orphans,
+                    Action::Leaves => Report::Leaves,
+                    Action::Unreachable(from) => Report::Unreachable(from.name()),
+                    _ => unreachable!("matched above"),
+                };
+                match notes_in_report(&report, db.clone(), md_static, color_scheme).await {
+                    Ok(notes) if notes.is_empty() => {
+                        println!("{}", format_two_tokens("no notes", &report.describe()));
+                        sleep(Duration::new(1, 0)).await;
+                    }
+                    Ok(notes) => {
+                        list = notes;
+                    }
+                    Err(err) => {
+                        eprintln!("report error: {:?}", err);
+                        sleep(Duration::new(1, 0)).await;
+                    }
+                }
+            }
+            Some(Action::Component(note)) => {
+                match component_of(&note, db.clone(), md_static, color_scheme).await {
+                    Ok(component) if component.is_empty() => {
+                        println!("{}", format_two_tokens("no cycles through", &note.name()));
+                        sleep(Duration::new(1, 0)).await;
+                    }
+                    Ok(component) => {
+                        list = component;
+                    }
+                    Err(err) => {
+                        eprintln!("cycles error: {:?}", err);
+                        sleep(Duration::new(1, 0)).await;
+                    }
+                }
+            }
+            Some(Action::FilterByProperty) => match filter_by_property(&list, db.clone()).await {
+                Ok(Some(filtered)) => {
+                    list = filtered;
+                }
+                Ok(None) => {
+                    println!("{}", format_two_tokens("no notes match", "property filter"));
+                    sleep(Duration::new(1, 0)).await;
+                }
+                Err(err) => {
+                    eprintln!("filter by property error: {:?}", err);
+                    sleep(Duration::new(1, 0)).await;
+                }
+            },
+            Some(Action::CycleListOrder) => {
+                let next_order = list_order.unwrap_or_default().toggle();
+                list_order = Some(next_order);
+                println!(
+                    "{}",
+                    format_two_tokens("ordered by", &next_order.to_string())
+                );
+                sleep(Duration::new(1, 0)).await;
+            }
+            Some(action @ (Action::Undo | Action::Redo)) => {
+                let (entry, verb) = match action {
+                    Action::Undo => (undo_last(db.clone()).await, "undo"),
+                    _ => (redo_last(db.clone()).await, "redo"),
+                };
+                match entry {
+                    Ok(Some(entry)) => {
+                        let touched =
+                            touched_notes(db.clone(), &entry, md_static, color_scheme).await;
+                        if !touched.is_empty() {
+                            list = touched;
+                        }
+                    }
+                    Ok(None) => {
+                        println!("{}", format_two_tokens("nothing to", verb));
+                    }
+                    Err(err) => {
+                        eprintln!("{} error: {:?}", verb, err);
+                    }
+                }
+                sleep(Duration::new(1, 0)).await;
             }
             _ => {}
         }
     }
 }
 
+async fn links_by_label(
+    note: &Note,
+    db: DatabaseAsyncHandle,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+    straight: bool,
+) -> Result<Option<Vec<Note>>, anyhow::Error> {
+    let forward = note
+        .fetch_labelled_forward_links(&db, md_static, color_scheme, straight)
+        .await?;
+    let backward = note
+        .fetch_labelled_backlinks(&db, md_static, color_scheme, straight)
+        .await?;
+
+    let mut options: Vec<(bool, Option<String>)> = vec![];
+    for (is_forward, links) in [(true, &forward), (false, &backward)] {
+        for (_note, label) in links {
+            let option = (is_forward, label.clone());
+            if !options.contains(&option) {
+                options.push(option);
+            }
+        }
+    }
+    if options.is_empty() {
+        return Ok(None);
+    }
+
+    let descriptions = options
+        .iter()
+        .map(|(is_forward, label)| {
+            let direction = if *is_forward {
+                "forward links"
+            } else {
+                "backlinks"
+            };
+            format!(
+                "{}: {}",
+                direction,
+                label.as_deref().unwrap_or("<no label>")
+            )
+        })
+        .collect::<Vec<_>>();
+    let picked = Select::new("filter links by label", descriptions)
+        .raw_prompt()?
+        .index;
+    let (is_forward, picked_label) = &options[picked];
+
+    let links = if *is_forward { forward } else { backward };
+    Ok(Some(
+        links
+            .into_iter()
+            .filter(|(_note, label)| label == picked_label)
+            .map(|(note, _label)| note)
+            .collect(),
+    ))
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn iteration(
-    db: SqliteAsyncHandle,
+    db: DatabaseAsyncHandle,
     list: Vec<Note>,
     external_commands: &ExternalCommands,
     surf_parsing: &SurfParsing,
@@ -240,6 +516,7 @@ pub async fn iteration(
     straight: bool,
     nested_threshold: usize,
     bindings_map: keymap::explore::Bindings,
+    previews: &PreviewCache,
 ) -> Result<(Vec<Note>, Option<Action>, PreviewType), anyhow::Error> {
     let out = Iteration::new(
         list.clone(),
@@ -252,6 +529,7 @@ pub async fn iteration(
         straight,
         nested_threshold,
         bindings_map,
+        previews.clone(),
     )
     .run()
     .await?;
@@ -279,6 +557,20 @@ pub async fn iteration(
         action @ Action::DecreaseUnlistedThreshold => (out.next_items, Some(action), preview_type),
         action @ Action::PushToStack(..) => (out.next_items, Some(action), preview_type),
         action @ Action::SwitchToStack => (out.next_items, Some(action), preview_type),
+        action @ Action::Search => (out.next_items, Some(action), preview_type),
+        action @ Action::FilterLinksByLabel(..) => (out.next_items, Some(action), preview_type),
+        action @ Action::CycleListOrder => (out.next_items, Some(action), preview_type),
+        action @ Action::Undo => (out.next_items, Some(action), preview_type),
+        action @ Action::Redo => (out.next_items, Some(action), preview_type),
+        action @ Action::EditAliases(..) => (out.next_items, Some(action), preview_type),
+        action @ Action::FilterByProperty => (out.next_items, Some(action), preview_type),
+        action @ Action::Merge(..) => (out.next_items, Some(action), preview_type),
+        action @ Action::Split(..) => (out.next_items, Some(action), preview_type),
+        action @ Action::Path(..) => (out.next_items, Some(action), preview_type),
+        action @ Action::Orphans => (out.next_items, Some(action), preview_type),
+        action @ Action::Leaves => (out.next_items, Some(action), preview_type),
+        action @ Action::Unreachable(..) => (out.next_items, Some(action), preview_type),
+        action @ Action::Component(..) => (out.next_items, Some(action), preview_type),
         Action::TogglePreview => (out.next_items, None, preview_type.toggle()),
     };
     Ok(res)
@@ -0,0 +1,313 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::{
+    config::{color::ColorScheme, SurfParsing},
+    database::DatabaseAsyncHandle,
+    graph::{cyclic_nodes, strongly_connected_components},
+    highlight::MarkdownStatic,
+    link::Link,
+    note::Note,
+    parse_cache::ParsedBody,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Format {
+    Dot,
+    Mermaid,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Details {
+    pub destinations: bool,
+    pub tasks: bool,
+}
+
+#[derive(Serialize)]
+struct ExportedDestination {
+    kind: &'static str,
+    description: String,
+    target: String,
+}
+
+#[derive(Serialize)]
+struct TaskCounts {
+    total: usize,
+    completed: usize,
+}
+
+#[derive(Serialize)]
+struct ExportedNote {
+    name: String,
+    kind: &'static str,
+    file: Option<String>,
+    in_cycle: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    destinations: Option<Vec<ExportedDestination>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tasks: Option<TaskCounts>,
+}
+
+#[derive(Serialize)]
+struct ExportedLink {
+    from: String,
+    to: String,
+    label: Option<String>,
+    in_cycle: bool,
+}
+
+#[derive(Serialize)]
+struct Export {
+    root: Option<String>,
+    direction: &'static str,
+    depth: Option<usize>,
+    notes: Vec<ExportedNote>,
+    links: Vec<ExportedLink>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn exec(
+    db: DatabaseAsyncHandle,
+    format: Format,
+    root: Option<String>,
+    up: bool,
+    depth: Option<usize>,
+    details: Details,
+    surf_parsing: &SurfParsing,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+) -> Result<String, anyhow::Error> {
+    let mut notes = match &root {
+        Some(name) => {
+            let root = db.lock().await.get(name, md_static, color_scheme).await?;
+            root.reachable_notes_within(db.clone(), md_static, color_scheme, !up, true, depth)
+                .await?
+        }
+        None => db.lock().await.list(md_static, color_scheme).await?,
+    };
+    notes.sort_by_key(Note::name);
+    let names = notes.iter().map(Note::name).collect::<Vec<_>>();
+    let included = names.iter().cloned().collect::<HashSet<_>>();
+
+    let links = db
+        .lock()
+        .await
+        .all_labelled_links()
+        .await?
+        .into_iter()
+        .filter(|link| included.contains(&link.from) && included.contains(&link.to))
+        .map(|link| (link.from, link.to, link.label))
+        .collect::<Vec<_>>();
+
+    let edges = links
+        .iter()
+        .map(|(from, to, _label)| (from.clone(), to.clone()))
+        .collect::<Vec<_>>();
+    let mut component_of = HashMap::new();
+    for (index, component) in strongly_connected_components(&names, &edges)
+        .into_iter()
+        .enumerate()
+    {
+        if component.len() > 1 {
+            for name in component {
+                component_of.insert(name, index);
+            }
+        }
+    }
+    let cyclic = cyclic_nodes(&names, &edges);
+    let link_in_cycle = |from: &String, to: &String| {
+        from == to
+            || matches!(
+                (component_of.get(from), component_of.get(to)),
+                (Some(a), Some(b)) if a == b
+            )
+    };
+
+    let mut exported_notes = vec![];
+    for note in &notes {
+        let destinations = if details.destinations {
+            let parsed = Link::parse(note, surf_parsing, color_scheme, &db).await?;
+            Some(
+                parsed
+                    .into_iter()
+                    .map(|link| ExportedDestination {
+                        kind: link.link.kind(),
+                        description: link.description.clone(),
+                        target: link.link.target(),
+                    })
+                    .collect(),
+            )
+        } else {
+            None
+        };
+        let tasks = if details.tasks {
+            let parsed = ParsedBody::of(note, surf_parsing, &db).await?.task_items;
+            Some(TaskCounts {
+                total: parsed.len(),
+                completed: parsed.iter().filter(|task| task.completed).count(),
+            })
+        } else {
+            None
+        };
+        let name = note.name();
+        let in_cycle = cyclic.contains(&name);
+        exported_notes.push(ExportedNote {
+            kind: if note.file_path().is_some() {
+                "note"
+            } else {
+                "tag"
+            },
+            file: note.file_path().map(|path| path.display().to_string()),
+            name,
+            in_cycle,
+            destinations,
+            tasks,
+        });
+    }
+    let exported_links = links
+        .into_iter()
+        .map(|(from, to, label)| ExportedLink {
+            in_cycle: link_in_cycle(&from, &to),
+            from,
+            to,
+            label,
+        })
+        .collect();
+
+    let export = Export {
+        root,
+        direction: if up { "up" } else { "down" },
+        depth,
+        notes: exported_notes,
+        links: exported_links,
+    };
+    let output = match format {
+        Format::Json => serde_json::to_string_pretty(&export)?,
+        Format::Dot => render_dot(&export),
+        Format::Mermaid => render_mermaid(&export),
+    };
+    Ok(output)
+}
+
+/// extra lines of node's label with counts of destinations by kind and of tasks
+fn summary_lines(note: &ExportedNote) -> Vec<String> {
+    let mut lines = vec![];
+    if let Some(destinations) = &note.destinations {
+        let mut counts: Vec<(&str, usize)> = vec![];
+        for destination in destinations {
+            match counts
+                .iter_mut()
+                .find(|(kind, _)| *kind == destination.kind)
+            {
+                Some((_, count)) => *count += 1,
+                None => counts.push((destination.kind, 1)),
+            }
+        }
+        if !counts.is_empty() {
+            let counts = counts
+                .into_iter()
+                .map(|(kind, count)| format!("{}: {}", kind, count))
+                .collect::<Vec<_>>();
+            lines.push(counts.join(", "));
+        }
+    }
+    if let Some(tasks) = &note.tasks {
+        if tasks.total > 0 {
+            lines.push(format!("tasks: {}/{}", tasks.completed, tasks.total));
+        }
+    }
+    lines
+}
+
+fn render_dot(export: &Export) -> String {
+    let escape = |input: &str| input.replace('\\', "\\\\").replace('"', "\\\"");
+    let mut output = vec!["digraph mds {".to_string()];
+    output.push("  node [shape=box];".to_string());
+    for note in &export.notes {
+        let mut label = vec![escape(&note.name)];
+        label.extend(summary_lines(note).iter().map(|line| escape(line)));
+        let mut attributes = vec![format!("label=\"{}\"", label.join("\\n"))];
+        if note.kind == "tag" {
+            attributes.push("shape=ellipse".to_string());
+        }
+        if note.in_cycle {
+            attributes.push("color=\"#d2b48c\"".to_string());
+            attributes.push("penwidth=2".to_string());
+        }
+        output.push(format!(
+            "  \"{}\" [{}];",
+            escape(&note.name),
+            attributes.join(", ")
+        ));
+    }
+    for link in &export.links {
+        let mut attributes = vec![];
+        if let Some(label) = &link.label {
+            attributes.push(format!("label=\"{}\"", escape(label)));
+        }
+        if link.in_cycle {
+            attributes.push("color=\"#d2b48c\"".to_string());
+        }
+        let attributes = if attributes.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", attributes.join(", "))
+        };
+        output.push(format!(
+            "  \"{}\" -> \"{}\"{};",
+            escape(&link.from),
+            escape(&link.to),
+            attributes
+        ));
+    }
+    output.push("}".to_string());
+    output.join("\n")
+}
+
+fn render_mermaid(export: &Export) -> String {
+    let escape = |input: &str| input.replace('"', "#quot;");
+    let ids = export
+        .notes
+        .iter()
+        .enumerate()
+        .map(|(index, note)| (note.name.as_str(), format!("n{}", index)))
+        .collect::<HashMap<_, _>>();
+
+    let mut output = vec!["flowchart TD".to_string()];
+    for note in &export.notes {
+        let mut label = vec![escape(&note.name)];
+        label.extend(summary_lines(note).iter().map(|line| escape(line)));
+        let label = label.join("<br/>");
+        let node = if note.kind == "tag" {
+            format!("([\"{}\"])", label)
+        } else {
+            format!("[\"{}\"]", label)
+        };
+        output.push(format!("  {}{}", ids[note.name.as_str()], node));
+    }
+    for link in &export.links {
+        let arrow = match &link.label {
+            Some(label) => format!("-->|\"{}\"|", escape(label)),
+            None => "-->".to_string(),
+        };
+        output.push(format!(
+            "  {} {} {}",
+            ids[link.from.as_str()],
+            arrow,
+            ids[link.to.as_str()]
+        ));
+    }
+    let cyclic = export
+        .notes
+        .iter()
+        .filter(|note| note.in_cycle)
+        .map(|note| ids[note.name.as_str()].clone())
+        .collect::<Vec<_>>();
+    if !cyclic.is_empty() {
+        output.push("  classDef cycle stroke:#d2b48c,stroke-width:2px".to_string());
+        output.push(format!("  class {} cycle", cyclic.join(",")));
+    }
+    output.join("\n")
+}
@@ -0,0 +1,103 @@
+use std::collections::HashSet;
+
+use crate::{
+    config::{color::ColorScheme, keymap, ExternalCommands, SurfParsing},
+    database::DatabaseAsyncHandle,
+    graph::{sinks, sources, unreachable},
+    highlight::MarkdownStatic,
+    note::Note,
+    print::format_two_tokens,
+};
+
+#[derive(Debug, Clone)]
+pub(crate) enum Report {
+    /// notes, which no other note links to
+    Orphans,
+    /// notes, which link to no other note
+    Leaves,
+    /// notes, which can't be reached by following links from this note
+    Unreachable(String),
+}
+
+impl Report {
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            Self::Orphans => "orphans".to_string(),
+            Self::Leaves => "leaves".to_string(),
+            Self::Unreachable(from) => format!("unreachable from `{}`", from),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn exec(
+    db: DatabaseAsyncHandle,
+    report: Report,
+    external_commands: ExternalCommands,
+    surf_parsing: SurfParsing,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+    bindings_map: keymap::surf::Bindings,
+    chck_bindings_map: keymap::checkmark::Bindings,
+    stack_bindings_map: keymap::stack::Bindings,
+    explore_bindings_map: keymap::explore::Bindings,
+) -> Result<String, anyhow::Error> {
+    let notes = notes_in_report(&report, db.clone(), md_static, color_scheme).await?;
+    if notes.is_empty() {
+        return Ok(format_two_tokens("no notes", &report.describe()));
+    }
+    for note in &notes {
+        println!("{}", note);
+    }
+    let names = notes.iter().map(Note::name).collect();
+
+    super::explore::exec(
+        db,
+        Some(names),
+        external_commands,
+        surf_parsing,
+        md_static,
+        color_scheme,
+        bindings_map,
+        chck_bindings_map,
+        stack_bindings_map,
+        explore_bindings_map,
+    )
+    .await
+}
+
+/// notes in `report`, sorted by name; links of a note to itself are ignored
+pub(crate) async fn notes_in_report(
+    report: &Report,
+    db: DatabaseAsyncHandle,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+) -> Result<Vec<Note>, anyhow::Error> {
+    let mut notes = db.lock().await.list(md_static, color_scheme).await?;
+    notes.sort_by_key(Note::name);
+    let names = notes.iter().map(Note::name).collect::<Vec<_>>();
+
+    let edges = db
+        .lock()
+        .await
+        .all_labelled_links()
+        .await?
+        .into_iter()
+        .map(|link| (link.from, link.to))
+        .collect::<Vec<_>>();
+
+    let selected: HashSet<String> = match report {
+        Report::Orphans => sources(&names, &edges),
+        Report::Leaves => sinks(&names, &edges),
+        Report::Unreachable(from) => {
+            let from = db.lock().await.get(from, md_static, color_scheme).await?;
+            unreachable(&names, &edges, &from.name())
+        }
+    }
+    .into_iter()
+    .collect();
+    Ok(notes
+        .into_iter()
+        .filter(|note| selected.contains(&note.name()))
+        .collect())
+}
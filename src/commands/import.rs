@@ -0,0 +1,214 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+
+use crate::{
+    config::{color::ColorScheme, SurfParsing},
+    database::DatabaseAsyncHandle,
+    highlight::MarkdownStatic,
+    note::{parse_heading_name, unique_name, Note},
+    print::format_two_tokens,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OnConflict {
+    Suffix,
+    Skip,
+}
+
+struct Candidate {
+    name: String,
+    file_path: PathBuf,
+    registered_as: String,
+    body: String,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn exec(
+    dir: &Path,
+    db: DatabaseAsyncHandle,
+    surf_parsing: &SurfParsing,
+    with_links: bool,
+    dry_run: bool,
+    on_conflict: OnConflict,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+) -> Result<String, anyhow::Error> {
+    let notes = db.lock().await.list(md_static, color_scheme).await?;
+    let mut taken_names = notes.iter().map(Note::name).collect::<HashSet<_>>();
+    let mut known_files: HashMap<PathBuf, String> = HashMap::new();
+    for note in &notes {
+        if let Some(Ok(path)) = note.file_path().map(fs::canonicalize) {
+            known_files.insert(path, note.name());
+        }
+    }
+
+    let work_dir = fs::canonicalize(".")?;
+    let mut candidates = vec![];
+    let mut unreadable = 0;
+    for file_path in markdown_files(dir)? {
+        let canonical = fs::canonicalize(&file_path)?;
+        if let Some(name) = known_files.get(&canonical) {
+            eprintln!(
+                "{}",
+                format_two_tokens(
+                    "already registered:",
+                    &format!("{} as {}", file_path.display(), name)
+                )
+            );
+            continue;
+        }
+        let body = match fs::read_to_string(&file_path) {
+            Ok(body) => body,
+            Err(err) => {
+                log::debug!("{:?} is unreadable: {:?}", file_path, err);
+                eprintln!(
+                    "{}",
+                    format_two_tokens("skipped, unreadable:", &file_path.display().to_string())
+                );
+                unreadable += 1;
+                continue;
+            }
+        };
+        let name = parse_heading_name(&body).unwrap_or_else(|| {
+            file_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+        let name = if taken_names.contains(&name) {
+            match on_conflict {
+                OnConflict::Skip => {
+                    eprintln!(
+                        "{}",
+                        format_two_tokens(
+                            "skipped, name is taken:",
+                            &format!("{} ({})", name, file_path.display())
+                        )
+                    );
+                    continue;
+                }
+                OnConflict::Suffix => unique_name(&name, &taken_names),
+            }
+        } else {
+            name
+        };
+        taken_names.insert(name.clone());
+
+        // bodies inside of work dir are registered relative to it, like the ones created by `mds`
+        let registered_as = match canonical.strip_prefix(&work_dir) {
+            Ok(relative) => Path::new("./").join(relative),
+            Err(_) => canonical.clone(),
+        };
+        known_files.insert(canonical, name.clone());
+        candidates.push(Candidate {
+            name,
+            file_path,
+            registered_as: registered_as.to_string_lossy().to_string(),
+            body,
+        });
+    }
+
+    let mut links = vec![];
+    if with_links {
+        for candidate in &candidates {
+            for to in referenced_notes(candidate, &known_files, surf_parsing) {
+                if to != candidate.name && !links.contains(&(candidate.name.clone(), to.clone())) {
+                    links.push((candidate.name.clone(), to));
+                }
+            }
+        }
+    }
+
+    let verb = if dry_run { "would import" } else { "imported" };
+    for candidate in &candidates {
+        if !dry_run {
+            let note = Note::new(
+                candidate.name.clone(),
+                Some(PathBuf::from(&candidate.registered_as)),
+                color_scheme,
+            );
+            db.lock().await.save(&note).await?;
+        }
+        eprintln!(
+            "{}",
+            format_two_tokens(
+                verb,
+                &format!("{} as {}", candidate.file_path.display(), candidate.name)
+            )
+        );
+    }
+    let verb = if dry_run { "would link" } else { "linked" };
+    for (from, to) in &links {
+        if !dry_run {
+            db.lock().await.insert_link(from, to, true, None).await?;
+        }
+        eprintln!(
+            "{}",
+            format_two_tokens(verb, &format!("{} -> {}", from, to))
+        );
+    }
+
+    let summary = format!(
+        "{} notes, {} links, {} unreadable files skipped",
+        candidates.len(),
+        links.len(),
+        unreadable
+    );
+    if dry_run {
+        return Ok(format_two_tokens("dry run:", &summary));
+    }
+    eprintln!("{}", format_two_tokens("imported", &summary));
+    Ok("success".truecolor(0, 255, 255).to_string())
+}
+
+/// names of notes, whose bodies are referenced from candidate by relative
+/// `[description](other.md)` links
+fn referenced_notes(
+    candidate: &Candidate,
+    known_files: &HashMap<PathBuf, String>,
+    surf_parsing: &SurfParsing,
+) -> Vec<String> {
+    let base = candidate.file_path.parent().unwrap_or(Path::new("."));
+    surf_parsing
+        .markdown_reference_link_regex
+        .0
+        .captures_iter(&candidate.body)
+        .filter_map(|captures| {
+            let url = &captures["url"];
+            if surf_parsing.url_regex.0.is_match(url) || Path::new(url).is_absolute() {
+                return None;
+            }
+            let url = url.split('#').next().unwrap_or(url);
+            let target = fs::canonicalize(base.join(url)).ok()?;
+            known_files.get(&target).cloned()
+        })
+        .collect()
+}
+
+/// `*.md` files in `dir` and its subdirectories, hidden entries excluded
+pub(crate) fn markdown_files(dir: &Path) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let mut files = vec![];
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+    for path in entries {
+        let hidden = path
+            .file_name()
+            .is_none_or(|name| name.to_string_lossy().starts_with('.'));
+        if hidden {
+            continue;
+        }
+        if path.is_dir() {
+            files.extend(markdown_files(&path)?);
+        } else if path.extension().is_some_and(|ext| ext == "md") {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
@@ -1,10 +1,8 @@
-use std::path::PathBuf;
-
 use colored::Colorize;
 
-use crate::database::Sqlite;
+use crate::{config::Storage, database};
 
-pub(crate) async fn exec(dir: PathBuf) -> Result<String, anyhow::Error> {
-    Sqlite::new(true, dir).await?;
+pub(crate) async fn exec(storage: Storage) -> Result<String, anyhow::Error> {
+    database::open(storage, true).await?;
     Ok("initialized db".truecolor(0, 255, 255).to_string())
 }
@@ -0,0 +1,112 @@
+use colored::Colorize;
+
+use crate::{
+    config::color::ColorScheme,
+    database::{DatabaseAsyncHandle, JournalEntry, StepReport},
+    highlight::MarkdownStatic,
+    note::Note,
+    print::format_two_tokens,
+};
+
+pub(crate) async fn undo(db: DatabaseAsyncHandle) -> Result<String, anyhow::Error> {
+    match undo_last(db).await? {
+        Some(_) => Ok("success".truecolor(0, 255, 255).to_string()),
+        None => Err(anyhow::anyhow!("nothing to undo")),
+    }
+}
+
+pub(crate) async fn redo(db: DatabaseAsyncHandle) -> Result<String, anyhow::Error> {
+    match redo_last(db).await? {
+        Some(_) => Ok("success".truecolor(0, 255, 255).to_string()),
+        None => Err(anyhow::anyhow!("nothing to redo")),
+    }
+}
+
+pub(crate) async fn log(db: DatabaseAsyncHandle, limit: usize) -> Result<String, anyhow::Error> {
+    let entries = db.lock().await.journal(limit).await?;
+    let lines = entries
+        .into_iter()
+        .map(|entry| {
+            let applied_at = chrono::DateTime::from_timestamp(entry.applied_at, 0)
+                .map(|time| {
+                    time.with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string()
+                })
+                .unwrap_or_default();
+            let state = if entry.undone { "undone" } else { "applied" };
+            format!(
+                "{:>5} {} {:>7} {}",
+                entry.id,
+                applied_at,
+                state,
+                entry.operation.to_string().truecolor(255, 0, 255)
+            )
+        })
+        .collect::<Vec<_>>();
+    Ok(lines.join("\n"))
+}
+
+fn print_report(report: &StepReport) {
+    for link in &report.skipped_links {
+        eprintln!(
+            "{}",
+            format_two_tokens(
+                "skipped link to a missing note",
+                &format!("{} -> {}", link.from, link.to)
+            )
+        );
+    }
+    for filename in &report.edited_files {
+        eprintln!(
+            "{}",
+            format_two_tokens("edited since, left as is", filename)
+        );
+    }
+}
+
+pub(crate) async fn undo_last(
+    db: DatabaseAsyncHandle,
+) -> Result<Option<JournalEntry>, anyhow::Error> {
+    let step = db.lock().await.undo().await?;
+    let Some((entry, report)) = step else {
+        return Ok(None);
+    };
+    print_report(&report);
+    eprintln!(
+        "{}",
+        format_two_tokens("undone", &entry.operation.to_string())
+    );
+    Ok(Some(entry))
+}
+
+pub(crate) async fn redo_last(
+    db: DatabaseAsyncHandle,
+) -> Result<Option<JournalEntry>, anyhow::Error> {
+    let step = db.lock().await.redo().await?;
+    let Some((entry, report)) = step else {
+        return Ok(None);
+    };
+    print_report(&report);
+    eprintln!(
+        "{}",
+        format_two_tokens("redone", &entry.operation.to_string())
+    );
+    Ok(Some(entry))
+}
+
+/// notes, touched by journal entry, which still exist after it was undone or redone
+pub(crate) async fn touched_notes(
+    db: DatabaseAsyncHandle,
+    entry: &JournalEntry,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+) -> Vec<Note> {
+    let mut result = vec![];
+    for name in entry.operation.notes() {
+        if let Ok(note) = db.lock().await.get(&name, md_static, color_scheme).await {
+            result.push(note);
+        }
+    }
+    result
+}
@@ -1,8 +1,9 @@
 use colored::Colorize;
+use inquire::{CustomUserError, Text};
 
 use crate::{
     config::{color::ColorScheme, ExternalCommands, SurfParsing},
-    database::{Database, SqliteAsyncHandle},
+    database::DatabaseAsyncHandle,
     highlight::MarkdownStatic,
     note::{Note, PreviewType},
     print::format_two_tokens,
@@ -10,7 +11,7 @@ use crate::{
 };
 
 pub(crate) async fn exec(
-    db: SqliteAsyncHandle,
+    db: DatabaseAsyncHandle,
     external_commands: ExternalCommands,
     surf_parsing: SurfParsing,
     md_static: MarkdownStatic,
@@ -56,7 +57,7 @@ pub(crate) async fn exec(
 #[allow(clippy::too_many_arguments)]
 pub(crate) async fn link(
     from: Note,
-    db: SqliteAsyncHandle,
+    db: DatabaseAsyncHandle,
     external_commands: &ExternalCommands,
     surf_parsing: &SurfParsing,
     md_static: MarkdownStatic,
@@ -84,25 +85,46 @@ pub(crate) async fn link(
     .run()
     .await?;
 
-    link_noninteractive(from, to, db, straight).await?;
+    let label = prompt_label(db.clone()).await?;
+    link_noninteractive(from, to, db, straight, label).await?;
     Ok(())
 }
 
+pub(crate) async fn prompt_label(db: DatabaseAsyncHandle) -> Result<Option<String>, anyhow::Error> {
+    let labels = db.lock().await.list_link_labels().await?;
+    let suggester = move |input: &str| -> Result<Vec<String>, CustomUserError> {
+        Ok(labels
+            .iter()
+            .filter(|label| label.starts_with(input))
+            .cloned()
+            .collect())
+    };
+    let label = Text::new("Enter link label (empty for none):")
+        .with_autocomplete(suggester)
+        .prompt()?;
+    let label = label.trim();
+    Ok((!label.is_empty()).then(|| label.to_string()))
+}
+
 pub(crate) async fn link_noninteractive(
     from: Note,
     to: Note,
-    db: SqliteAsyncHandle,
+    db: DatabaseAsyncHandle,
     straight: bool,
+    label: Option<String>,
 ) -> Result<(), anyhow::Error> {
     db.lock()
         .await
-        .insert_link(&from.name(), &to.name(), straight)
+        .insert_link(&from.name(), &to.name(), straight, label.as_deref())
         .await?;
+    let label = label
+        .map(|label| format!(" [{}]", label))
+        .unwrap_or_default();
     eprintln!(
         "{}",
         format_two_tokens(
             "linked: ",
-            &format!("\"{}\" -> \"{}\"", from.name(), to.name())
+            &format!("\"{}\" -> \"{}\"{}", from.name(), to.name(), label)
         )
     );
     Ok(())
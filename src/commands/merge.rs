@@ -0,0 +1,154 @@
+use colored::Colorize;
+use inquire::Confirm;
+
+use crate::{
+    config::{color::ColorScheme, ExternalCommands, SurfParsing},
+    database::{BodyRewrite, DatabaseAsyncHandle},
+    front_matter,
+    highlight::MarkdownStatic,
+    note::{parse_heading_name, Note, PreviewType},
+    print::format_two_tokens,
+    skim::open::Iteration,
+};
+
+pub(crate) async fn exec(
+    db: DatabaseAsyncHandle,
+    external_commands: ExternalCommands,
+    surf_parsing: SurfParsing,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+) -> Result<String, anyhow::Error> {
+    let list = db.lock().await.list(md_static, color_scheme).await?;
+    let straight = true;
+    let multi = false;
+    let nested_threshold = 1;
+
+    let source = Iteration::new(
+        "merge from".to_string(),
+        list,
+        db.clone(),
+        multi,
+        PreviewType::Details,
+        external_commands.clone(),
+        surf_parsing.clone(),
+        md_static,
+        color_scheme,
+        straight,
+        nested_threshold,
+    )
+    .run()
+    .await?;
+
+    merge(
+        source,
+        db,
+        &external_commands,
+        &surf_parsing,
+        md_static,
+        color_scheme,
+    )
+    .await?;
+
+    Ok("success".truecolor(0, 255, 255).to_string())
+}
+
+/// body of `source` without its heading, appended to body of `target` under a heading
+fn merged_body(target_body: &str, source_name: &str, source_body: &str) -> String {
+    let mut lines = source_body.lines().collect::<Vec<_>>();
+    if let Some(first) = lines.first() {
+        if parse_heading_name(first).as_deref() == Some(source_name) {
+            lines.remove(0);
+        }
+    }
+    let appended = lines.join("\n");
+    format!(
+        "{}\n\n## {}\n\n{}\n",
+        target_body.trim_end(),
+        source_name,
+        appended.trim()
+    )
+}
+
+pub(crate) async fn merge(
+    source: Note,
+    db: DatabaseAsyncHandle,
+    external_commands: &ExternalCommands,
+    surf_parsing: &SurfParsing,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+) -> Result<Note, anyhow::Error> {
+    let name: String = source.name().chars().take(40).collect();
+    let list = db
+        .lock()
+        .await
+        .list(md_static, color_scheme)
+        .await?
+        .into_iter()
+        .filter(|note| note.name() != source.name())
+        .collect::<Vec<_>>();
+    let target = Iteration::new(
+        format!("merge {} into", name),
+        list,
+        db.clone(),
+        false,
+        PreviewType::Details,
+        external_commands.clone(),
+        surf_parsing.clone(),
+        md_static,
+        color_scheme,
+        true,
+        1,
+    )
+    .run()
+    .await?;
+
+    let rewrite = match (source.file_path(), target.file_path()) {
+        (Some(source_path), Some(target_path)) => {
+            let source_body = front_matter::read_body(source_path)?;
+            let target_body = front_matter::read_body(target_path)?;
+            Some(BodyRewrite {
+                filename: target_path.to_string_lossy().to_string(),
+                after: merged_body(&target_body, &source.name(), &source_body),
+                before: target_body,
+            })
+        }
+        (Some(_), None) => {
+            return Err(anyhow::anyhow!(
+                "`{}` is a tag, body of `{}` would be lost",
+                target.name(),
+                source.name()
+            ));
+        }
+        (None, _) => None,
+    };
+
+    let prompt = format!("Merge `{}` into `{}`?", source.name(), target.name());
+    if !Confirm::new(&prompt).with_default(false).prompt()? {
+        return Ok(target);
+    }
+    if let Some(rewrite) = &rewrite {
+        front_matter::write_body(&rewrite.filename, &rewrite.after)?;
+    }
+    let merged = db
+        .lock()
+        .await
+        .merge_notes(&source.name(), &target.name(), rewrite.as_ref())
+        .await;
+    if let Err(err) = merged {
+        if let Some(rewrite) = &rewrite {
+            let _ = front_matter::write_body(&rewrite.filename, &rewrite.before);
+        }
+        return Err(err.into());
+    }
+
+    eprintln!(
+        "{}",
+        format_two_tokens("merged", &format!("{} -> {}", source.name(), target.name()))
+    );
+    let target = db
+        .lock()
+        .await
+        .get(&target.name(), md_static, color_scheme)
+        .await?;
+    Ok(target)
+}
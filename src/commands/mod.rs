@@ -1,18 +1,36 @@
 pub mod debug_cfg;
+pub mod doctor;
+pub mod dump;
 pub mod init_db;
 
+pub mod alias;
 pub mod create;
+pub mod import;
+pub mod merge;
 pub mod remove;
 pub mod rename;
+pub mod split;
 
 pub mod link;
+pub mod sync_links;
 pub mod unlink;
 
 pub mod explore;
 pub mod surf;
 
+pub mod cycles;
+pub mod export;
+pub mod hygiene;
+pub mod path;
 pub mod print;
+pub mod prop;
+pub mod search;
 pub mod select;
+pub mod stats;
+
+pub mod journal;
+pub mod trash;
 
 pub mod checkmark;
 pub mod stack;
+pub mod stacks;
@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+
+use bidir_termtree::{Down, Tree};
+use inquire::Select;
+
+use crate::{
+    config::{color::ColorScheme, keymap, ExternalCommands, SurfParsing},
+    database::DatabaseAsyncHandle,
+    graph::{shortest_path, simple_paths},
+    highlight::MarkdownStatic,
+    note::{Note, NoteLinkTerm, PreviewType},
+    print::format_two_tokens,
+    skim::open::Iteration,
+};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct PathQuery {
+    /// links may be followed backwards too
+    pub undirected: bool,
+    /// all simple paths up to this number of links, instead of a single shortest one
+    pub all_up_to: Option<usize>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn exec(
+    db: DatabaseAsyncHandle,
+    from: &str,
+    to: &str,
+    query: PathQuery,
+    external_commands: ExternalCommands,
+    surf_parsing: SurfParsing,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+    bindings_map: keymap::surf::Bindings,
+    chck_bindings_map: keymap::checkmark::Bindings,
+    stack_bindings_map: keymap::stack::Bindings,
+    explore_bindings_map: keymap::explore::Bindings,
+) -> Result<String, anyhow::Error> {
+    let from = db.lock().await.get(from, md_static, color_scheme).await?;
+    let to = db.lock().await.get(to, md_static, color_scheme).await?;
+
+    let on_path = print_paths(&from, &to, query, db.clone(), md_static, color_scheme, true).await?;
+    let names = on_path.iter().map(Note::name).collect();
+
+    super::explore::exec(
+        db,
+        Some(names),
+        external_commands,
+        surf_parsing,
+        md_static,
+        color_scheme,
+        bindings_map,
+        chck_bindings_map,
+        stack_bindings_map,
+        explore_bindings_map,
+    )
+    .await
+}
+
+/// asks for the other end of path and whether links may be followed backwards
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn path_to(
+    from: Note,
+    db: DatabaseAsyncHandle,
+    external_commands: &ExternalCommands,
+    surf_parsing: &SurfParsing,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+    straight: bool,
+    nested_threshold: usize,
+) -> Result<Vec<Note>, anyhow::Error> {
+    let name: String = from.name().chars().take(40).collect();
+
+    let hint = format!("path from {}", name);
+    let list = db.lock().await.list(md_static, color_scheme).await?;
+    let to = Iteration::new(
+        hint,
+        list,
+        db.clone(),
+        false,
+        PreviewType::Details,
+        external_commands.clone(),
+        surf_parsing.clone(),
+        md_static,
+        color_scheme,
+        straight,
+        nested_threshold,
+    )
+    .run()
+    .await?;
+
+    let options: Vec<&str> = vec!["directed", "undirected"];
+    let undirected = Select::new("follow links", options).prompt()? == "undirected";
+    let query = PathQuery {
+        undirected,
+        all_up_to: None,
+    };
+    print_paths(&from, &to, query, db, md_static, color_scheme, straight).await
+}
+
+/// prints paths from `from` to `to` as term trees, and returns notes on them in order
+/// of appearance; `straight: false` follows links backwards
+pub(crate) async fn print_paths(
+    from: &Note,
+    to: &Note,
+    query: PathQuery,
+    db: DatabaseAsyncHandle,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+    straight: bool,
+) -> Result<Vec<Note>, anyhow::Error> {
+    let notes = db.lock().await.list(md_static, color_scheme).await?;
+    let labels = db
+        .lock()
+        .await
+        .all_labelled_links()
+        .await?
+        .into_iter()
+        .map(|link| ((link.from, link.to), link.label))
+        .collect::<HashMap<_, _>>();
+
+    let mut edges = labels
+        .keys()
+        .map(|(from, to)| match straight {
+            true => (from.clone(), to.clone()),
+            false => (to.clone(), from.clone()),
+        })
+        .collect::<Vec<_>>();
+    if query.undirected {
+        let reversed = edges
+            .iter()
+            .map(|(from, to)| (to.clone(), from.clone()))
+            .collect::<Vec<_>>();
+        edges.extend(reversed);
+    }
+
+    let paths = match query.all_up_to {
+        Some(max_len) => simple_paths(&edges, &from.name(), &to.name(), max_len),
+        None => shortest_path(&edges, &from.name(), &to.name())
+            .into_iter()
+            .collect(),
+    };
+    if paths.is_empty() {
+        return Err(anyhow::anyhow!(
+            "no path from `{}` to `{}`",
+            from.name(),
+            to.name()
+        ));
+    }
+
+    let by_name = notes
+        .into_iter()
+        .map(|note| (note.name(), note))
+        .collect::<HashMap<_, _>>();
+    let mut on_path: Vec<Note> = vec![];
+    for (index, path) in paths.iter().enumerate() {
+        if paths.len() > 1 {
+            eprintln!(
+                "{}",
+                format_two_tokens(
+                    &format!("path {}:", index + 1),
+                    &format!("{} links", path.len() - 1)
+                )
+            );
+        }
+        let path = path
+            .iter()
+            .filter_map(|name| by_name.get(name).cloned())
+            .collect::<Vec<_>>();
+        println!("{}", path_tree(&path, &labels, color_scheme));
+        for note in path {
+            if !on_path.contains(&note) {
+                on_path.push(note);
+            }
+        }
+    }
+    Ok(on_path)
+}
+
+/// chain of notes, each labelled with the link, which leads to it from the previous one;
+/// links, followed backwards, are marked with `←`
+fn path_tree(
+    path: &[Note],
+    labels: &HashMap<(String, String), Option<String>>,
+    color_scheme: ColorScheme,
+) -> Tree<NoteLinkTerm, Down> {
+    let terms = path.iter().enumerate().map(|(index, note)| {
+        let term = NoteLinkTerm::Note(note.clone());
+        if index == 0 {
+            return term;
+        }
+        let (prev, next) = (path[index - 1].name(), note.name());
+        let label = match labels.get(&(prev.clone(), next.clone())) {
+            Some(label) => label.clone(),
+            None => match labels.get(&(next, prev)).cloned().flatten() {
+                Some(label) => Some(format!("← {}", label)),
+                None => Some("←".to_string()),
+            },
+        };
+        match label {
+            Some(label) => NoteLinkTerm::Labelled(Box::new(term), label, color_scheme),
+            None => term,
+        }
+    });
+    chain_tree(terms)
+}
+
+/// each term is the only child of previous one
+pub(crate) fn chain_tree(
+    terms: impl IntoIterator<Item = NoteLinkTerm>,
+) -> Tree<NoteLinkTerm, Down> {
+    let mut trees = terms.into_iter().map(Tree::new).collect::<Vec<_>>();
+    let mut tree = trees.pop().expect("chain has at least one term");
+    while let Some(mut parent) = trees.pop() {
+        parent.push(tree);
+        tree = parent;
+    }
+    tree
+}
@@ -2,7 +2,7 @@ use std::collections::HashSet;
 
 use crate::{
     config::{color::ColorScheme, ExternalCommands, SurfParsing},
-    database::{Database, SqliteAsyncHandle},
+    database::DatabaseAsyncHandle,
     highlight::MarkdownStatic,
     note::PreviewType,
     print::format_two_tokens,
@@ -11,7 +11,7 @@ use crate::{
 use colored::Colorize;
 
 pub(crate) async fn exec(
-    db: SqliteAsyncHandle,
+    db: DatabaseAsyncHandle,
     external_commands: ExternalCommands,
     surf_parsing: SurfParsing,
     name: Option<String>,
@@ -48,7 +48,6 @@ pub(crate) async fn exec(
             0,
             nested_threshold,
             HashSet::new(),
-            external_commands,
             surf_parsing,
             db,
             md_static,
@@ -0,0 +1,104 @@
+use colored::Colorize;
+use inquire::{CustomUserError, Text};
+
+use crate::{
+    config::color::ColorScheme, database::DatabaseAsyncHandle, highlight::MarkdownStatic,
+    note::Note, print::format_two_tokens,
+};
+
+pub(crate) async fn set(
+    db: DatabaseAsyncHandle,
+    name: &str,
+    key: &str,
+    value: Option<&str>,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+) -> Result<String, anyhow::Error> {
+    let key = key.trim();
+    if key.is_empty() || key.contains('=') {
+        return Err(anyhow::anyhow!("invalid property key `{}`", key));
+    }
+    let note = db.lock().await.get(name, md_static, color_scheme).await?;
+    db.lock()
+        .await
+        .set_property(&note.name(), key, value)
+        .await?;
+    let change = match value {
+        Some(value) => format!("{}: {}={}", note.name(), key, value),
+        None => format!("{}: {}", note.name(), key),
+    };
+    let verb = if value.is_some() { "set" } else { "unset" };
+    eprintln!("{}", format_two_tokens(verb, &change));
+    Ok("success".truecolor(0, 255, 255).to_string())
+}
+
+pub(crate) async fn get(
+    db: DatabaseAsyncHandle,
+    name: &str,
+    key: Option<&str>,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+) -> Result<String, anyhow::Error> {
+    let note = db.lock().await.get(name, md_static, color_scheme).await?;
+    let properties = db.lock().await.properties(&note.name()).await?;
+    match key {
+        Some(key) => properties.get(key).cloned().ok_or(anyhow::anyhow!(
+            "`{}` has no property `{}`",
+            note.name(),
+            key
+        )),
+        None => {
+            let lines = properties
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect::<Vec<_>>();
+            Ok(lines.join("\n"))
+        }
+    }
+}
+
+/// `key=value` matches notes having the value, bare `key` (or `key=`) matches any value
+fn parse_filter(input: &str) -> Option<(String, Option<String>)> {
+    let (key, value) = match input.split_once('=') {
+        Some((key, value)) => (key.trim(), Some(value.trim().to_string())),
+        None => (input.trim(), None),
+    };
+    let value = value.filter(|value| !value.is_empty());
+    (!key.is_empty()).then(|| (key.to_string(), value))
+}
+
+pub(crate) async fn filter_by_property(
+    list: &[Note],
+    db: DatabaseAsyncHandle,
+) -> Result<Option<Vec<Note>>, anyhow::Error> {
+    let keys = db.lock().await.list_property_keys().await?;
+    if keys.is_empty() {
+        return Ok(None);
+    }
+    let suggester = move |input: &str| -> Result<Vec<String>, CustomUserError> {
+        Ok(keys
+            .iter()
+            .filter(|key| !input.contains('=') && key.starts_with(input))
+            .map(|key| format!("{}=", key))
+            .collect())
+    };
+    let input = Text::new("Enter property filter (key=value or key):")
+        .with_autocomplete(suggester)
+        .prompt()?;
+    let (key, value) = match parse_filter(&input) {
+        Some(filter) => filter,
+        None => return Ok(None),
+    };
+
+    let matching = db
+        .lock()
+        .await
+        .find_by_property(&key, value.as_deref())
+        .await?;
+    let filtered = list
+        .iter()
+        .filter(|note| matching.contains(&note.name()))
+        .cloned()
+        .collect::<Vec<_>>();
+    Ok((!filtered.is_empty()).then_some(filtered))
+}
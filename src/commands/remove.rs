@@ -1,8 +1,6 @@
-use std::fs;
-
 use crate::{
     config::{color::ColorScheme, ExternalCommands, SurfParsing},
-    database::{Database, SqliteAsyncHandle},
+    database::DatabaseAsyncHandle,
     highlight::MarkdownStatic,
     note::{Note, PreviewType},
     print::format_two_tokens,
@@ -13,7 +11,7 @@ use colored::Colorize;
 use inquire::Confirm;
 
 pub(crate) async fn exec(
-    db: SqliteAsyncHandle,
+    db: DatabaseAsyncHandle,
     external_commands: ExternalCommands,
     surf_parsing: SurfParsing,
     md_static: MarkdownStatic,
@@ -45,7 +43,7 @@ pub(crate) async fn exec(
 }
 
 pub(crate) async fn remove(
-    db: SqliteAsyncHandle,
+    db: DatabaseAsyncHandle,
     note: Note,
     confirm: bool,
 ) -> Result<bool, anyhow::Error> {
@@ -59,15 +57,13 @@ pub(crate) async fn remove(
             }
         }
     }
+    // body is moved to trash dir along with a snapshot of links and stacks
     db.lock().await.remove_note(&note).await?;
 
-    if let Some(file_path) = note.file_path() {
-        fs::remove_file(file_path)?;
-    }
     eprintln!(
         "{}",
         format_two_tokens(
-            "removed ",
+            "moved to trash ",
             &format!("{}, {:?}", note.name(), note.file_path())
         )
     );
@@ -1,17 +1,24 @@
+use std::{
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
 use crate::{
     config::{color::ColorScheme, ExternalCommands, SurfParsing},
-    database::{Database, SqliteAsyncHandle},
+    database::{BodyRewrite, DatabaseAsyncHandle},
+    front_matter,
     highlight::MarkdownStatic,
-    note::{Note, PreviewType},
+    note::{parse_heading_name, rewrite_heading, Note, PreviewType},
     print::format_two_tokens,
     skim::open::Iteration,
 };
 use colored::Colorize;
-use inquire::Text;
+use inquire::{Confirm, Text};
 use syntect::easy::HighlightLines;
 
 pub(crate) async fn exec(
-    db: SqliteAsyncHandle,
+    db: DatabaseAsyncHandle,
     external_commands: ExternalCommands,
     surf_parsing: SurfParsing,
 
@@ -31,7 +38,7 @@ pub(crate) async fn exec(
         multi,
         PreviewType::Details,
         external_commands.clone(),
-        surf_parsing,
+        surf_parsing.clone(),
         md_static,
         color_scheme,
         straight,
@@ -40,22 +47,53 @@ pub(crate) async fn exec(
     .run()
     .await?;
 
-    rename(note, db, md_static).await?;
+    rename(note, db, &surf_parsing, md_static, color_scheme).await?;
 
     Ok("success".truecolor(0, 255, 255).to_string())
 }
 
 pub(crate) async fn rename(
     mut note: Note,
-    db: SqliteAsyncHandle,
+    db: DatabaseAsyncHandle,
+    surf_parsing: &SurfParsing,
     md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
 ) -> Result<Note, anyhow::Error> {
     let new_name = Text::new("Enter new note's name:")
         .with_initial_value(&note.name())
         .prompt()?;
 
     let prev_name = note.name();
-    db.lock().await.rename_note(&note, &new_name).await?;
+    let notes = db.lock().await.list(md_static, color_scheme).await?;
+    let mut rewrites = find_rewrites(&note, &new_name, &notes, surf_parsing);
+    if !rewrites.is_empty() {
+        for rewrite in &rewrites {
+            print_diff(rewrite);
+        }
+        let prompt = format!(
+            "Rewrite references to `{}` in {} files?",
+            prev_name,
+            rewrites.len()
+        );
+        if !Confirm::new(&prompt).with_default(true).prompt()? {
+            rewrites.clear();
+        }
+    }
+
+    for rewrite in &rewrites {
+        front_matter::write_body(&rewrite.filename, &rewrite.after)?;
+    }
+    let renamed = db
+        .lock()
+        .await
+        .rename_note(&note, &new_name, &rewrites)
+        .await;
+    if let Err(err) = renamed {
+        for rewrite in &rewrites {
+            let _ = front_matter::write_body(&rewrite.filename, &rewrite.before);
+        }
+        return Err(err.into());
+    }
     let mut highlighter = HighlightLines::new(md_static.1, md_static.2);
     note.rename(&new_name, &mut highlighter, md_static);
 
@@ -63,5 +101,122 @@ pub(crate) async fn rename(
         "{}",
         format_two_tokens("renamed", &format!("{} -> {}", prev_name, new_name))
     );
+    if !rewrites.is_empty() {
+        eprintln!(
+            "{}",
+            format_two_tokens("rewritten", &format!("{} files", rewrites.len()))
+        );
+    }
     Ok(note)
 }
+
+/// changed lines, compared pairwise, and the rest of the longer text, if a rewrite
+/// changed the number of lines
+fn print_diff(rewrite: &BodyRewrite) {
+    eprintln!("{}", rewrite.filename.truecolor(0, 255, 255));
+    let before = rewrite.before.lines().collect::<Vec<_>>();
+    let after = rewrite.after.lines().collect::<Vec<_>>();
+    for number in 0..std::cmp::max(before.len(), after.len()) {
+        let (before, after) = (before.get(number), after.get(number));
+        if before == after {
+            continue;
+        }
+        if let Some(before) = before {
+            eprintln!("{:>5} {}", number + 1, format!("- {}", before).red());
+        }
+        if let Some(after) = after {
+            eprintln!("{:>5} {}", number + 1, format!("+ {}", after).green());
+        }
+    }
+}
+
+/// spans of `body` to be replaced with new name: `[[old name]]` wikilinks and descriptions
+/// of `[old name](file.md)` links to the file of renamed note
+fn reference_spans(
+    body: &str,
+    prev_name: &str,
+    note_dir: &Path,
+    renamed_file: Option<&PathBuf>,
+    surf_parsing: &SurfParsing,
+) -> Vec<Range<usize>> {
+    let mut spans = vec![];
+    for captures in surf_parsing.wikilink_regex.0.captures_iter(body) {
+        if let Some(name) = captures.name("name") {
+            if name.as_str().trim() == prev_name {
+                spans.push(name.range());
+            }
+        }
+    }
+
+    let renamed_file = renamed_file.and_then(|file| fs::canonicalize(file).ok());
+    if let Some(renamed_file) = renamed_file {
+        let regex = &surf_parsing.markdown_reference_link_regex.0;
+        for captures in regex.captures_iter(body) {
+            let (Some(description), Some(url)) =
+                (captures.name("description"), captures.name("url"))
+            else {
+                continue;
+            };
+            if description.as_str().trim() != prev_name {
+                continue;
+            }
+            let target = note_dir.join(url.as_str().trim());
+            if fs::canonicalize(target).is_ok_and(|target| target == renamed_file) {
+                spans.push(description.range());
+            }
+        }
+    }
+    spans.sort_by_key(|span| span.start);
+    spans
+}
+
+/// bodies with references to `renamed` and its own heading, rewritten to `new_name`
+fn find_rewrites(
+    renamed: &Note,
+    new_name: &str,
+    notes: &[Note],
+    surf_parsing: &SurfParsing,
+) -> Vec<BodyRewrite> {
+    let prev_name = renamed.name();
+    let prev_name = prev_name.as_str();
+    let mut rewrites = vec![];
+    for note in notes {
+        let Some(file_path) = note.file_path() else {
+            continue;
+        };
+        let Ok(before) = front_matter::read_body(file_path) else {
+            continue;
+        };
+        let note_dir = file_path.parent().unwrap_or(Path::new("."));
+
+        let mut after = String::with_capacity(before.len());
+        let mut last = 0;
+        for span in reference_spans(
+            &before,
+            prev_name,
+            note_dir,
+            renamed.file_path(),
+            surf_parsing,
+        ) {
+            if span.start < last {
+                continue;
+            }
+            after.push_str(&before[last..span.start]);
+            after.push_str(new_name);
+            last = span.end;
+        }
+        after.push_str(&before[last..]);
+
+        if note.name() == prev_name && parse_heading_name(&after).as_deref() == Some(prev_name) {
+            after = rewrite_heading(&after, new_name);
+        }
+        if after != before {
+            rewrites.push(BodyRewrite {
+                filename: file_path.to_string_lossy().to_string(),
+                before,
+                after,
+            });
+        }
+    }
+    rewrites
+}
@@ -0,0 +1,40 @@
+use crate::{
+    config::color::ColorScheme, database::DatabaseAsyncHandle, highlight::MarkdownStatic,
+    note::Note, print::format_two_tokens,
+};
+
+pub(crate) async fn exec(
+    query: &str,
+    db: DatabaseAsyncHandle,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+) -> Result<String, anyhow::Error> {
+    let found = search(query, db, md_static, color_scheme).await?;
+    if found.is_empty() {
+        eprintln!("{}", format_two_tokens("no notes match", query));
+    }
+
+    Ok(found
+        .iter()
+        .map(|note| note.name())
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+pub(crate) async fn search(
+    query: &str,
+    db: DatabaseAsyncHandle,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+) -> Result<Vec<Note>, anyhow::Error> {
+    let mut lock = db.lock().await;
+    let refreshed = lock.refresh_search_index().await?;
+    if refreshed > 0 {
+        eprintln!(
+            "{}",
+            format_two_tokens("reindexed", &format!("{refreshed} modified notes"))
+        );
+    }
+    let found = lock.search(query, md_static, color_scheme).await?;
+    Ok(found)
+}
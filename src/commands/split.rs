@@ -0,0 +1,239 @@
+use std::{collections::HashSet, fmt::Display, fs};
+
+use colored::Colorize;
+use comrak::{
+    nodes::{AstNode, NodeValue},
+    parse_document, Arena, ComrakOptions,
+};
+use inquire::MultiSelect;
+use syntect::easy::HighlightLines;
+
+use crate::{
+    config::{color::ColorScheme, ExternalCommands, SurfParsing},
+    database::{BodyRewrite, DatabaseAsyncHandle},
+    front_matter,
+    highlight::MarkdownStatic,
+    note::{parse_heading_name, unique_name, Note, PreviewType},
+    print::format_two_tokens,
+    skim::open::Iteration,
+};
+
+pub(crate) async fn exec(
+    db: DatabaseAsyncHandle,
+    external_commands: ExternalCommands,
+    surf_parsing: SurfParsing,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+) -> Result<String, anyhow::Error> {
+    let list = db.lock().await.list(md_static, color_scheme).await?;
+    let straight = true;
+    let multi = false;
+    let nested_threshold = 1;
+
+    let note = Iteration::new(
+        "split".to_string(),
+        list,
+        db.clone(),
+        multi,
+        PreviewType::Details,
+        external_commands,
+        surf_parsing,
+        md_static,
+        color_scheme,
+        straight,
+        nested_threshold,
+    )
+    .run()
+    .await?;
+
+    split(note, db, md_static, color_scheme).await?;
+
+    Ok("success".truecolor(0, 255, 255).to_string())
+}
+
+/// section of a body under a heading, spanning lines `start..end` (0-based)
+#[derive(Debug, Clone)]
+struct Section {
+    level: u8,
+    title: String,
+    start: usize,
+    end: usize,
+}
+
+impl Display for Section {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} (lines {}-{})",
+            "#".repeat(self.level as usize),
+            self.title,
+            self.start + 1,
+            self.end
+        )
+    }
+}
+
+fn heading_text<'a>(node: &'a AstNode<'a>, text: &mut String) {
+    match &node.data.borrow().value {
+        NodeValue::Text(literal) => text.push_str(literal),
+        NodeValue::Code(code) => text.push_str(&code.literal),
+        NodeValue::SoftBreak | NodeValue::LineBreak => text.push(' '),
+        _ => {}
+    }
+    for child in node.children() {
+        heading_text(child, text);
+    }
+}
+
+/// top level headings of `body`, except for the `# 💖 name` one, each section ending
+/// right before the next heading of the same or a higher level
+fn sections(body: &str) -> Vec<Section> {
+    let arena = Arena::new();
+    let root = parse_document(&arena, body, &ComrakOptions::default());
+    let title = parse_heading_name(body);
+
+    let mut headings = vec![];
+    for node in root.children() {
+        if let NodeValue::Heading(heading) = &node.data.borrow().value {
+            let mut text = String::new();
+            heading_text(node, &mut text);
+            let start = node.data.borrow().sourcepos.start.line.saturating_sub(1);
+            headings.push((heading.level, text.trim().to_string(), start));
+        }
+    }
+
+    let total = body.lines().count();
+    let mut result = vec![];
+    for (index, (level, text, start)) in headings.iter().enumerate() {
+        let is_title = index == 0
+            && *level == 1
+            && title.as_deref() == Some(text.trim_start_matches('💖').trim());
+        if is_title || text.is_empty() {
+            continue;
+        }
+        let end = headings[index + 1..]
+            .iter()
+            .find(|(next_level, ..)| next_level <= level)
+            .map(|(.., next_start)| *next_start)
+            .unwrap_or(total);
+        result.push(Section {
+            level: *level,
+            title: text.clone(),
+            start: *start,
+            end,
+        });
+    }
+    result
+}
+
+/// picked sections, except for the ones nested into other picked sections
+fn outermost(mut picked: Vec<Section>) -> Vec<Section> {
+    picked.sort_by_key(|section| section.start);
+    let mut result: Vec<Section> = vec![];
+    for section in picked {
+        match result.last() {
+            Some(last) if section.start < last.end => {}
+            _ => result.push(section),
+        }
+    }
+    result
+}
+
+/// extracts sections under headings, picked in prompt, into new notes, linked from `note`;
+/// returns created notes
+pub(crate) async fn split(
+    note: Note,
+    db: DatabaseAsyncHandle,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+) -> Result<Vec<Note>, anyhow::Error> {
+    let Some(file_path) = note.file_path() else {
+        return Err(anyhow::anyhow!(
+            "`{}` is a tag, it has no body",
+            note.name()
+        ));
+    };
+    let body = front_matter::read_body(file_path)?;
+    let candidates = sections(&body);
+    if candidates.is_empty() {
+        return Err(anyhow::anyhow!("no headings to split `{}` by", note.name()));
+    }
+
+    let picked = MultiSelect::new("pick sections to split out", candidates).prompt()?;
+    let picked = outermost(picked);
+    if picked.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut taken_names = HashSet::new();
+    for existing in db.lock().await.list(md_static, color_scheme).await? {
+        taken_names.insert(existing.name());
+        taken_names.extend(existing.aliases().iter().cloned());
+    }
+
+    let lines = body.lines().collect::<Vec<_>>();
+    let mut highlighter = HighlightLines::new(md_static.1, md_static.2);
+    let mut children = vec![];
+    for section in &picked {
+        let name = unique_name(&section.title, &taken_names);
+        taken_names.insert(name.clone());
+        let child = Note::init(name, false, &mut highlighter, md_static, color_scheme);
+        let content = lines[section.start + 1..section.end].join("\n");
+        let child_body = format!("{}\n\n{}\n", Note::heading(&child.name()), content.trim());
+        if let Some(child_path) = child.file_path() {
+            fs::write(child_path, child_body)?;
+        }
+        children.push(child);
+    }
+
+    let mut rewritten = lines
+        .iter()
+        .map(|line| line.to_string())
+        .collect::<Vec<_>>();
+    for (section, child) in picked.iter().zip(&children).rev() {
+        let link = format!(
+            "[{}]({})",
+            child.name(),
+            child
+                .file_path()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default()
+        );
+        let mut replacement = vec![link];
+        if section.end < lines.len() {
+            replacement.push(String::new());
+        }
+        rewritten.splice(section.start..section.end, replacement);
+    }
+    let mut rewritten = rewritten.join("\n");
+    rewritten.push('\n');
+    let rewrite = BodyRewrite {
+        filename: file_path.to_string_lossy().to_string(),
+        before: body,
+        after: rewritten,
+    };
+    front_matter::write_body(file_path, &rewrite.after)?;
+
+    let saved = db
+        .lock()
+        .await
+        .split_note(&note.name(), &children, Some(&rewrite))
+        .await;
+    if let Err(err) = saved {
+        let _ = front_matter::write_body(file_path, &rewrite.before);
+        for child in &children {
+            if let Some(child_path) = child.file_path() {
+                let _ = fs::remove_file(child_path);
+            }
+        }
+        return Err(err.into());
+    }
+
+    for child in &children {
+        eprintln!(
+            "{}",
+            format_two_tokens("split out", &format!("{} -> {}", note.name(), child.name()))
+        );
+    }
+    Ok(children)
+}
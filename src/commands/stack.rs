@@ -1,20 +1,21 @@
 use std::time::Duration;
 
+use inquire::Select;
 use tokio::time::sleep;
 
 use crate::{
     config::{color::ColorScheme, keymap, ExternalCommands, SurfParsing},
-    database::{Database, SqliteAsyncHandle},
+    database::DatabaseAsyncHandle,
     highlight::MarkdownStatic,
     note::{Note, PreviewType},
     print::format_two_tokens,
     skim::stack_sequential::Action,
 };
 
-use super::explore::GLOBAL_STACK;
-
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn exec(
-    db: SqliteAsyncHandle,
+    db: DatabaseAsyncHandle,
+    stack: String,
     input_items_from_explore: Vec<Note>,
     external_commands: ExternalCommands,
     surf_parsing: SurfParsing,
@@ -22,8 +23,13 @@ pub(crate) async fn exec(
     color_scheme: ColorScheme,
     bindings_map: keymap::stack::Bindings,
 ) -> Result<String, anyhow::Error> {
-    let notes = stack_select(
+    let stacks = db.lock().await.list_stacks().await?;
+    if !stacks.contains(&stack) {
+        return Err(anyhow::anyhow!("no stack `{}`", stack));
+    }
+    let (notes, _stack) = stack_select(
         db,
+        stack,
         input_items_from_explore,
         external_commands,
         surf_parsing,
@@ -38,15 +44,34 @@ pub(crate) async fn exec(
         .unwrap_or("".to_string()))
 }
 
+pub(crate) async fn pick_stack(
+    db: DatabaseAsyncHandle,
+    current: &str,
+    hint: &str,
+) -> Result<String, anyhow::Error> {
+    let mut stacks = db.lock().await.list_stacks().await?;
+    if let Some(index) = stacks.iter().position(|stack| stack == current) {
+        let current = stacks.remove(index);
+        stacks.insert(0, current);
+    }
+    match stacks.len() {
+        0 => Err(anyhow::anyhow!("no stacks found")),
+        1 => Ok(stacks.remove(0)),
+        _ => Ok(Select::new(hint, stacks).prompt()?),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn stack_select(
-    db: SqliteAsyncHandle,
+    db: DatabaseAsyncHandle,
+    mut stack: String,
     input_items_from_explore: Vec<Note>,
     external_commands: ExternalCommands,
     surf_parsing: SurfParsing,
     md_static: MarkdownStatic,
     color_scheme: ColorScheme,
     bindings_map: keymap::stack::Bindings,
-) -> Result<Vec<Note>, anyhow::Error> {
+) -> Result<(Vec<Note>, String), anyhow::Error> {
     let straight = true;
     let multi = true;
     let nested_threshold = 1;
@@ -56,10 +81,10 @@ pub(crate) async fn stack_select(
         let list = db
             .lock()
             .await
-            .select_from_stack(GLOBAL_STACK, md_static, color_scheme)
+            .select_from_stack(&stack, md_static, color_scheme)
             .await?;
         let action = crate::skim::stack_sequential::Iteration::new(
-            format!("stack; {GLOBAL_STACK}"),
+            format!("stack; {stack}"),
             input_items_from_explore.clone(),
             list,
             db.clone(),
@@ -90,14 +115,18 @@ pub(crate) async fn stack_select(
                             .join(", ")
                     )
                 );
-                return Ok(notes);
+                return Ok((notes, stack));
             }
             Action::Return(notes) => {
                 println!(
                     "{}",
                     format_two_tokens("returning to previous", "selection in explore")
                 );
-                return Ok(notes);
+                return Ok((notes, stack));
+            }
+            Action::SelectStack => {
+                preselected_item = None;
+                stack = pick_stack(db.clone(), &stack, "select stack").await?;
             }
             Action::TogglePreview => {
                 preselected_item = None;
@@ -106,42 +135,39 @@ pub(crate) async fn stack_select(
             Action::Pop(note) => {
                 preselected_item = None;
                 let name = note.name();
-                db.lock()
-                    .await
-                    .pop_note_from_stack(GLOBAL_STACK, &name)
-                    .await?;
+                db.lock().await.pop_note_from_stack(&stack, &name).await?;
                 println!(
                     "{}",
-                    format_two_tokens("popped ", &format!("{name} from {GLOBAL_STACK}"))
+                    format_two_tokens("popped ", &format!("{name} from {stack}"))
                 );
                 sleep(Duration::new(0, 500_000_000)).await;
             }
             Action::MoveTopmost(note) => {
                 preselected_item = None;
                 let name = note.name();
-                db.lock().await.move_to_topmost(GLOBAL_STACK, &name).await?;
+                db.lock().await.move_to_topmost(&stack, &name).await?;
                 println!(
                     "{}",
-                    format_two_tokens("moved to topmost ", &format!("{name} in {GLOBAL_STACK}"))
+                    format_two_tokens("moved to topmost ", &format!("{name} in {stack}"))
                 );
                 sleep(Duration::new(0, 500_000_000)).await;
             }
             Action::SwapWithAbove(note) => {
                 let name = note.name();
-                db.lock().await.swap_with_above(GLOBAL_STACK, &name).await?;
+                db.lock().await.swap_with_above(&stack, &name).await?;
                 preselected_item = Some(note.name());
                 println!(
                     "{}",
-                    format_two_tokens("swapped with above ", &format!("{name} in {GLOBAL_STACK}"))
+                    format_two_tokens("swapped with above ", &format!("{name} in {stack}"))
                 );
             }
             Action::SwapWithBelow(note) => {
                 let name = note.name();
-                db.lock().await.swap_with_below(GLOBAL_STACK, &name).await?;
+                db.lock().await.swap_with_below(&stack, &name).await?;
                 preselected_item = Some(note.name());
                 println!(
                     "{}",
-                    format_two_tokens("swapped with below ", &format!("{name} in {GLOBAL_STACK}"))
+                    format_two_tokens("swapped with below ", &format!("{name} in {stack}"))
                 );
             }
         }
@@ -0,0 +1,57 @@
+use colored::Colorize;
+
+use crate::{database::DatabaseAsyncHandle, print::format_two_tokens};
+
+use super::explore::GLOBAL_STACK;
+
+pub(crate) async fn list(db: DatabaseAsyncHandle) -> Result<String, anyhow::Error> {
+    let stacks = db.lock().await.list_stacks().await?;
+    Ok(stacks.join("\n"))
+}
+
+pub(crate) async fn create(db: DatabaseAsyncHandle, name: &str) -> Result<String, anyhow::Error> {
+    db.lock().await.create_stack(name).await?;
+    eprintln!("{}", format_two_tokens("created stack", name));
+    Ok("success".truecolor(0, 255, 255).to_string())
+}
+
+pub(crate) async fn rename(
+    db: DatabaseAsyncHandle,
+    name: &str,
+    new_name: &str,
+) -> Result<String, anyhow::Error> {
+    check_not_default(name)?;
+    check_exists(&db, name).await?;
+    db.lock().await.rename_stack(name, new_name).await?;
+    eprintln!(
+        "{}",
+        format_two_tokens("renamed stack", &format!("{} -> {}", name, new_name))
+    );
+    Ok("success".truecolor(0, 255, 255).to_string())
+}
+
+pub(crate) async fn delete(db: DatabaseAsyncHandle, name: &str) -> Result<String, anyhow::Error> {
+    check_not_default(name)?;
+    check_exists(&db, name).await?;
+    db.lock().await.remove_stack(name).await?;
+    eprintln!("{}", format_two_tokens("deleted stack", name));
+    Ok("success".truecolor(0, 255, 255).to_string())
+}
+
+fn check_not_default(name: &str) -> Result<(), anyhow::Error> {
+    if name == GLOBAL_STACK {
+        return Err(anyhow::anyhow!(
+            "`{}` is the default stack and cannot be renamed or deleted",
+            GLOBAL_STACK
+        ));
+    }
+    Ok(())
+}
+
+async fn check_exists(db: &DatabaseAsyncHandle, name: &str) -> Result<(), anyhow::Error> {
+    let stacks = db.lock().await.list_stacks().await?;
+    if !stacks.iter().any(|stack| stack == name) {
+        return Err(anyhow::anyhow!("no stack `{}`", name));
+    }
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::{
+    config::{color::ColorScheme, SurfParsing},
+    database::DatabaseAsyncHandle,
+    highlight::MarkdownStatic,
+    stats::Stats,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Format {
+    Table,
+    Json,
+}
+
+pub(crate) async fn exec(
+    db: DatabaseAsyncHandle,
+    format: Format,
+    root: Option<String>,
+    up: bool,
+    surf_parsing: &SurfParsing,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+) -> Result<String, anyhow::Error> {
+    let root = match root {
+        Some(name) => Some(db.lock().await.get(&name, md_static, color_scheme).await?),
+        None => None,
+    };
+    let stats = Stats::collect(
+        db,
+        root.as_ref(),
+        !up,
+        surf_parsing,
+        md_static,
+        color_scheme,
+    )
+    .await?;
+    let output = match format {
+        Format::Json => serde_json::to_string_pretty(&stats)?,
+        Format::Table => stats.table(),
+    };
+    Ok(output)
+}
@@ -4,10 +4,10 @@ use tokio::time::sleep;
 
 use crate::{
     config::{color::ColorScheme, keymap, ExternalCommands, SurfParsing},
-    database::{Database, SqliteAsyncHandle},
+    database::DatabaseAsyncHandle,
     highlight::MarkdownStatic,
     link::Link,
-    note::{Note, PreviewType},
+    note::{Note, PreviewCache, PreviewType},
     print::format_two_tokens,
     skim::explore::Action,
     skim::surf::Action as SurfAction,
@@ -18,7 +18,7 @@ use crate::{
 use super::explore::iteration;
 
 pub(crate) async fn exec(
-    db: SqliteAsyncHandle,
+    db: DatabaseAsyncHandle,
     surf: SurfParsing,
     external_commands: ExternalCommands,
     md_static: MarkdownStatic,
@@ -31,6 +31,7 @@ pub(crate) async fn exec(
     let mut preview_type = PreviewType::default();
     let nested_threshold = 1;
     let straight = true;
+    let previews = PreviewCache::new()?;
     let note = loop {
         let (next_items, opened, preview_type_after) = iteration(
             db.clone(),
@@ -43,6 +44,7 @@ pub(crate) async fn exec(
             straight,
             nested_threshold,
             explore_bindings_map.clone(),
+            &previews,
         )
         .await?;
         preview_type = preview_type_after;
@@ -71,7 +73,7 @@ pub(crate) async fn exec(
 #[allow(clippy::too_many_arguments)]
 pub(crate) async fn surf_note(
     note: Note,
-    db: SqliteAsyncHandle,
+    db: DatabaseAsyncHandle,
     external_commands: &ExternalCommands,
     surf: &SurfParsing,
     md_static: MarkdownStatic,
@@ -83,11 +85,10 @@ pub(crate) async fn surf_note(
         let all_vec = note
             .reachable_notes(db.clone(), md_static, color_scheme, straight, true)
             .await?;
-        let links: std::io::Result<Vec<_>> = all_vec
-            .into_iter()
-            .map(|v| Link::parse(&v, surf, color_scheme))
-            .collect();
-        let links: Vec<_> = links?.into_iter().flatten().collect();
+        let mut links = vec![];
+        for v in &all_vec {
+            links.extend(Link::parse(v, surf, color_scheme, &db).await?);
+        }
         let action = SurfIteration::new(
             links,
             false,
@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+
+use colored::Colorize;
+
+use crate::{
+    config::{color::ColorScheme, SurfParsing},
+    database::DatabaseAsyncHandle,
+    front_matter,
+    highlight::MarkdownStatic,
+    note::Note,
+    print::format_two_tokens,
+};
+
+#[derive(Debug, Default)]
+pub(crate) struct SyncReport {
+    pub linked: Vec<String>,
+    pub unresolved: Vec<String>,
+    pub stale: Vec<String>,
+    /// links, not created by `sync-links`, which aren't mentioned in body either
+    pub unmentioned: Vec<String>,
+    pub pruned: bool,
+}
+
+impl SyncReport {
+    fn print(&self, from: &str) {
+        for to in &self.linked {
+            eprintln!(
+                "{}",
+                format_two_tokens("linked", &format!("{} -> {}", from, to))
+            );
+        }
+        for name in &self.unresolved {
+            eprintln!(
+                "{}",
+                format_two_tokens("unresolved", &format!("[[{}]] in {}", name, from))
+            );
+        }
+        let verb = if self.pruned { "pruned" } else { "stale" };
+        for to in &self.stale {
+            eprintln!(
+                "{}",
+                format_two_tokens(verb, &format!("{} -> {}", from, to))
+            );
+        }
+        for to in &self.unmentioned {
+            eprintln!(
+                "{}",
+                format_two_tokens("unmentioned", &format!("{} -> {}", from, to))
+            );
+        }
+    }
+}
+
+/// names of `[[wikilinks]]` in order of first occurrence
+pub(crate) fn parse_wikilinks(body: &str, surf_parsing: &SurfParsing) -> Vec<String> {
+    let mut names: Vec<String> = vec![];
+    for captures in surf_parsing.wikilink_regex.0.captures_iter(body) {
+        if let Some(name) = captures.name("name") {
+            let name = name.as_str().trim();
+            if !name.is_empty() && !names.iter().any(|known| known == name) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names
+}
+
+/// names of `notes` by themselves and by their aliases; exact name wins over an alias,
+/// as in name lookups of database
+pub(crate) fn resolved_names(notes: &[Note]) -> HashMap<String, String> {
+    let mut names = HashMap::new();
+    let mut sorted = notes.iter().collect::<Vec<_>>();
+    sorted.sort_by_key(|note| note.name());
+    for note in &sorted {
+        for alias in note.aliases() {
+            names.entry(alias.clone()).or_insert_with(|| note.name());
+        }
+    }
+    for note in sorted {
+        names.insert(note.name(), note.name());
+    }
+    names
+}
+
+/// `names` are as returned by [`resolved_names`]
+pub(crate) async fn sync_note(
+    note: &Note,
+    names: &HashMap<String, String>,
+    db: DatabaseAsyncHandle,
+    surf_parsing: &SurfParsing,
+    prune: bool,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+) -> Result<SyncReport, anyhow::Error> {
+    let mut report = SyncReport {
+        pruned: prune,
+        ..Default::default()
+    };
+    let body = match note.file_path() {
+        // front matter of plain files storage may mention names too
+        Some(file_path) => front_matter::read_body(file_path)?,
+        None => return Ok(report),
+    };
+    let from = note.name();
+
+    let mut targets = vec![];
+    for name in parse_wikilinks(&body, surf_parsing) {
+        match names.get(&name) {
+            Some(target) if *target != from => targets.push(target.clone()),
+            Some(_) => {}
+            None => report.unresolved.push(name),
+        }
+    }
+
+    let existing = db
+        .lock()
+        .await
+        .find_links_from(&from, md_static, color_scheme, true)
+        .await?
+        .into_iter()
+        .map(|note| note.name())
+        .collect::<Vec<_>>();
+    for to in &targets {
+        if !existing.contains(to) {
+            db.lock().await.insert_synced_link(&from, to).await?;
+            report.linked.push(to.clone());
+        }
+    }
+
+    let synced = db.lock().await.find_synced_links_from(&from).await?;
+    for to in existing {
+        if targets.contains(&to) {
+            continue;
+        }
+        if !synced.contains(&to) {
+            report.unmentioned.push(to);
+            continue;
+        }
+        if prune {
+            db.lock().await.remove_link(&from, &to, true).await?;
+        }
+        report.stale.push(to);
+    }
+    Ok(report)
+}
+
+/// syncs single note, as a hook after it's been opened
+pub(crate) async fn sync_opened(
+    note: &Note,
+    db: DatabaseAsyncHandle,
+    surf_parsing: &SurfParsing,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+) -> Result<(), anyhow::Error> {
+    let notes = db.lock().await.list(md_static, color_scheme).await?;
+    let names = resolved_names(&notes);
+    let report = sync_note(
+        note,
+        &names,
+        db,
+        surf_parsing,
+        false,
+        md_static,
+        color_scheme,
+    )
+    .await?;
+    report.print(&note.name());
+    Ok(())
+}
+
+pub(crate) async fn exec(
+    db: DatabaseAsyncHandle,
+    surf_parsing: &SurfParsing,
+    name: Option<String>,
+    prune: bool,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+) -> Result<String, anyhow::Error> {
+    let all = db.lock().await.list(md_static, color_scheme).await?;
+    let names = resolved_names(&all);
+    let notes = match name {
+        Some(name) => vec![db.lock().await.get(&name, md_static, color_scheme).await?],
+        None => all,
+    };
+
+    let (mut linked, mut unresolved, mut stale, mut unmentioned) = (0, 0, 0, 0);
+    for note in notes.iter().rev() {
+        let report = sync_note(
+            note,
+            &names,
+            db.clone(),
+            surf_parsing,
+            prune,
+            md_static,
+            color_scheme,
+        )
+        .await?;
+        report.print(&note.name());
+        linked += report.linked.len();
+        unresolved += report.unresolved.len();
+        stale += report.stale.len();
+        unmentioned += report.unmentioned.len();
+    }
+
+    let verb = if prune { "pruned" } else { "stale" };
+    eprintln!(
+        "{}",
+        format_two_tokens(
+            "synced",
+            &format!(
+                "{} links created, {} unresolved, {} {}, {} unmentioned",
+                linked, unresolved, stale, verb, unmentioned
+            )
+        )
+    );
+    Ok("success".truecolor(0, 255, 255).to_string())
+}
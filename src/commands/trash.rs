@@ -0,0 +1,109 @@
+use colored::Colorize;
+use inquire::{Confirm, Select};
+
+use crate::{
+    config::color::ColorScheme,
+    database::{DatabaseAsyncHandle, TrashEntry},
+    highlight::MarkdownStatic,
+    print::format_two_tokens,
+};
+
+fn describe(entry: &TrashEntry) -> String {
+    let trashed_at = chrono::DateTime::from_timestamp(entry.trashed_at, 0)
+        .map(|time| {
+            time.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_default();
+    format!(
+        "{:>5} {} {} ({} links, {} stacks)",
+        entry.id,
+        trashed_at,
+        entry.snapshot.name.truecolor(255, 0, 255),
+        entry.snapshot.links.len(),
+        entry.snapshot.stacks.len(),
+    )
+}
+
+pub(crate) async fn list(db: DatabaseAsyncHandle) -> Result<String, anyhow::Error> {
+    let entries = db.lock().await.list_trash().await?;
+    let lines = entries.iter().map(describe).collect::<Vec<_>>();
+    Ok(lines.join("\n"))
+}
+
+async fn pick_entry(
+    db: &DatabaseAsyncHandle,
+    id: Option<i64>,
+    hint: &str,
+) -> Result<TrashEntry, anyhow::Error> {
+    let entries = db.lock().await.list_trash().await?;
+    if entries.is_empty() {
+        return Err(anyhow::anyhow!("trash is empty"));
+    }
+    match id {
+        Some(id) => entries
+            .into_iter()
+            .find(|entry| entry.id == id)
+            .ok_or(anyhow::anyhow!("no entry {} in trash", id)),
+        None => {
+            let descriptions = entries.iter().map(describe).collect::<Vec<_>>();
+            let picked = Select::new(hint, descriptions).raw_prompt()?.index;
+            Ok(entries[picked].clone())
+        }
+    }
+}
+
+pub(crate) async fn restore(
+    db: DatabaseAsyncHandle,
+    id: Option<i64>,
+    md_static: MarkdownStatic,
+    color_scheme: ColorScheme,
+) -> Result<String, anyhow::Error> {
+    let entry = pick_entry(&db, id, "restore from trash").await?;
+    let name = &entry.snapshot.name;
+
+    let existing = db.lock().await.get(name, md_static, color_scheme).await;
+    if existing.is_ok() {
+        return Err(anyhow::anyhow!(
+            "note `{}` already exists, rename it before restoring",
+            name
+        ));
+    }
+
+    let skipped = db.lock().await.restore_from_trash(entry.id).await?;
+    for link in skipped {
+        eprintln!(
+            "{}",
+            format_two_tokens(
+                "skipped link to a missing note",
+                &format!("{} -> {}", link.from, link.to)
+            )
+        );
+    }
+    eprintln!("{}", format_two_tokens("restored", name));
+    Ok("success".truecolor(0, 255, 255).to_string())
+}
+
+pub(crate) async fn purge(
+    db: DatabaseAsyncHandle,
+    id: Option<i64>,
+) -> Result<String, anyhow::Error> {
+    let prompt = match id {
+        Some(id) => {
+            let entry = pick_entry(&db, Some(id), "").await?;
+            format!("sure you want to delete `{}` for good", entry.snapshot.name)
+        }
+        None => "sure you want to delete all notes in trash for good".to_string(),
+    };
+    if !Confirm::new(&prompt).prompt()? {
+        return Ok("aborted".to_string());
+    }
+
+    let purged = db.lock().await.purge_trash(id).await?;
+    eprintln!(
+        "{}",
+        format_two_tokens("purged from trash", &purged.to_string())
+    );
+    Ok("success".truecolor(0, 255, 255).to_string())
+}
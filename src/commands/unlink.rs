@@ -1,8 +1,9 @@
 use colored::Colorize;
+use inquire::Select;
 
 use crate::{
     config::{color::ColorScheme, ExternalCommands, SurfParsing},
-    database::{Database, SqliteAsyncHandle},
+    database::DatabaseAsyncHandle,
     highlight::MarkdownStatic,
     note::{Note, PreviewType},
     print::format_two_tokens,
@@ -10,7 +11,7 @@ use crate::{
 };
 
 pub(crate) async fn exec(
-    db: SqliteAsyncHandle,
+    db: DatabaseAsyncHandle,
     external_commands: ExternalCommands,
     surf_parsing: SurfParsing,
 
@@ -54,7 +55,7 @@ pub(crate) async fn exec(
 
 pub(crate) async fn unlink(
     from: Note,
-    db: SqliteAsyncHandle,
+    db: DatabaseAsyncHandle,
     external_commands: &ExternalCommands,
     surf_parsing: &SurfParsing,
     md_static: MarkdownStatic,
@@ -69,8 +70,9 @@ pub(crate) async fn unlink(
     let forward_links = db
         .lock()
         .await
-        .find_links_from(&from.name(), md_static, color_scheme, straight)
+        .find_labelled_links_from(&from.name(), md_static, color_scheme, straight)
         .await?;
+    let forward_links = filter_by_label(forward_links)?;
     let to = Iteration::new(
         hint,
         forward_links,
@@ -100,3 +102,26 @@ pub(crate) async fn unlink(
     );
     Ok(())
 }
+
+fn filter_by_label(links: Vec<(Note, Option<String>)>) -> Result<Vec<Note>, anyhow::Error> {
+    let mut labels = links
+        .iter()
+        .filter_map(|(_note, label)| label.clone())
+        .collect::<Vec<_>>();
+    labels.sort();
+    labels.dedup();
+    if labels.is_empty() {
+        return Ok(links.into_iter().map(|(note, _label)| note).collect());
+    }
+
+    let any = "<any>".to_string();
+    let mut options = vec![any.clone()];
+    options.extend(labels);
+    let picked = Select::new("unlink from links labelled", options).prompt()?;
+
+    Ok(links
+        .into_iter()
+        .filter(|(_note, label)| picked == any || label.as_ref() == Some(&picked))
+        .map(|(note, _label)| note)
+        .collect())
+}
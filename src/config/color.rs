@@ -37,6 +37,7 @@ pub struct Links {
     pub code_block: ConfigRGB,
     pub unlisted: ConfigRGB,
     pub cycle: ConfigRGB,
+    pub label: ConfigRGB,
 }
 
 impl_try_from_kdl_node_tagged!(Color, "world.color",
@@ -62,5 +63,7 @@ impl_try_from_kdl_node_tagged!(Links, "world.color.elements.links",
     "broken" => broken,
     "code_block" => code_block,
     "unlisted" => unlisted,
-    "cycle" => cycle
+    "cycle" => cycle;
+    optional
+    "label" => label
 );
@@ -14,11 +14,10 @@ impl TryFrom<&KdlNode> for CmdTemplate {
         let entries = value.entries();
         let first = entries.first();
         let Some(first) = first else {
-            return Err(KdlNodeErrorType {
+            return Err(Into::<miette::Report>::into(KdlNodeErrorType {
                 err_span: *value.span(),
                 description: "node expected to have at least 1 argument".to_string(),
-            })
-            .map_err(Into::<miette::Report>::into)?;
+            }))?;
         };
 
         let command = first
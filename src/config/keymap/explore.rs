@@ -27,6 +27,20 @@ pub enum Action {
     IncreaseUnlistedThreshold,
     PushNoteToStack,
     SwitchModeToStack,
+    FullTextSearch,
+    FilterLinksByLabel,
+    CycleListOrder,
+    UndoLastChange,
+    RedoLastChange,
+    EditAliases,
+    FilterByProperty,
+    MergeNote,
+    SplitNote,
+    ShowPath,
+    ShowOrphans,
+    ShowLeaves,
+    ShowUnreachable,
+    ShowComponent,
 }
 
 #[derive(Debug, Clone)]
@@ -50,6 +64,20 @@ pub struct ExploreKeymap {
     pub increase_unlisted_threshold: SingleKey,
     pub push_note_to_stack: SingleKey,
     pub switch_mode_to_stack: SingleKey,
+    pub full_text_search: SingleKey,
+    pub filter_links_by_label: SingleKey,
+    pub cycle_list_order: SingleKey,
+    pub undo_last_change: SingleKey,
+    pub redo_last_change: SingleKey,
+    pub edit_aliases: SingleKey,
+    pub filter_by_property: SingleKey,
+    pub merge_note: SingleKey,
+    pub split_note: SingleKey,
+    pub show_path: SingleKey,
+    pub show_orphans: SingleKey,
+    pub show_leaves: SingleKey,
+    pub show_unreachable: SingleKey,
+    pub show_component: SingleKey,
 }
 
 impl_try_from_kdl_node_uniqueness_check!(
@@ -73,7 +101,22 @@ impl_try_from_kdl_node_uniqueness_check!(
     decrease_unlisted_threshold,
     increase_unlisted_threshold,
     push_note_to_stack,
-    switch_mode_to_stack
+    switch_mode_to_stack;
+    optional
+    full_text_search,
+    filter_links_by_label,
+    cycle_list_order,
+    undo_last_change,
+    redo_last_change,
+    edit_aliases,
+    filter_by_property,
+    merge_note,
+    split_note,
+    show_path,
+    show_orphans,
+    show_leaves,
+    show_unreachable,
+    show_component
 );
 
 impl_from_self_into_action_hashmap!(ExploreKeymap, Action,
@@ -95,5 +138,19 @@ impl_from_self_into_action_hashmap!(ExploreKeymap, Action,
     Action::DecreaseUnlistedThreshold => decrease_unlisted_threshold | "accept".to_string(),
     Action::IncreaseUnlistedThreshold => increase_unlisted_threshold | "accept".to_string(),
     Action::PushNoteToStack => push_note_to_stack | "accept".to_string(),
-    Action::SwitchModeToStack => switch_mode_to_stack | "accept".to_string()
+    Action::SwitchModeToStack => switch_mode_to_stack | "accept".to_string(),
+    Action::FullTextSearch => full_text_search | "accept".to_string(),
+    Action::FilterLinksByLabel => filter_links_by_label | "accept".to_string(),
+    Action::CycleListOrder => cycle_list_order | "accept".to_string(),
+    Action::UndoLastChange => undo_last_change | "accept".to_string(),
+    Action::RedoLastChange => redo_last_change | "accept".to_string(),
+    Action::EditAliases => edit_aliases | "accept".to_string(),
+    Action::FilterByProperty => filter_by_property | "accept".to_string(),
+    Action::MergeNote => merge_note | "accept".to_string(),
+    Action::SplitNote => split_note | "accept".to_string(),
+    Action::ShowPath => show_path | "accept".to_string(),
+    Action::ShowOrphans => show_orphans | "accept".to_string(),
+    Action::ShowLeaves => show_leaves | "accept".to_string(),
+    Action::ShowUnreachable => show_unreachable | "accept".to_string(),
+    Action::ShowComponent => show_component | "accept".to_string()
 );
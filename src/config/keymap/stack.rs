@@ -15,6 +15,7 @@ pub struct StackKeymap {
     pub swap_with_above: SingleKey,
     pub swap_with_below: SingleKey,
     pub deselect_all: SingleKey,
+    pub select_stack: SingleKey,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -26,6 +27,7 @@ pub enum Action {
     SwapWithAbove,
     SwapWithBelow,
     DeselectAll,
+    SelectStack,
 }
 
 impl_try_from_kdl_node_uniqueness_check!(
@@ -37,7 +39,9 @@ impl_try_from_kdl_node_uniqueness_check!(
     return_to_explore,
     swap_with_above,
     swap_with_below,
-    deselect_all
+    deselect_all;
+    optional
+    select_stack
 );
 
 impl_from_self_into_action_hashmap!(StackKeymap, Action,
@@ -47,6 +51,7 @@ impl_from_self_into_action_hashmap!(StackKeymap, Action,
     Action::ReturnToExplore => return_to_explore | "accept".to_string(),
     Action::SwapWithAbove => swap_with_above | "accept".to_string(),
     Action::SwapWithBelow => swap_with_below | "accept".to_string(),
-    Action::DeselectAll => deselect_all | "deselect-all".to_string()
+    Action::DeselectAll => deselect_all | "deselect-all".to_string(),
+    Action::SelectStack => select_stack | "accept".to_string()
 
 );
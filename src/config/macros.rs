@@ -40,7 +40,7 @@ macro_rules! impl_try_from_kdl_node {
 }
 #[macro_export]
 macro_rules! impl_try_from_kdl_node_tagged {
-($type: ident, $parent: expr, $($tag: expr => $field: ident),+ ) => (
+($type: ident, $parent: expr, $($tag: expr => $field: ident),+ $(; optional $($opt_tag: expr => $opt_field: ident),+)? ) => (
 
     impl TryFrom<&KdlNode> for $type {
         type Error = miette::Report;
@@ -66,9 +66,22 @@ macro_rules! impl_try_from_kdl_node_tagged {
                         .map_err(|err| Into::<miette::Report>::into(err))?;
                     hashmap.insert(tag, node);
                 }
+                $($(
+                    let node = value
+                        .children()
+                        .and_then(|children| children.get($opt_tag))
+                        .or_else(|| $crate::config::default_node($parent, $opt_tag))
+                        .ok_or(KdlNodeErrorType {
+                                    err_span: value.span().clone(),
+                                    description: format!("no `{}.{}` in config", $parent, $opt_tag),
+                                })
+                        .map_err(|err| Into::<miette::Report>::into(err))?;
+                    hashmap.insert($opt_tag, node);
+                )+)?
                 $type {
                     $($field: hashmap[$tag].try_into()?
                     ),+
+                    $($(, $opt_field: hashmap[$opt_tag].try_into()?)+)?
 
                 }
             };
@@ -84,7 +97,7 @@ pub mod keymap {
 
     #[macro_export]
     macro_rules! impl_try_from_kdl_node_uniqueness_check {
-        ($type: ident, $parent: expr, $($field: ident),+ ) => (
+        ($type: ident, $parent: expr, $($field: ident),+ $(; optional $($opt_field: ident),+)? ) => (
 
             impl TryFrom<&KdlNode> for $type {
                 type Error = miette::Report;
@@ -110,8 +123,22 @@ pub mod keymap {
                                 .map_err(|err| Into::<miette::Report>::into(err))?;
                             hashmap.insert(tag, node);
                         }
+                        $($(
+                            let tag = stringify!($opt_field);
+                            let node = value
+                                .children()
+                                .and_then(|children| children.get(tag))
+                                .or_else(|| $crate::config::default_node($parent, tag))
+                                .ok_or(KdlNodeErrorType {
+                                            err_span: value.span().clone(),
+                                            description: format!("no `{}.{}` in config", $parent, tag),
+                                        })
+                                .map_err(|err| Into::<miette::Report>::into(err))?;
+                            hashmap.insert(tag, node);
+                        )+)?
                         $type {
                             $($field: hashmap[stringify!($field)].try_into()?),+
+                            $($(, $opt_field: hashmap[stringify!($opt_field)].try_into()?)+)?
 
                         }
                     };
@@ -124,6 +151,10 @@ pub mod keymap {
                             keys_hash_set.insert(result.$field.clone());
 
                         )+
+                    $($(
+                            count += 1;
+                            keys_hash_set.insert(result.$opt_field.clone());
+                        )+)?
                     }
                     if count != keys_hash_set.len() {
                         let err = KdlNodeErrorType {
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::{fs, path::PathBuf};
 
 use kdl::{KdlDocument, KdlNode};
+use lazy_static::lazy_static;
 use miette::{Diagnostic, IntoDiagnostic, Report, SourceSpan};
 use thiserror::Error;
 
@@ -22,9 +23,26 @@ pub mod keymap;
 pub mod surf_parsing;
 
 static PROGRAM_NAME: &str = "mds";
+
+lazy_static! {
+    static ref SAMPLE_CONFIG: KdlDocument = include_str!("../../config.kdl")
+        .parse()
+        .expect("sample config is valid kdl");
+}
+
+/// node `tag` under `parent` path, e.g. `world.keymap.explore`, of sample config from
+/// repository root; keys, added to it since, may be omitted from user's config
+pub fn default_node(parent: &str, tag: &str) -> Option<&'static KdlNode> {
+    let mut doc: &KdlDocument = &SAMPLE_CONFIG;
+    for name in parent.split('.') {
+        doc = doc.get(name)?.children()?;
+    }
+    doc.get(tag)
+}
 #[derive(Debug)]
 pub struct Config {
     pub work_dir: ConfigPath,
+    pub storage: Storage,
     pub surf_parsing: SurfParsing,
     pub external_commands: ExternalCommands,
     pub color: Color,
@@ -70,6 +88,45 @@ impl TryFrom<&KdlNode> for ConfigPath {
     }
 }
 
+/// where notes' names, links and stacks are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Storage {
+    /// `.sqlite` database in notes' work dir
+    Sqlite,
+    /// yaml front matter of notes' files and `.mds` dir in notes' work dir
+    Files,
+}
+
+impl TryFrom<&KdlNode> for Storage {
+    type Error = miette::Report;
+
+    fn try_from(value: &KdlNode) -> Result<Self, Self::Error> {
+        let string = value
+            .get(0)
+            .ok_or(KdlNodeErrorType {
+                err_span: *value.span(),
+                description: "node's first argument not found".to_string(),
+            })
+            .map_err(Into::<miette::Report>::into)?
+            .value()
+            .as_string()
+            .ok_or(KdlNodeErrorType {
+                err_span: *value.span(),
+                description: "argument's value is expected to be of string type".to_string(),
+            })
+            .map_err(Into::<miette::Report>::into)?;
+
+        match string {
+            "sqlite" => Ok(Self::Sqlite),
+            "files" => Ok(Self::Files),
+            _ => Err(KdlNodeErrorType {
+                err_span: *value.span(),
+                description: "expected one of `sqlite`, `files`".to_string(),
+            })?,
+        }
+    }
+}
+
 impl Config {
     pub fn parse() -> miette::Result<Self> {
         let xdg_dirs = xdg::BaseDirectories::with_prefix(PROGRAM_NAME).into_diagnostic()?;
@@ -106,4 +163,6 @@ impl_try_from_kdl_node_tagged!(Config, "world",
     "notes-work-dir" => work_dir, 
     "external-commands" => external_commands, 
     "color" => color, 
-    "keymap" => keymap);
+    "keymap" => keymap;
+    optional
+    "storage" => storage);
@@ -11,12 +11,17 @@ pub struct SurfParsing {
     pub markdown_reference_link_regex: ConfigRegex,
     pub task_item_regex: ConfigRegex,
     pub has_line_regex: ConfigRegex,
+    pub wikilink_regex: ConfigRegex,
+    pub sync_wikilinks_on_open: ConfigFlag,
 }
 impl_try_from_kdl_node_tagged!(SurfParsing, "world.surf-parsing", 
     "markdown-reference-link-regex" => markdown_reference_link_regex,
     "url-regex" => url_regex,
     "file-dest-has-line-regex" => has_line_regex,
-    "task-item-regex" => task_item_regex);
+    "task-item-regex" => task_item_regex;
+    optional
+    "wikilink-regex" => wikilink_regex,
+    "sync-wikilinks-on-open" => sync_wikilinks_on_open);
 
 #[derive(Debug, Clone)]
 pub struct ConfigRegex(pub Regex);
@@ -53,3 +58,29 @@ impl TryFrom<&KdlNode> for ConfigRegex {
         Ok(Self(regex))
     }
 }
+
+#[derive(Debug, Clone, Copy)]
+pub struct ConfigFlag(pub bool);
+
+impl TryFrom<&KdlNode> for ConfigFlag {
+    type Error = miette::Report;
+
+    fn try_from(value: &KdlNode) -> Result<Self, Self::Error> {
+        let flag = value
+            .get(0)
+            .ok_or(KdlNodeErrorType {
+                err_span: *value.span(),
+                description: "node's first argument not found".to_string(),
+            })
+            .map_err(Into::<miette::Report>::into)?
+            .value()
+            .as_bool()
+            .ok_or(KdlNodeErrorType {
+                err_span: *value.span(),
+                description: "argument's value is expected to be of bool type".to_string(),
+            })
+            .map_err(Into::<miette::Report>::into)?;
+
+        Ok(Self(flag))
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::journal::{LinkRecord, StackOrder};
+
+/// version of dump format, bumped on incompatible changes
+pub const DUMP_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DumpedNote {
+    pub name: String,
+    pub tag: bool,
+    pub filename: Option<String>,
+    pub created_at: Option<i64>,
+    pub updated_at: Option<i64>,
+    pub opened_at: Option<i64>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub properties: BTreeMap<String, String>,
+}
+
+/// whole graph of notes, links and stacks, independent of db schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dump {
+    pub version: u32,
+    pub notes: Vec<DumpedNote>,
+    pub links: Vec<LinkRecord>,
+    pub stacks: Vec<StackOrder>,
+}
+
+impl Dump {
+    /// list of problems, which would prevent dump from being restored
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = vec![];
+        if self.version != DUMP_VERSION {
+            problems.push(format!(
+                "unsupported dump version {}, expected {}",
+                self.version, DUMP_VERSION
+            ));
+            return problems;
+        }
+
+        let mut names = HashSet::new();
+        let mut filenames = HashSet::new();
+        for note in &self.notes {
+            if note.name.is_empty() {
+                problems.push("note with empty name".to_string());
+            }
+            if !names.insert(note.name.as_str()) {
+                problems.push(format!("duplicate note `{}`", note.name));
+            }
+            match (&note.filename, note.tag) {
+                (Some(_), true) => {
+                    problems.push(format!("tag `{}` has a file body", note.name));
+                }
+                (None, false) => {
+                    problems.push(format!("note `{}` has no file body", note.name));
+                }
+                (Some(filename), false) => {
+                    if !filenames.insert(filename.as_str()) {
+                        problems.push(format!("file `{}` is body of more than one note", filename));
+                    }
+                }
+                (None, true) => {}
+            }
+        }
+
+        let mut aliases = HashSet::new();
+        for note in &self.notes {
+            for alias in &note.aliases {
+                if names.contains(alias.as_str()) || !aliases.insert(alias.as_str()) {
+                    problems.push(format!(
+                        "alias `{}` of `{}` is already taken",
+                        alias, note.name
+                    ));
+                }
+            }
+        }
+
+        let mut links = HashSet::new();
+        for link in &self.links {
+            for end in [&link.from, &link.to] {
+                if !names.contains(end.as_str()) {
+                    problems.push(format!(
+                        "link `{}` -> `{}` refers to unknown note `{}`",
+                        link.from, link.to, end
+                    ));
+                }
+            }
+            if !links.insert((link.from.as_str(), link.to.as_str())) {
+                problems.push(format!("duplicate link `{}` -> `{}`", link.from, link.to));
+            }
+        }
+
+        let mut stacks = HashSet::new();
+        for order in &self.stacks {
+            if !stacks.insert(order.stack.as_str()) {
+                problems.push(format!("duplicate stack `{}`", order.stack));
+            }
+            let mut stacked = HashSet::new();
+            for note in &order.notes {
+                if !names.contains(note.as_str()) {
+                    problems.push(format!(
+                        "stack `{}` refers to unknown note `{}`",
+                        order.stack, note
+                    ));
+                }
+                if !stacked.insert(note.as_str()) {
+                    problems.push(format!(
+                        "note `{}` is in stack `{}` more than once",
+                        note, order.stack
+                    ));
+                }
+            }
+        }
+        problems
+    }
+}
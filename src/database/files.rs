@@ -0,0 +1,749 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use async_std::sync::Mutex;
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use sqlx::{Error, Result};
+
+use crate::{
+    config::color::ColorScheme, front_matter, highlight::MarkdownStatic, note::Note,
+    parse_cache::ParseCache,
+};
+
+use super::{
+    dump::Dump,
+    journal::{BodyRewrite, JournalEntry, LinkRecord, StepReport},
+    memory::Record,
+    trash::TrashEntry,
+    Database, Memory, NoteTimestamps, Subgraph,
+};
+
+static TAGS_FILE: &str = "tags.yaml";
+static STACKS_FILE: &str = "stacks.yaml";
+static JOURNAL_FILE: &str = "journal.yaml";
+static TRASH_FILE: &str = "trash.yaml";
+/// kept apart from front matter, so that merely opening a note doesn't touch its file
+static OPENED_FILE: &str = "opened.yaml";
+
+/// keys of front matter, which belong to storage; all other keys are kept intact
+static KEYS: [&str; 7] = [
+    "name",
+    "aliases",
+    "properties",
+    "links",
+    "stacks",
+    "created_at",
+    "updated_at",
+];
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct FrontMatter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    properties: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    links: Vec<FrontMatterLink>,
+    /// index of note in each stack it belongs to, 0 is topmost
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    stacks: BTreeMap<String, usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    created_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    updated_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FrontMatterLink {
+    to: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    synced: bool,
+}
+
+fn encode_error(err: serde_yaml::Error) -> Error {
+    Error::Protocol(format!("{:?}", err))
+}
+
+fn decode_error(err: serde_yaml::Error) -> Error {
+    Error::Decode(Box::new(err))
+}
+
+/// storage, which keeps names, links and stacks of notes in yaml front matter of their
+/// files, and everything not tied to a single file under its own dir
+pub struct Files {
+    memory: Memory,
+    dir: PathBuf,
+    /// notes' work dir, `dir` is in; filenames of bodies are relative to it
+    root: PathBuf,
+    /// yaml of every file, as it was last read or written, so that unchanged ones are skipped
+    written: BTreeMap<PathBuf, String>,
+    /// notes and stacks as of last flush, so that only front matter of changed notes is
+    /// rendered again
+    flushed_notes: BTreeMap<String, Record>,
+    flushed_stacks: BTreeMap<String, Vec<String>>,
+}
+
+impl Files {
+    pub fn new(create_if_missing: bool, dir: impl AsRef<Path>) -> Result<Arc<Mutex<Self>>> {
+        let dir = dir.as_ref();
+        log::debug!("opening plain files storage at {:?}", dir);
+
+        if !dir.exists() {
+            if !create_if_missing {
+                return Err(Error::Protocol(format!(
+                    "storage dir {:?} doesn't exist, run `mds init`",
+                    dir
+                )));
+            }
+            fs::create_dir_all(dir)?;
+        }
+        // not relative to cwd, which may change while storage is open
+        let dir = fs::canonicalize(dir)?;
+        let root = dir.parent().unwrap_or(&dir).to_path_buf();
+
+        let memory = Self::load(&root, &dir)?;
+        let mut files = Self {
+            flushed_notes: memory.notes.clone(),
+            flushed_stacks: memory.stacks.clone(),
+            memory,
+            dir,
+            root,
+            written: BTreeMap::new(),
+        };
+        files.written = files.render()?;
+        files.written.retain(|path, _| path.exists());
+        files.flush()?;
+
+        Ok(Arc::new(Mutex::new(files)))
+    }
+
+    fn load(root: &Path, dir: &Path) -> Result<Memory> {
+        let mut memory = Memory::new(root);
+
+        let mut front_matters = vec![];
+        let mut file_paths = vec![];
+        markdown_files(root, &mut file_paths)?;
+        for file_path in file_paths {
+            let content = fs::read_to_string(&file_path)?;
+            let Some(yaml) = front_matter::split(&content).0 else {
+                continue;
+            };
+            let Ok(mapping) = serde_yaml::from_str::<Mapping>(yaml) else {
+                log::debug!("skipping {:?} with unparsable front matter", file_path);
+                continue;
+            };
+            if !mapping.contains_key("name") {
+                continue;
+            }
+            let parsed: FrontMatter =
+                serde_yaml::from_value(Value::Mapping(mapping)).map_err(decode_error)?;
+            let name = parsed.name.clone().unwrap_or_default();
+            let relative = file_path.strip_prefix(root).unwrap_or(&file_path);
+            let filename = Path::new(".").join(relative).to_string_lossy().to_string();
+            front_matters.push((name, Some(filename), parsed));
+        }
+        let tags: BTreeMap<String, FrontMatter> =
+            read_yaml(&dir.join(TAGS_FILE))?.unwrap_or_default();
+        for (name, parsed) in tags {
+            front_matters.push((name, None, parsed));
+        }
+
+        for (name, filename, parsed) in &front_matters {
+            if let Some(other) = memory.notes.get(name) {
+                let describe = |filename: &Option<String>| {
+                    filename
+                        .clone()
+                        .unwrap_or_else(|| dir.join(TAGS_FILE).display().to_string())
+                };
+                return Err(Error::Protocol(format!(
+                    "note `{}` is defined twice: in {} and in {}",
+                    name,
+                    describe(&other.filename),
+                    describe(filename)
+                )));
+            }
+            let mut aliases = parsed.aliases.clone();
+            aliases.sort();
+            let record = Record {
+                filename: filename.clone(),
+                created_at: parsed.created_at,
+                updated_at: parsed.updated_at,
+                aliases,
+                properties: parsed.properties.clone(),
+                ..Default::default()
+            };
+            memory.notes.insert(name.clone(), record);
+        }
+
+        if let Some(stacks) = read_yaml::<Vec<String>>(&dir.join(STACKS_FILE))? {
+            memory.stacks = stacks.into_iter().map(|stack| (stack, vec![])).collect();
+        }
+        let mut positions: BTreeMap<String, Vec<(usize, String)>> = BTreeMap::new();
+        for (name, _filename, parsed) in front_matters {
+            for link in parsed.links {
+                if !memory.notes.contains_key(&link.to) {
+                    log::debug!("dropping link {} -> {} to missing note", name, link.to);
+                    continue;
+                }
+                if let Some(record) = memory.notes.get_mut(&name) {
+                    if link.synced {
+                        record.synced.insert(link.to.clone());
+                    }
+                    record.links.insert(link.to, link.label);
+                }
+            }
+            for (stack, index) in parsed.stacks {
+                positions
+                    .entry(stack)
+                    .or_default()
+                    .push((index, name.clone()));
+            }
+        }
+        for (stack, mut notes) in positions {
+            notes.sort();
+            let notes = notes.into_iter().map(|(_index, name)| name).collect();
+            memory.stacks.insert(stack, notes);
+        }
+
+        let opened: BTreeMap<String, i64> = read_yaml(&dir.join(OPENED_FILE))?.unwrap_or_default();
+        for (name, opened_at) in opened {
+            if let Some(record) = memory.notes.get_mut(&name) {
+                record.opened_at = Some(opened_at);
+            }
+        }
+        memory.journal = read_yaml(&dir.join(JOURNAL_FILE))?.unwrap_or_default();
+        memory.trash = read_yaml(&dir.join(TRASH_FILE))?.unwrap_or_default();
+
+        Ok(memory)
+    }
+
+    fn front_matter(&self, name: &str, record: &Record) -> FrontMatter {
+        let links = record
+            .links
+            .iter()
+            .map(|(to, label)| FrontMatterLink {
+                to: to.clone(),
+                label: label.clone(),
+                synced: record.synced.contains(to),
+            })
+            .collect();
+        let stacks = self
+            .memory
+            .stacks
+            .iter()
+            .filter_map(|(stack, notes)| {
+                let index = notes.iter().position(|note| note == name)?;
+                Some((stack.clone(), index))
+            })
+            .collect();
+
+        FrontMatter {
+            name: record.filename.as_ref().map(|_| name.to_string()),
+            aliases: record.aliases.clone(),
+            properties: record.properties.clone(),
+            links,
+            stacks,
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+        }
+    }
+
+    /// yaml of front matter of a note with a body
+    fn render_note(&self, name: &str, record: &Record) -> Result<String> {
+        serde_yaml::to_string(&self.front_matter(name, record)).map_err(encode_error)
+    }
+
+    /// yaml of files in storage's own dir; tags and opened ones only if `notes_changed`,
+    /// list of stacks only if `stacks_changed`
+    fn render_own(
+        &self,
+        notes_changed: bool,
+        stacks_changed: bool,
+    ) -> Result<BTreeMap<PathBuf, String>> {
+        let mut own = vec![
+            (JOURNAL_FILE, serde_yaml::to_string(&self.memory.journal)),
+            (TRASH_FILE, serde_yaml::to_string(&self.memory.trash)),
+        ];
+        if notes_changed {
+            let mut tags = BTreeMap::new();
+            let mut opened = BTreeMap::new();
+            for (name, record) in &self.memory.notes {
+                if record.filename.is_none() {
+                    tags.insert(name.clone(), self.front_matter(name, record));
+                }
+                if let Some(opened_at) = record.opened_at {
+                    opened.insert(name.clone(), opened_at);
+                }
+            }
+            own.push((TAGS_FILE, serde_yaml::to_string(&tags)));
+            own.push((OPENED_FILE, serde_yaml::to_string(&opened)));
+        }
+        if stacks_changed {
+            let stacks = self.memory.stacks.keys().collect::<Vec<_>>();
+            own.push((STACKS_FILE, serde_yaml::to_string(&stacks)));
+        }
+
+        let mut files = BTreeMap::new();
+        for (file_name, yaml) in own {
+            files.insert(self.dir.join(file_name), yaml.map_err(encode_error)?);
+        }
+        Ok(files)
+    }
+
+    /// yaml of every file, storage currently consists of
+    fn render(&self) -> Result<BTreeMap<PathBuf, String>> {
+        let mut files = self.render_own(true, true)?;
+        for (name, record) in &self.memory.notes {
+            if let Some(filename) = &record.filename {
+                files.insert(self.root.join(filename), self.render_note(name, record)?);
+            }
+        }
+        Ok(files)
+    }
+
+    /// notes, whose records or positions in stacks changed since last flush, and whether
+    /// stacks did
+    fn changed_since_flush(&self) -> (BTreeSet<String>, bool) {
+        let notes = &self.memory.notes;
+        let mut changed = notes
+            .iter()
+            .filter(|(name, record)| self.flushed_notes.get(*name) != Some(record))
+            .map(|(name, _)| name.clone())
+            .collect::<BTreeSet<_>>();
+        changed.extend(
+            self.flushed_notes
+                .keys()
+                .filter(|name| !notes.contains_key(*name))
+                .cloned(),
+        );
+
+        let stacks = &self.memory.stacks;
+        let mut stacks_changed = false;
+        for stack in stacks.keys().chain(self.flushed_stacks.keys()) {
+            let (now, before) = (stacks.get(stack), self.flushed_stacks.get(stack));
+            if now != before {
+                stacks_changed = true;
+                changed.extend(now.into_iter().chain(before).flatten().cloned());
+            }
+        }
+        (changed, stacks_changed)
+    }
+
+    /// writes every file, which changed since last flush
+    fn flush(&mut self) -> Result<()> {
+        let (changed, stacks_changed) = self.changed_since_flush();
+
+        // `None` for files, which are no longer bodies of notes
+        let mut files: BTreeMap<PathBuf, Option<String>> = BTreeMap::new();
+        for name in &changed {
+            let flushed = self.flushed_notes.get(name);
+            if let Some(filename) = flushed.and_then(|record| record.filename.as_ref()) {
+                files.insert(self.root.join(filename), None);
+            }
+        }
+        for name in &changed {
+            let Some(record) = self.memory.notes.get(name) else {
+                continue;
+            };
+            if let Some(filename) = &record.filename {
+                let yaml = self.render_note(name, record)?;
+                files.insert(self.root.join(filename), Some(yaml));
+            }
+        }
+        for (file_path, yaml) in self.render_own(!changed.is_empty(), stacks_changed)? {
+            files.insert(file_path, Some(yaml));
+        }
+
+        for (file_path, yaml) in files {
+            let Some(yaml) = yaml else {
+                if self.written.remove(&file_path).is_some() && file_path.exists() {
+                    write_front_matter(&file_path, None)?;
+                }
+                continue;
+            };
+            if self.written.get(&file_path) == Some(&yaml) {
+                continue;
+            }
+            log::debug!("writing storage to {:?}", file_path);
+            if file_path.starts_with(&self.dir) {
+                fs::write(&file_path, &yaml)?;
+            } else {
+                write_front_matter(&file_path, Some(&yaml))?;
+            }
+            self.written.insert(file_path, yaml);
+        }
+
+        for name in changed {
+            match self.memory.notes.get(&name) {
+                Some(record) => self.flushed_notes.insert(name, record.clone()),
+                None => self.flushed_notes.remove(&name),
+            };
+        }
+        if stacks_changed {
+            self.flushed_stacks = self.memory.stacks.clone();
+        }
+
+        Ok(())
+    }
+}
+
+fn read_yaml<T: DeserializeOwned>(file_path: &Path) -> Result<Option<T>> {
+    if !file_path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(file_path)?;
+    Ok(Some(serde_yaml::from_str(&content).map_err(decode_error)?))
+}
+
+/// replaces keys of storage in front matter of a body file with `yaml`
+fn write_front_matter(file_path: &Path, yaml: Option<&str>) -> Result<()> {
+    let content = fs::read_to_string(file_path).unwrap_or_default();
+    let (current, body) = front_matter::split(&content);
+    let mut mapping = current
+        .and_then(|current| serde_yaml::from_str::<Mapping>(current).ok())
+        .unwrap_or_default();
+    for key in KEYS {
+        mapping.remove(key);
+    }
+    if let Some(yaml) = yaml {
+        let own: Mapping = serde_yaml::from_str(yaml).map_err(decode_error)?;
+        mapping.extend(own);
+    }
+
+    let yaml = match mapping.is_empty() {
+        true => None,
+        false => Some(serde_yaml::to_string(&mapping).map_err(encode_error)?),
+    };
+    fs::write(file_path, front_matter::join(yaml.as_deref(), body))?;
+    Ok(())
+}
+
+/// `*.md` files under `dir`, except for hidden dirs
+fn markdown_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if path.is_dir() && !hidden {
+            markdown_files(&path, files)?;
+        } else if path.is_file() && path.extension().is_some_and(|ext| ext == "md") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl Database for Files {
+    async fn save(&mut self, note: &Note) -> Result<()> {
+        Database::save(&mut self.memory, note).await?;
+        self.flush()
+    }
+
+    async fn list(
+        &self,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+    ) -> Result<Vec<Note>> {
+        Database::list(&self.memory, md_static, color_scheme).await
+    }
+
+    async fn get(
+        &self,
+        name: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+    ) -> Result<Note> {
+        Database::get(&self.memory, name, md_static, color_scheme).await
+    }
+
+    async fn remove_note(&mut self, note: &Note) -> Result<()> {
+        Database::remove_note(&mut self.memory, note).await?;
+        self.flush()
+    }
+
+    async fn rename_note(
+        &mut self,
+        note: &Note,
+        new_name: &str,
+        rewrites: &[BodyRewrite],
+    ) -> Result<()> {
+        Database::rename_note(&mut self.memory, note, new_name, rewrites).await?;
+        self.flush()
+    }
+
+    async fn insert_link(
+        &mut self,
+        from: &str,
+        to: &str,
+        straight: bool,
+        label: Option<&str>,
+    ) -> Result<()> {
+        Database::insert_link(&mut self.memory, from, to, straight, label).await?;
+        self.flush()
+    }
+
+    async fn remove_link(&mut self, from: &str, to: &str, straight: bool) -> Result<()> {
+        Database::remove_link(&mut self.memory, from, to, straight).await?;
+        self.flush()
+    }
+
+    async fn find_links_from(
+        &self,
+        from: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+        straight: bool,
+    ) -> Result<Vec<Note>> {
+        Database::find_links_from(&self.memory, from, md_static, color_scheme, straight).await
+    }
+
+    async fn find_links_to(
+        &self,
+        to: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+        straight: bool,
+    ) -> Result<Vec<Note>> {
+        Database::find_links_to(&self.memory, to, md_static, color_scheme, straight).await
+    }
+
+    async fn find_labelled_links_from(
+        &self,
+        from: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+        straight: bool,
+    ) -> Result<Vec<(Note, Option<String>)>> {
+        Database::find_labelled_links_from(&self.memory, from, md_static, color_scheme, straight)
+            .await
+    }
+
+    async fn find_labelled_links_to(
+        &self,
+        to: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+        straight: bool,
+    ) -> Result<Vec<(Note, Option<String>)>> {
+        Database::find_labelled_links_to(&self.memory, to, md_static, color_scheme, straight).await
+    }
+
+    async fn reachable_subgraph(
+        &self,
+        from: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+        straight: bool,
+        depth: Option<usize>,
+    ) -> Result<Subgraph> {
+        Database::reachable_subgraph(&self.memory, from, md_static, color_scheme, straight, depth)
+            .await
+    }
+
+    async fn list_link_labels(&self) -> Result<Vec<String>> {
+        Database::list_link_labels(&self.memory).await
+    }
+
+    async fn all_labelled_links(&self) -> Result<Vec<LinkRecord>> {
+        Database::all_labelled_links(&self.memory).await
+    }
+
+    async fn push_note_to_stack(&mut self, stack: &str, note: &str) -> Result<()> {
+        Database::push_note_to_stack(&mut self.memory, stack, note).await?;
+        self.flush()
+    }
+
+    async fn select_from_stack(
+        &mut self,
+        stack: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+    ) -> Result<Vec<Note>> {
+        Database::select_from_stack(&mut self.memory, stack, md_static, color_scheme).await
+    }
+
+    async fn pop_note_from_stack(&mut self, stack: &str, note: &str) -> Result<()> {
+        Database::pop_note_from_stack(&mut self.memory, stack, note).await?;
+        self.flush()
+    }
+
+    async fn move_to_topmost(&mut self, stack: &str, note: &str) -> Result<()> {
+        Database::move_to_topmost(&mut self.memory, stack, note).await?;
+        self.flush()
+    }
+
+    async fn swap_with_above(&mut self, stack: &str, note: &str) -> Result<()> {
+        Database::swap_with_above(&mut self.memory, stack, note).await?;
+        self.flush()
+    }
+
+    async fn swap_with_below(&mut self, stack: &str, note: &str) -> Result<()> {
+        Database::swap_with_below(&mut self.memory, stack, note).await?;
+        self.flush()
+    }
+
+    async fn create_stack(&mut self, stack: &str) -> Result<()> {
+        Database::create_stack(&mut self.memory, stack).await?;
+        self.flush()
+    }
+
+    async fn list_stacks(&self) -> Result<Vec<String>> {
+        Database::list_stacks(&self.memory).await
+    }
+
+    async fn rename_stack(&mut self, stack: &str, new_name: &str) -> Result<()> {
+        Database::rename_stack(&mut self.memory, stack, new_name).await?;
+        self.flush()
+    }
+
+    async fn remove_stack(&mut self, stack: &str) -> Result<()> {
+        Database::remove_stack(&mut self.memory, stack).await?;
+        self.flush()
+    }
+
+    async fn refresh_search_index(&mut self) -> Result<usize> {
+        Database::refresh_search_index(&mut self.memory).await
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+    ) -> Result<Vec<Note>> {
+        Database::search(&self.memory, query, md_static, color_scheme).await
+    }
+
+    async fn mark_opened(&mut self, name: &str) -> Result<()> {
+        Database::mark_opened(&mut self.memory, name).await?;
+        self.flush()
+    }
+
+    async fn timestamps(&self) -> Result<HashMap<String, NoteTimestamps>> {
+        Database::timestamps(&self.memory).await
+    }
+
+    async fn undo(&mut self) -> Result<Option<(JournalEntry, StepReport)>> {
+        let entry = Database::undo(&mut self.memory).await?;
+        self.flush()?;
+        Ok(entry)
+    }
+
+    async fn redo(&mut self) -> Result<Option<(JournalEntry, StepReport)>> {
+        let entry = Database::redo(&mut self.memory).await?;
+        self.flush()?;
+        Ok(entry)
+    }
+
+    async fn journal(&self, limit: usize) -> Result<Vec<JournalEntry>> {
+        Database::journal(&self.memory, limit).await
+    }
+
+    async fn list_trash(&self) -> Result<Vec<TrashEntry>> {
+        Database::list_trash(&self.memory).await
+    }
+
+    async fn restore_from_trash(&mut self, id: i64) -> Result<Vec<LinkRecord>> {
+        let skipped = Database::restore_from_trash(&mut self.memory, id).await?;
+        self.flush()?;
+        Ok(skipped)
+    }
+
+    async fn purge_trash(&mut self, id: Option<i64>) -> Result<usize> {
+        let purged = Database::purge_trash(&mut self.memory, id).await?;
+        self.flush()?;
+        Ok(purged)
+    }
+
+    async fn set_filename(&mut self, name: &str, filename: Option<&str>) -> Result<()> {
+        Database::set_filename(&mut self.memory, name, filename).await?;
+        self.flush()
+    }
+
+    async fn find_dangling_stack_entries(&self) -> Result<Vec<(String, String)>> {
+        Database::find_dangling_stack_entries(&self.memory).await
+    }
+
+    async fn repair_stacks(&mut self) -> Result<()> {
+        Database::repair_stacks(&mut self.memory).await?;
+        self.flush()
+    }
+
+    async fn dump(&self) -> Result<Dump> {
+        Database::dump(&self.memory).await
+    }
+
+    async fn restore_dump(&mut self, dump: &Dump) -> Result<()> {
+        Database::restore_dump(&mut self.memory, dump).await?;
+        self.flush()
+    }
+
+    async fn set_aliases(&mut self, note: &str, aliases: &[String]) -> Result<()> {
+        Database::set_aliases(&mut self.memory, note, aliases).await?;
+        self.flush()
+    }
+
+    async fn properties(&self, note: &str) -> Result<BTreeMap<String, String>> {
+        Database::properties(&self.memory, note).await
+    }
+
+    async fn set_property(&mut self, note: &str, key: &str, value: Option<&str>) -> Result<()> {
+        Database::set_property(&mut self.memory, note, key, value).await?;
+        self.flush()
+    }
+
+    async fn find_by_property(&self, key: &str, value: Option<&str>) -> Result<Vec<String>> {
+        Database::find_by_property(&self.memory, key, value).await
+    }
+
+    async fn list_property_keys(&self) -> Result<Vec<String>> {
+        Database::list_property_keys(&self.memory).await
+    }
+
+    async fn insert_synced_link(&mut self, from: &str, to: &str) -> Result<()> {
+        Database::insert_synced_link(&mut self.memory, from, to).await?;
+        self.flush()
+    }
+
+    async fn find_synced_links_from(&self, from: &str) -> Result<Vec<String>> {
+        Database::find_synced_links_from(&self.memory, from).await
+    }
+
+    async fn merge_notes(
+        &mut self,
+        source: &str,
+        target: &str,
+        rewrite: Option<&BodyRewrite>,
+    ) -> Result<()> {
+        Database::merge_notes(&mut self.memory, source, target, rewrite).await?;
+        self.flush()
+    }
+
+    async fn split_note(
+        &mut self,
+        note: &str,
+        children: &[Note],
+        rewrite: Option<&BodyRewrite>,
+    ) -> Result<()> {
+        Database::split_note(&mut self.memory, note, children, rewrite).await?;
+        self.flush()
+    }
+
+    /// kept in memory only, so as not to clutter storage dir with derived data
+    fn parse_cache(&self) -> ParseCache {
+        self.memory.parse_cache()
+    }
+}
@@ -0,0 +1,239 @@
+use std::{collections::BTreeMap, fmt::Display, io};
+
+use serde::{Deserialize, Serialize};
+
+use crate::front_matter;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkRecord {
+    pub from: String,
+    pub to: String,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StackOrder {
+    pub stack: String,
+    pub notes: Vec<String>,
+}
+
+/// text of a body file without front matter, before and after an operation rewrote it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BodyRewrite {
+    pub filename: String,
+    pub before: String,
+    pub after: String,
+}
+
+impl BodyRewrite {
+    /// puts back `after` if `forward`, or `before` otherwise, unless the file has been
+    /// edited since, in which case it's left as is and `false` is returned
+    pub fn apply(&self, forward: bool) -> io::Result<bool> {
+        let (expected, replacement) = if forward {
+            (&self.before, &self.after)
+        } else {
+            (&self.after, &self.before)
+        };
+        match front_matter::read_body(&self.filename) {
+            Ok(current) if current == *replacement => Ok(true),
+            Ok(current) if current == *expected => {
+                front_matter::write_body(&self.filename, replacement)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+/// everything needed to bring a removed note back
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoteSnapshot {
+    pub name: String,
+    pub filename: Option<String>,
+    pub body: Option<String>,
+    pub created_at: Option<i64>,
+    pub links: Vec<LinkRecord>,
+    pub stacks: Vec<StackOrder>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub properties: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Operation {
+    CreateNote(NoteSnapshot),
+    RemoveNote(NoteSnapshot),
+    RenameNote {
+        from: String,
+        to: String,
+        /// references to the note in bodies, rewritten to the new name
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        rewrites: Vec<BodyRewrite>,
+    },
+    Link(LinkRecord),
+    Unlink(LinkRecord),
+    /// link, created from a `[[wikilink]]` by `sync-links`
+    LinkSynced(LinkRecord),
+    UnlinkSynced(LinkRecord),
+    ReorderStack {
+        stack: String,
+        before: Vec<String>,
+        after: Vec<String>,
+    },
+    CreateStack {
+        stack: String,
+    },
+    RenameStack {
+        from: String,
+        to: String,
+    },
+    RemoveStack(StackOrder),
+    SetAliases {
+        note: String,
+        before: Vec<String>,
+        after: Vec<String>,
+    },
+    SetProperty {
+        note: String,
+        key: String,
+        before: Option<String>,
+        after: Option<String>,
+    },
+    /// removal of `source` and operations, moving its links, stacks, aliases and properties
+    /// to `target`, applied in order
+    MergeNotes {
+        source: String,
+        target: String,
+        operations: Vec<Operation>,
+        /// body of source, appended to body of target
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        rewrite: Option<BodyRewrite>,
+    },
+    /// creation of notes, extracted from sections of `note`, and links to them, applied in order
+    SplitNote {
+        note: String,
+        operations: Vec<Operation>,
+        /// sections of `note`, replaced with links to created notes
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        rewrite: Option<BodyRewrite>,
+    },
+}
+
+impl Operation {
+    pub fn notes(&self) -> Vec<String> {
+        match self {
+            Self::CreateNote(snapshot) | Self::RemoveNote(snapshot) => {
+                vec![snapshot.name.clone()]
+            }
+            Self::RenameNote { from, to, .. } => vec![to.clone(), from.clone()],
+            Self::Link(link)
+            | Self::Unlink(link)
+            | Self::LinkSynced(link)
+            | Self::UnlinkSynced(link) => vec![link.from.clone(), link.to.clone()],
+            Self::ReorderStack { before, after, .. } => {
+                let mut notes = after.clone();
+                notes.extend(before.iter().filter(|note| !after.contains(note)).cloned());
+                notes
+            }
+            Self::CreateStack { .. } | Self::RenameStack { .. } => vec![],
+            Self::RemoveStack(order) => order.notes.clone(),
+            Self::SetAliases { note, .. } | Self::SetProperty { note, .. } => vec![note.clone()],
+            Self::MergeNotes { source, target, .. } => vec![target.clone(), source.clone()],
+            Self::SplitNote { note, .. } => vec![note.clone()],
+        }
+    }
+
+    /// drops body from snapshots of note `name` with body at `filename`, returns whether
+    /// any was dropped
+    pub fn forget_body(&mut self, name: &str, filename: Option<&str>) -> bool {
+        match self {
+            Self::CreateNote(snapshot) | Self::RemoveNote(snapshot) => {
+                if snapshot.name != name || snapshot.filename.as_deref() != filename {
+                    return false;
+                }
+                snapshot.body.take().is_some()
+            }
+            Self::MergeNotes { operations, .. } | Self::SplitNote { operations, .. } => {
+                let mut forgot = false;
+                for operation in operations {
+                    forgot |= operation.forget_body(name, filename);
+                }
+                forgot
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let link_repr = |link: &LinkRecord| match &link.label {
+            Some(label) => format!("`{}` -> `{}` [{}]", link.from, link.to, label),
+            None => format!("`{}` -> `{}`", link.from, link.to),
+        };
+        match self {
+            Self::CreateNote(snapshot) => write!(f, "create note `{}`", snapshot.name),
+            Self::RemoveNote(snapshot) => write!(f, "remove note `{}`", snapshot.name),
+            Self::RenameNote { from, to, .. } => {
+                write!(f, "rename note `{}` -> `{}`", from, to)
+            }
+            Self::Link(link) => write!(f, "link {}", link_repr(link)),
+            Self::Unlink(link) => write!(f, "unlink {}", link_repr(link)),
+            Self::LinkSynced(link) => write!(f, "link synced {}", link_repr(link)),
+            Self::UnlinkSynced(link) => write!(f, "unlink synced {}", link_repr(link)),
+            Self::ReorderStack { stack, .. } => write!(f, "change stack `{}`", stack),
+            Self::CreateStack { stack } => write!(f, "create stack `{}`", stack),
+            Self::RenameStack { from, to } => write!(f, "rename stack `{}` -> `{}`", from, to),
+            Self::RemoveStack(order) => write!(f, "delete stack `{}`", order.stack),
+            Self::SetAliases { note, after, .. } => {
+                write!(f, "set aliases of `{}` to [{}]", note, after.join(", "))
+            }
+            Self::SetProperty {
+                note, key, after, ..
+            } => match after {
+                Some(value) => write!(f, "set property `{}={}` of `{}`", key, value, note),
+                None => write!(f, "unset property `{}` of `{}`", key, note),
+            },
+            Self::MergeNotes { source, target, .. } => {
+                write!(f, "merge note `{}` into `{}`", source, target)
+            }
+            Self::SplitNote {
+                note, operations, ..
+            } => {
+                let created = operations
+                    .iter()
+                    .filter(|operation| matches!(operation, Self::CreateNote(..)))
+                    .count();
+                write!(f, "split {} note(s) out of `{}`", created, note)
+            }
+        }
+    }
+}
+
+/// what undo or redo of a journal entry couldn't bring back
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StepReport {
+    /// links of restored notes to notes, which don't exist anymore
+    pub skipped_links: Vec<LinkRecord>,
+    /// body files, which have been edited since, so their rewrites were left out
+    pub edited_files: Vec<String>,
+}
+
+impl StepReport {
+    pub(crate) fn apply_rewrite(&mut self, rewrite: &BodyRewrite, forward: bool) -> io::Result<()> {
+        if !rewrite.apply(forward)? {
+            self.edited_files.push(rewrite.filename.clone());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: i64,
+    pub operation: Operation,
+    pub undone: bool,
+    pub applied_at: i64,
+}
@@ -0,0 +1,1608 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fs,
+    path::PathBuf,
+};
+
+use async_trait::async_trait;
+use sqlx::{Error, Result};
+
+use crate::{
+    commands::explore::GLOBAL_STACK,
+    config::color::ColorScheme,
+    front_matter,
+    highlight::MarkdownStatic,
+    note::Note,
+    parse_cache::{ParseCache, ParsedFiles},
+};
+
+use super::{
+    dump::{Dump, DumpedNote, DUMP_VERSION},
+    journal::{
+        BodyRewrite, JournalEntry, LinkRecord, NoteSnapshot, Operation, StackOrder, StepReport,
+    },
+    now, parse_labelled_names, parse_names,
+    trash::{self, TrashEntry},
+    Database, LinksFrom, NoteTimestamps, Subgraph,
+};
+
+/// a note with everything, attached to it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct Record {
+    pub filename: Option<String>,
+    pub created_at: Option<i64>,
+    pub updated_at: Option<i64>,
+    pub opened_at: Option<i64>,
+    /// sorted
+    pub aliases: Vec<String>,
+    pub properties: BTreeMap<String, String>,
+    /// names of linked notes with labels of links
+    pub links: BTreeMap<String, Option<String>>,
+    /// names of linked notes, which links were created from `[[wikilinks]]`
+    pub synced: BTreeSet<String>,
+}
+
+/// whole graph of notes, kept in memory, with constraints and cascades of sqlite schema
+#[derive(Debug, Clone, Default)]
+pub struct Memory {
+    pub(super) notes: BTreeMap<String, Record>,
+    /// notes of each stack, topmost first
+    pub(super) stacks: BTreeMap<String, Vec<String>>,
+    pub(super) journal: Vec<JournalEntry>,
+    pub(super) trash: Vec<TrashEntry>,
+    /// by file path; shared with cache, handed out by `parse_cache`
+    parsed_files: ParsedFiles,
+    /// notes' work dir, bodies are trashed under it
+    root: PathBuf,
+}
+
+fn violation(description: String) -> Error {
+    Error::Protocol(description)
+}
+
+impl Memory {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let mut memory = Self {
+            root: root.into(),
+            ..Default::default()
+        };
+        memory.stacks.insert(GLOBAL_STACK.to_string(), vec![]);
+        memory
+    }
+
+    /// runs `f` against a copy of state, which replaces the current one only if `f` succeeds;
+    /// copying is O(vault), so it's only for operations, which may fail partway, others
+    /// check everything before mutating
+    fn transaction<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let mut copy = self.clone();
+        let result = f(&mut copy)?;
+        *self = copy;
+        Ok(result)
+    }
+
+    fn record(&self, name: &str) -> Result<&Record> {
+        self.notes.get(name).ok_or(Error::RowNotFound)
+    }
+
+    fn check_exists(&self, name: &str) -> Result<()> {
+        if !self.notes.contains_key(name) {
+            return Err(violation(format!("note `{}` doesn't exist", name)));
+        }
+        Ok(())
+    }
+
+    fn note(&self, name: &str, color_scheme: ColorScheme) -> Note {
+        let record = self.notes.get(name);
+        let filename = record.and_then(|record| record.filename.clone());
+        let mut note = Note::new(name.to_string(), filename.map(PathBuf::from), color_scheme);
+        if let Some(record) = record {
+            note.set_aliases(record.aliases.clone());
+        }
+        note
+    }
+
+    fn insert_note(&mut self, name: &str, record: Record) -> Result<()> {
+        if self.notes.contains_key(name) {
+            return Err(violation(format!("note `{}` already exists", name)));
+        }
+        if let Some(filename) = &record.filename {
+            if let Some((owner, _)) = self
+                .notes
+                .iter()
+                .find(|(_, other)| other.filename.as_ref() == Some(filename))
+            {
+                return Err(violation(format!(
+                    "file `{}` is already body of `{}`",
+                    filename, owner
+                )));
+            }
+        }
+        self.notes.insert(name.to_string(), record);
+        Ok(())
+    }
+
+    fn save_note(&mut self, note: &Note) -> Result<()> {
+        let record = Record {
+            filename: note
+                .file_path()
+                .map(|path| path.to_string_lossy().to_string()),
+            created_at: Some(now()),
+            updated_at: Some(now()),
+            ..Default::default()
+        };
+        self.insert_note(&note.name(), record)
+    }
+
+    fn insert_link(&mut self, from: &str, to: &str, label: Option<&str>) -> Result<()> {
+        self.check_exists(to)?;
+        let record = self
+            .notes
+            .get_mut(from)
+            .ok_or_else(|| violation(format!("note `{}` doesn't exist", from)))?;
+        if record.links.contains_key(to) {
+            return Err(violation(format!(
+                "link `{}` -> `{}` already exists",
+                from, to
+            )));
+        }
+        record
+            .links
+            .insert(to.to_string(), label.map(str::to_string));
+        Ok(())
+    }
+
+    fn remove_link(&mut self, from: &str, to: &str) {
+        if let Some(record) = self.notes.get_mut(from) {
+            record.links.remove(to);
+            record.synced.remove(to);
+        }
+    }
+
+    fn touch_updated(&mut self, from: &str, to: &str) {
+        for name in [from, to] {
+            if let Some(record) = self.notes.get_mut(name) {
+                record.updated_at = Some(now());
+            }
+        }
+    }
+
+    fn rename_note(&mut self, name: &str, new_name: &str) -> Result<()> {
+        if name != new_name && self.notes.contains_key(new_name) {
+            return Err(violation(format!("note `{}` already exists", new_name)));
+        }
+        let Some(mut record) = self.notes.remove(name) else {
+            return Ok(());
+        };
+        record.updated_at = Some(now());
+        self.notes.insert(new_name.to_string(), record);
+
+        for record in self.notes.values_mut() {
+            if let Some(label) = record.links.remove(name) {
+                record.links.insert(new_name.to_string(), label);
+            }
+            if record.synced.remove(name) {
+                record.synced.insert(new_name.to_string());
+            }
+        }
+        for notes in self.stacks.values_mut() {
+            for note in notes.iter_mut().filter(|note| *note == name) {
+                *note = new_name.to_string();
+            }
+        }
+        Ok(())
+    }
+
+    fn stack_order(&self, stack: &str) -> Vec<String> {
+        self.stacks.get(stack).cloned().unwrap_or_default()
+    }
+
+    fn set_stack_order(&mut self, stack: &str, notes: &[String]) -> Result<()> {
+        for note in notes {
+            self.check_exists(note)?;
+        }
+        match self.stacks.get_mut(stack) {
+            Some(order) => {
+                *order = notes.to_vec();
+                Ok(())
+            }
+            None if notes.is_empty() => Ok(()),
+            None => Err(violation(format!("stack `{}` doesn't exist", stack))),
+        }
+    }
+
+    fn index_in_stack(&self, stack: &str, note: &str) -> Result<usize> {
+        self.stacks
+            .get(stack)
+            .and_then(|notes| notes.iter().position(|stacked| stacked == note))
+            .ok_or(Error::RowNotFound)
+    }
+
+    fn push_note_to_stack(&mut self, stack: &str, note: &str) -> Result<()> {
+        self.check_exists(note)?;
+        let notes = self
+            .stacks
+            .get_mut(stack)
+            .ok_or_else(|| violation(format!("stack `{}` doesn't exist", stack)))?;
+        if notes.iter().any(|stacked| stacked == note) {
+            return Err(violation(format!(
+                "note `{}` is already in stack `{}`",
+                note, stack
+            )));
+        }
+        notes.insert(0, note.to_string());
+        Ok(())
+    }
+
+    fn pop_note_from_stack(&mut self, stack: &str, note: &str) -> Result<()> {
+        let index = self.index_in_stack(stack, note)?;
+        if let Some(notes) = self.stacks.get_mut(stack) {
+            notes.remove(index);
+        }
+        Ok(())
+    }
+
+    fn swap_with_above(&mut self, stack: &str, note: &str) -> Result<()> {
+        let index = self.index_in_stack(stack, note)?;
+        if let Some(notes) = self.stacks.get_mut(stack) {
+            if index + 1 < notes.len() {
+                notes.swap(index, index + 1);
+            }
+        }
+        Ok(())
+    }
+
+    fn swap_with_below(&mut self, stack: &str, note: &str) -> Result<()> {
+        let index = self.index_in_stack(stack, note)?;
+        if let Some(notes) = self.stacks.get_mut(stack) {
+            if index > 0 {
+                notes.swap(index, index - 1);
+            }
+        }
+        Ok(())
+    }
+
+    fn move_to_topmost(&mut self, stack: &str, note: &str) -> Result<()> {
+        let index = self.index_in_stack(stack, note)?;
+        if let Some(notes) = self.stacks.get_mut(stack) {
+            let note = notes.remove(index);
+            notes.insert(0, note);
+        }
+        Ok(())
+    }
+
+    fn insert_into_stack(&mut self, stack: &str, note: &str, index: usize) -> Result<()> {
+        let mut notes = self.stack_order(stack);
+        let index = std::cmp::min(index, notes.len());
+        notes.insert(index, note.to_string());
+        self.set_stack_order(stack, &notes)
+    }
+
+    fn create_stack(&mut self, stack: &str) -> Result<()> {
+        if self.stacks.contains_key(stack) {
+            return Err(violation(format!("stack `{}` already exists", stack)));
+        }
+        self.stacks.insert(stack.to_string(), vec![]);
+        Ok(())
+    }
+
+    fn rename_stack(&mut self, stack: &str, new_name: &str) -> Result<()> {
+        if stack != new_name && self.stacks.contains_key(new_name) {
+            return Err(violation(format!("stack `{}` already exists", new_name)));
+        }
+        if let Some(notes) = self.stacks.remove(stack) {
+            self.stacks.insert(new_name.to_string(), notes);
+        }
+        Ok(())
+    }
+
+    fn find_aliases(&self, note: &str) -> Vec<String> {
+        self.notes
+            .get(note)
+            .map(|record| record.aliases.clone())
+            .unwrap_or_default()
+    }
+
+    fn set_aliases(&mut self, note: &str, aliases: &[String]) -> Result<()> {
+        for alias in aliases {
+            if let Some((owner, _)) = self
+                .notes
+                .iter()
+                .find(|(name, record)| *name != note && record.aliases.contains(alias))
+            {
+                return Err(violation(format!(
+                    "alias `{}` already belongs to `{}`",
+                    alias, owner
+                )));
+            }
+        }
+        match self.notes.get_mut(note) {
+            Some(record) => {
+                record.aliases = aliases.to_vec();
+                record.aliases.sort();
+                Ok(())
+            }
+            None if aliases.is_empty() => Ok(()),
+            None => Err(violation(format!("note `{}` doesn't exist", note))),
+        }
+    }
+
+    /// note, which is named `alias` or already has it as an alias
+    fn alias_owner(&self, alias: &str) -> Option<String> {
+        if self.notes.contains_key(alias) {
+            return Some(alias.to_string());
+        }
+        self.notes
+            .iter()
+            .find(|(_, record)| record.aliases.iter().any(|own| own == alias))
+            .map(|(name, _)| name.clone())
+    }
+
+    fn find_properties(&self, note: &str) -> BTreeMap<String, String> {
+        self.notes
+            .get(note)
+            .map(|record| record.properties.clone())
+            .unwrap_or_default()
+    }
+
+    fn set_property(&mut self, note: &str, key: &str, value: Option<&str>) -> Result<()> {
+        match (self.notes.get_mut(note), value) {
+            (Some(record), Some(value)) => {
+                record.properties.insert(key.to_string(), value.to_string());
+            }
+            (Some(record), None) => {
+                record.properties.remove(key);
+            }
+            (None, Some(_)) => {
+                return Err(violation(format!("note `{}` doesn't exist", note)));
+            }
+            (None, None) => {}
+        }
+        Ok(())
+    }
+
+    fn snapshot_note(&self, name: &str) -> Result<NoteSnapshot> {
+        let record = self.record(name)?;
+        let mut links = record
+            .links
+            .iter()
+            .map(|(to, label)| LinkRecord {
+                from: name.to_string(),
+                to: to.clone(),
+                label: label.clone(),
+            })
+            .collect::<Vec<_>>();
+        for (from, other) in &self.notes {
+            if from == name {
+                continue;
+            }
+            if let Some(label) = other.links.get(name) {
+                links.push(LinkRecord {
+                    from: from.clone(),
+                    to: name.to_string(),
+                    label: label.clone(),
+                });
+            }
+        }
+        let stacks = self
+            .stacks
+            .iter()
+            .filter(|(_, notes)| notes.iter().any(|note| note == name))
+            .map(|(stack, notes)| StackOrder {
+                stack: stack.clone(),
+                notes: notes.clone(),
+            })
+            .collect();
+        let body = record
+            .filename
+            .as_ref()
+            .and_then(|filename| fs::read_to_string(filename).ok());
+
+        Ok(NoteSnapshot {
+            name: name.to_string(),
+            filename: record.filename.clone(),
+            body,
+            created_at: record.created_at,
+            links,
+            stacks,
+            aliases: record.aliases.clone(),
+            properties: record.properties.clone(),
+        })
+    }
+
+    fn drop_note(&mut self, name: &str) -> Result<NoteSnapshot> {
+        let snapshot = self.snapshot_note(name)?;
+        for notes in self.stacks.values_mut() {
+            notes.retain(|note| note != name);
+        }
+        if let Some(filename) = self.notes.remove(name).and_then(|record| record.filename) {
+            self.parsed_files.lock().unwrap().remove(&filename);
+        }
+        for record in self.notes.values_mut() {
+            record.links.remove(name);
+            record.synced.remove(name);
+        }
+
+        Ok(snapshot)
+    }
+
+    fn insert_restored_note(&mut self, snapshot: &NoteSnapshot) -> Result<()> {
+        let record = Record {
+            filename: snapshot.filename.clone(),
+            created_at: snapshot.created_at,
+            updated_at: Some(now()),
+            ..Default::default()
+        };
+        self.insert_note(&snapshot.name, record)?;
+
+        if let Some(filename) = &snapshot.filename {
+            let file_path = &self.root.join(filename);
+            trash::move_from_trash(&self.root, filename)?;
+            if let (Some(body), false) = (&snapshot.body, file_path.exists()) {
+                fs::write(file_path, body)?;
+            }
+        }
+        self.trash.retain(|entry| {
+            entry.snapshot.name != snapshot.name || entry.snapshot.filename != snapshot.filename
+        });
+
+        Ok(())
+    }
+
+    /// links to notes, which have been removed since, are skipped and put into `report`
+    fn restore_note(&mut self, snapshot: &NoteSnapshot, report: &mut StepReport) -> Result<()> {
+        self.insert_restored_note(snapshot)?;
+        for link in &snapshot.links {
+            if !self.notes.contains_key(&link.from) || !self.notes.contains_key(&link.to) {
+                report.skipped_links.push(link.clone());
+                continue;
+            }
+            self.insert_link(&link.from, &link.to, link.label.as_deref())?;
+        }
+        for order in &snapshot.stacks {
+            self.set_stack_order(&order.stack, &order.notes)?;
+        }
+        self.set_aliases(&snapshot.name, &snapshot.aliases)?;
+        for (key, value) in &snapshot.properties {
+            self.set_property(&snapshot.name, key, Some(value))?;
+        }
+
+        Ok(())
+    }
+
+    fn trash_note(&mut self, snapshot: &NoteSnapshot) {
+        // body stays in trash dir
+        let snapshot = NoteSnapshot {
+            body: None,
+            ..snapshot.clone()
+        };
+        let id = self.trash.iter().map(|entry| entry.id).max().unwrap_or(0) + 1;
+        self.trash.push(TrashEntry {
+            id,
+            snapshot,
+            trashed_at: now(),
+        });
+    }
+
+    fn record_operation(&mut self, operation: Operation) {
+        // a new mutation invalidates everything, that could have been redone
+        self.journal.retain(|entry| !entry.undone);
+        let id = self.journal.iter().map(|entry| entry.id).max().unwrap_or(0) + 1;
+        self.journal.push(JournalEntry {
+            id,
+            operation,
+            undone: false,
+            applied_at: now(),
+        });
+    }
+
+    fn record_reorder(&mut self, stack: &str, before: Vec<String>) {
+        let after = self.stack_order(stack);
+        if before != after {
+            self.record_operation(Operation::ReorderStack {
+                stack: stack.to_string(),
+                before,
+                after,
+            });
+        }
+    }
+
+    /// applies `operation` again if `forward`, or its inverse otherwise;
+    /// returns the operation to be stored back into journal and body files, which
+    /// have to be moved to trash once the transaction is committed
+    fn apply(
+        &mut self,
+        mut operation: Operation,
+        forward: bool,
+        report: &mut StepReport,
+    ) -> Result<(Operation, Vec<String>)> {
+        let mut stale_files = vec![];
+        match (&mut operation, forward) {
+            (Operation::CreateNote(snapshot), true) | (Operation::RemoveNote(snapshot), false) => {
+                self.restore_note(snapshot, report)?;
+            }
+            (Operation::CreateNote(snapshot), false) | (Operation::RemoveNote(snapshot), true) => {
+                *snapshot = self.drop_note(&snapshot.name)?;
+                self.trash_note(snapshot);
+                stale_files.extend(snapshot.filename.clone());
+            }
+            (Operation::RenameNote { from, to, rewrites }, forward) => {
+                let (from, to) = if forward { (from, to) } else { (to, from) };
+                self.rename_note(from, to)?;
+                for rewrite in rewrites.iter() {
+                    report.apply_rewrite(rewrite, forward)?;
+                }
+            }
+            (Operation::Link(link), true) | (Operation::Unlink(link), false) => {
+                self.insert_link(&link.from, &link.to, link.label.as_deref())?;
+            }
+            (Operation::Link(link), false) | (Operation::Unlink(link), true) => {
+                self.remove_link(&link.from, &link.to);
+            }
+            (Operation::LinkSynced(link), true) | (Operation::UnlinkSynced(link), false) => {
+                self.insert_link(&link.from, &link.to, link.label.as_deref())?;
+                if let Some(record) = self.notes.get_mut(&link.from) {
+                    record.synced.insert(link.to.clone());
+                }
+            }
+            (Operation::LinkSynced(link), false) | (Operation::UnlinkSynced(link), true) => {
+                self.remove_link(&link.from, &link.to);
+            }
+            (
+                Operation::ReorderStack {
+                    stack,
+                    before,
+                    after,
+                },
+                forward,
+            ) => {
+                let notes = if forward { after } else { before };
+                self.set_stack_order(stack, notes)?;
+            }
+            (Operation::CreateStack { stack }, true) => {
+                self.create_stack(stack)?;
+            }
+            (Operation::CreateStack { stack }, false) => {
+                self.stacks.remove(stack.as_str());
+            }
+            (Operation::RenameStack { from, to }, forward) => {
+                let (from, to) = if forward { (from, to) } else { (to, from) };
+                self.rename_stack(from, to)?;
+            }
+            (Operation::RemoveStack(order), true) => {
+                self.stacks.remove(&order.stack);
+            }
+            (Operation::RemoveStack(order), false) => {
+                self.create_stack(&order.stack)?;
+                self.set_stack_order(&order.stack, &order.notes)?;
+            }
+            (
+                Operation::SetAliases {
+                    note,
+                    before,
+                    after,
+                },
+                forward,
+            ) => {
+                let aliases = if forward { after } else { before };
+                self.set_aliases(note, aliases)?;
+            }
+            (
+                Operation::SetProperty {
+                    note,
+                    key,
+                    before,
+                    after,
+                },
+                forward,
+            ) => {
+                let value = if forward { after } else { before };
+                self.set_property(note, key, value.as_deref())?;
+            }
+            (Operation::MergeNotes { operations, .. }, forward)
+            | (Operation::SplitNote { operations, .. }, forward) => {
+                let mut steps = std::mem::take(operations);
+                if !forward {
+                    steps.reverse();
+                }
+                for step in steps {
+                    let (step, files) = self.apply(step, forward, report)?;
+                    operations.push(step);
+                    stale_files.extend(files);
+                }
+                if !forward {
+                    operations.reverse();
+                }
+            }
+        }
+        if let Operation::MergeNotes {
+            rewrite: Some(rewrite),
+            ..
+        }
+        | Operation::SplitNote {
+            rewrite: Some(rewrite),
+            ..
+        } = &operation
+        {
+            report.apply_rewrite(rewrite, forward)?;
+        }
+
+        Ok((operation, stale_files))
+    }
+
+    fn step_journal(&mut self, forward: bool) -> Result<Option<(JournalEntry, StepReport)>> {
+        let entry = if forward {
+            self.journal
+                .iter()
+                .filter(|entry| entry.undone)
+                .min_by_key(|entry| entry.id)
+        } else {
+            self.journal
+                .iter()
+                .filter(|entry| !entry.undone)
+                .max_by_key(|entry| entry.id)
+        };
+        let Some(entry) = entry.cloned() else {
+            return Ok(None);
+        };
+        log::debug!("journal step (forward: {}) of {:?}", forward, entry);
+
+        let mut report = StepReport::default();
+        let (operation, stale_files) = self.transaction(|memory| {
+            let (operation, stale_files) =
+                memory.apply(entry.operation.clone(), forward, &mut report)?;
+            if let Some(stored) = memory
+                .journal
+                .iter_mut()
+                .find(|stored| stored.id == entry.id)
+            {
+                stored.operation = operation.clone();
+                stored.undone = !forward;
+            }
+            Ok((operation, stale_files))
+        })?;
+
+        for file_path in stale_files {
+            trash::move_to_trash(&self.root, &file_path)?;
+        }
+
+        let entry = JournalEntry {
+            operation,
+            undone: !forward,
+            ..entry
+        };
+        Ok(Some((entry, report)))
+    }
+
+    fn labelled_links_from(
+        &self,
+        from: &str,
+        color_scheme: ColorScheme,
+    ) -> Vec<(Note, Option<String>)> {
+        let Some(record) = self.notes.get(from) else {
+            return vec![];
+        };
+        record
+            .links
+            .iter()
+            .rev()
+            .map(|(to, label)| (self.note(to, color_scheme), label.clone()))
+            .collect()
+    }
+
+    fn labelled_links_to(
+        &self,
+        to: &str,
+        color_scheme: ColorScheme,
+    ) -> Vec<(Note, Option<String>)> {
+        self.notes
+            .iter()
+            .rev()
+            .filter_map(|(from, record)| {
+                record
+                    .links
+                    .get(to)
+                    .map(|label| (self.note(from, color_scheme), label.clone()))
+            })
+            .collect()
+    }
+
+    /// notes of subgraph, in order of breadth-first traversal, and links from each of them
+    fn reachable(
+        &self,
+        from: &str,
+        color_scheme: ColorScheme,
+        straight: bool,
+        depth: Option<usize>,
+    ) -> Result<(Vec<Note>, LinksFrom)> {
+        if !self.notes.contains_key(from) {
+            return Err(Error::RowNotFound);
+        }
+        let mut notes = vec![self.note(from, color_scheme)];
+        let mut links = HashMap::new();
+        let mut visited = HashSet::from([from.to_string()]);
+        let mut layer = vec![from.to_string()];
+        let mut layer_depth = 0;
+        while !layer.is_empty() && depth.is_none_or(|depth| layer_depth < depth) {
+            let mut next_layer = vec![];
+            for name in layer {
+                let next = if straight {
+                    self.labelled_links_from(&name, color_scheme)
+                } else {
+                    self.labelled_links_to(&name, color_scheme)
+                };
+                for (note, _label) in &next {
+                    if visited.insert(note.name()) {
+                        next_layer.push(note.name());
+                        notes.push(note.clone());
+                    }
+                }
+                let next = next
+                    .into_iter()
+                    .map(|(note, label)| (note.name(), label))
+                    .collect();
+                links.insert(name, next);
+            }
+            layer = next_layer;
+            layer_depth += 1;
+        }
+        Ok((notes, links))
+    }
+}
+
+#[async_trait]
+impl Database for Memory {
+    async fn save(&mut self, note: &Note) -> Result<()> {
+        log::debug!("saving note to memory {:?}", note);
+
+        self.save_note(note)?;
+        let snapshot = NoteSnapshot {
+            name: note.name(),
+            filename: note
+                .file_path()
+                .map(|path| path.to_string_lossy().to_string()),
+            body: None,
+            created_at: None,
+            links: vec![],
+            stacks: vec![],
+            aliases: vec![],
+            properties: BTreeMap::new(),
+        };
+        self.record_operation(Operation::CreateNote(snapshot));
+        Ok(())
+    }
+
+    async fn list(
+        &self,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+    ) -> Result<Vec<Note>> {
+        let res = self
+            .notes
+            .keys()
+            .rev()
+            .map(|name| self.note(name, color_scheme))
+            .collect();
+        Ok(parse_names(res, md_static).await)
+    }
+
+    async fn get(
+        &self,
+        name: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+    ) -> Result<Note> {
+        // exact name wins over an alias
+        let owner = self.alias_owner(name).ok_or(Error::RowNotFound)?;
+        let res = parse_names(vec![self.note(&owner, color_scheme)], md_static).await;
+        res.into_iter().next().ok_or(Error::RowNotFound)
+    }
+
+    async fn find_links_from(
+        &self,
+        from: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+        straight: bool,
+    ) -> Result<Vec<Note>> {
+        let res = self
+            .find_labelled_links_from(from, md_static, color_scheme, straight)
+            .await?;
+        Ok(res.into_iter().map(|(note, _label)| note).collect())
+    }
+
+    async fn find_links_to(
+        &self,
+        to: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+        straight: bool,
+    ) -> Result<Vec<Note>> {
+        let res = self
+            .find_labelled_links_to(to, md_static, color_scheme, straight)
+            .await?;
+        Ok(res.into_iter().map(|(note, _label)| note).collect())
+    }
+
+    async fn find_labelled_links_from(
+        &self,
+        from: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+        straight: bool,
+    ) -> Result<Vec<(Note, Option<String>)>> {
+        let res = if straight {
+            self.labelled_links_from(from, color_scheme)
+        } else {
+            self.labelled_links_to(from, color_scheme)
+        };
+        Ok(parse_labelled_names(res, md_static).await)
+    }
+
+    async fn find_labelled_links_to(
+        &self,
+        to: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+        straight: bool,
+    ) -> Result<Vec<(Note, Option<String>)>> {
+        let res = if straight {
+            self.labelled_links_to(to, color_scheme)
+        } else {
+            self.labelled_links_from(to, color_scheme)
+        };
+        Ok(parse_labelled_names(res, md_static).await)
+    }
+
+    async fn reachable_subgraph(
+        &self,
+        from: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+        straight: bool,
+        depth: Option<usize>,
+    ) -> Result<Subgraph> {
+        let (notes, links) = self.reachable(from, color_scheme, straight, depth)?;
+        Ok(Subgraph::new(notes, links, md_static))
+    }
+
+    async fn list_link_labels(&self) -> Result<Vec<String>> {
+        let labels = self
+            .notes
+            .values()
+            .flat_map(|record| record.links.values().flatten().cloned())
+            .collect::<BTreeSet<_>>();
+        Ok(labels.into_iter().collect())
+    }
+
+    async fn all_labelled_links(&self) -> Result<Vec<LinkRecord>> {
+        let links = self
+            .notes
+            .iter()
+            .flat_map(|(from, record)| {
+                record.links.iter().map(|(to, label)| LinkRecord {
+                    from: from.clone(),
+                    to: to.clone(),
+                    label: label.clone(),
+                })
+            })
+            .collect();
+        Ok(links)
+    }
+
+    async fn insert_link(
+        &mut self,
+        mut from: &str,
+        mut to: &str,
+        straight: bool,
+        label: Option<&str>,
+    ) -> Result<()> {
+        if !straight {
+            std::mem::swap(&mut from, &mut to);
+        }
+        log::debug!("saving link {} -> {} ({:?})", from, to, label);
+
+        self.insert_link(from, to, label)?;
+        self.touch_updated(from, to);
+        self.record_operation(Operation::Link(LinkRecord {
+            from: from.to_string(),
+            to: to.to_string(),
+            label: label.map(str::to_string),
+        }));
+        Ok(())
+    }
+
+    async fn remove_link(&mut self, mut from: &str, mut to: &str, straight: bool) -> Result<()> {
+        if !straight {
+            std::mem::swap(&mut from, &mut to);
+        }
+        log::debug!("removing link {} -> {} ", from, to);
+
+        let record = self.notes.get(from);
+        let label = record.and_then(|record| record.links.get(to).cloned());
+        let synced = record.is_some_and(|record| record.synced.contains(to));
+        self.remove_link(from, to);
+        self.touch_updated(from, to);
+        if let Some(label) = label {
+            let link = LinkRecord {
+                from: from.to_string(),
+                to: to.to_string(),
+                label,
+            };
+            let operation = if synced {
+                Operation::UnlinkSynced(link)
+            } else {
+                Operation::Unlink(link)
+            };
+            self.record_operation(operation);
+        }
+        Ok(())
+    }
+
+    async fn remove_note(&mut self, note: &Note) -> Result<()> {
+        log::debug!("removing note {:?}", note);
+
+        let snapshot = self.drop_note(&note.name())?;
+        self.trash_note(&snapshot);
+        self.record_operation(Operation::RemoveNote(snapshot));
+
+        if let Some(file_path) = note.file_path() {
+            trash::move_to_trash(&self.root, &file_path.to_string_lossy())?;
+        }
+
+        Ok(())
+    }
+
+    async fn rename_note(
+        &mut self,
+        note: &Note,
+        new_name: &str,
+        rewrites: &[BodyRewrite],
+    ) -> Result<()> {
+        log::debug!("renaming note {:?} -> {}", note, new_name);
+
+        self.rename_note(&note.name(), new_name)?;
+        self.record_operation(Operation::RenameNote {
+            from: note.name(),
+            to: new_name.to_string(),
+            rewrites: rewrites.to_vec(),
+        });
+        Ok(())
+    }
+
+    async fn push_note_to_stack(&mut self, stack: &str, note: &str) -> Result<()> {
+        let before = self.stack_order(stack);
+        self.push_note_to_stack(stack, note)?;
+        self.record_reorder(stack, before);
+        Ok(())
+    }
+
+    async fn select_from_stack(
+        &mut self,
+        stack: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+    ) -> Result<Vec<Note>> {
+        let res = self
+            .stack_order(stack)
+            .iter()
+            .map(|name| self.note(name, color_scheme))
+            .collect();
+        Ok(parse_names(res, md_static).await)
+    }
+
+    async fn pop_note_from_stack(&mut self, stack: &str, note: &str) -> Result<()> {
+        let before = self.stack_order(stack);
+        self.pop_note_from_stack(stack, note)?;
+        self.record_reorder(stack, before);
+        Ok(())
+    }
+
+    async fn move_to_topmost(&mut self, stack: &str, note: &str) -> Result<()> {
+        let before = self.stack_order(stack);
+        self.move_to_topmost(stack, note)?;
+        self.record_reorder(stack, before);
+        Ok(())
+    }
+
+    async fn swap_with_above(&mut self, stack: &str, note: &str) -> Result<()> {
+        let before = self.stack_order(stack);
+        self.swap_with_above(stack, note)?;
+        self.record_reorder(stack, before);
+        Ok(())
+    }
+
+    async fn swap_with_below(&mut self, stack: &str, note: &str) -> Result<()> {
+        let before = self.stack_order(stack);
+        self.swap_with_below(stack, note)?;
+        self.record_reorder(stack, before);
+        Ok(())
+    }
+
+    async fn create_stack(&mut self, stack: &str) -> Result<()> {
+        log::debug!("creating stack {}", stack);
+
+        self.create_stack(stack)?;
+        self.record_operation(Operation::CreateStack {
+            stack: stack.to_string(),
+        });
+        Ok(())
+    }
+
+    async fn list_stacks(&self) -> Result<Vec<String>> {
+        Ok(self.stacks.keys().cloned().collect())
+    }
+
+    async fn rename_stack(&mut self, stack: &str, new_name: &str) -> Result<()> {
+        log::debug!("renaming stack {} -> {}", stack, new_name);
+
+        self.rename_stack(stack, new_name)?;
+        self.record_operation(Operation::RenameStack {
+            from: stack.to_string(),
+            to: new_name.to_string(),
+        });
+        Ok(())
+    }
+
+    async fn remove_stack(&mut self, stack: &str) -> Result<()> {
+        log::debug!("removing stack {}", stack);
+
+        let notes = self.stack_order(stack);
+        self.stacks.remove(stack);
+        self.record_operation(Operation::RemoveStack(StackOrder {
+            stack: stack.to_string(),
+            notes,
+        }));
+        Ok(())
+    }
+
+    async fn refresh_search_index(&mut self) -> Result<usize> {
+        // bodies are read on every search
+        Ok(0)
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+    ) -> Result<Vec<Note>> {
+        log::debug!("full-text search of `{}`", query);
+
+        let terms = query
+            .split_whitespace()
+            .map(|term| term.trim_matches('"').to_lowercase())
+            .filter(|term| !term.is_empty())
+            .collect::<Vec<_>>();
+        if terms.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut ranked = vec![];
+        for (name, record) in &self.notes {
+            let Some(filename) = &record.filename else {
+                continue;
+            };
+            let Ok(body) = front_matter::read_body(filename) else {
+                continue;
+            };
+            let body = body.to_lowercase();
+            let counts = terms
+                .iter()
+                .map(|term| body.matches(term.as_str()).count())
+                .collect::<Vec<_>>();
+            if counts.iter().all(|count| *count > 0) {
+                ranked.push((counts.iter().sum::<usize>(), name));
+            }
+        }
+        ranked.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(b.1)));
+
+        let res = ranked
+            .into_iter()
+            .map(|(_, name)| self.note(name, color_scheme))
+            .collect();
+        Ok(parse_names(res, md_static).await)
+    }
+
+    async fn mark_opened(&mut self, name: &str) -> Result<()> {
+        if let Some(record) = self.notes.get_mut(name) {
+            record.opened_at = Some(now());
+        }
+        Ok(())
+    }
+
+    async fn timestamps(&self) -> Result<HashMap<String, NoteTimestamps>> {
+        Ok(self
+            .notes
+            .iter()
+            .map(|(name, record)| {
+                let timestamps = NoteTimestamps {
+                    created: record.created_at,
+                    updated: record.updated_at,
+                    opened: record.opened_at,
+                };
+                (name.clone(), timestamps)
+            })
+            .collect())
+    }
+
+    async fn undo(&mut self) -> Result<Option<(JournalEntry, StepReport)>> {
+        self.step_journal(false)
+    }
+
+    async fn redo(&mut self) -> Result<Option<(JournalEntry, StepReport)>> {
+        self.step_journal(true)
+    }
+
+    async fn journal(&self, limit: usize) -> Result<Vec<JournalEntry>> {
+        let mut res = self.journal.clone();
+        res.sort_by_key(|entry| std::cmp::Reverse(entry.id));
+        res.truncate(limit);
+        Ok(res)
+    }
+
+    async fn list_trash(&self) -> Result<Vec<TrashEntry>> {
+        let mut res = self.trash.clone();
+        res.sort_by_key(|entry| std::cmp::Reverse(entry.id));
+        Ok(res)
+    }
+
+    async fn restore_from_trash(&mut self, id: i64) -> Result<Vec<LinkRecord>> {
+        self.transaction(|memory| {
+            let entry = memory
+                .trash
+                .iter()
+                .find(|entry| entry.id == id)
+                .cloned()
+                .ok_or(Error::RowNotFound)?;
+            let snapshot = entry.snapshot;
+            log::debug!("restoring from trash {:?}", snapshot);
+
+            memory.insert_restored_note(&snapshot)?;
+
+            let mut skipped = vec![];
+            for link in snapshot.links {
+                if memory.notes.contains_key(&link.from) && memory.notes.contains_key(&link.to) {
+                    memory.insert_link(&link.from, &link.to, link.label.as_deref())?;
+                } else {
+                    skipped.push(link);
+                }
+            }
+            for order in snapshot.stacks {
+                if !memory.stacks.contains_key(&order.stack) {
+                    continue;
+                }
+                let index = order
+                    .notes
+                    .iter()
+                    .position(|note| note == &snapshot.name)
+                    .unwrap_or(0);
+                memory.insert_into_stack(&order.stack, &snapshot.name, index)?;
+            }
+            // may have been taken, while the note was in trash
+            let aliases = snapshot
+                .aliases
+                .into_iter()
+                .filter(|alias| memory.alias_owner(alias).is_none())
+                .collect::<Vec<_>>();
+            memory.set_aliases(&snapshot.name, &aliases)?;
+            for (key, value) in &snapshot.properties {
+                memory.set_property(&snapshot.name, key, Some(value))?;
+            }
+            memory.trash.retain(|entry| entry.id != id);
+
+            let restored = memory.snapshot_note(&snapshot.name)?;
+            memory.record_operation(Operation::CreateNote(restored));
+            Ok(skipped)
+        })
+    }
+
+    async fn purge_trash(&mut self, id: Option<i64>) -> Result<usize> {
+        let (purged, kept): (Vec<_>, Vec<_>) = self
+            .trash
+            .drain(..)
+            .partition(|entry| id.is_none_or(|id| entry.id == id));
+        self.trash = kept;
+        for entry in &purged {
+            // purged note shouldn't be recoverable from journal either
+            let snapshot = &entry.snapshot;
+            for journal_entry in self.journal.iter_mut() {
+                journal_entry
+                    .operation
+                    .forget_body(&snapshot.name, snapshot.filename.as_deref());
+            }
+            if let Some(filename) = &entry.snapshot.filename {
+                trash::remove_from_trash(&self.root, filename)?;
+            }
+        }
+
+        Ok(purged.len())
+    }
+
+    async fn set_filename(&mut self, name: &str, filename: Option<&str>) -> Result<()> {
+        log::debug!("setting body of {} to {:?}", name, filename);
+
+        if let Some(filename) = filename {
+            if let Some((owner, _)) = self.notes.iter().find(|(owner, record)| {
+                *owner != name && record.filename.as_deref() == Some(filename)
+            }) {
+                return Err(violation(format!(
+                    "file `{}` is already body of `{}`",
+                    filename, owner
+                )));
+            }
+        }
+        if let Some(record) = self.notes.get_mut(name) {
+            if let Some(previous) = &record.filename {
+                self.parsed_files.lock().unwrap().remove(previous);
+            }
+            record.filename = filename.map(str::to_string);
+            record.updated_at = Some(now());
+        }
+
+        Ok(())
+    }
+
+    async fn find_dangling_stack_entries(&self) -> Result<Vec<(String, String)>> {
+        let mut res = vec![];
+        for (stack, notes) in &self.stacks {
+            for note in notes {
+                if !self.notes.contains_key(note) {
+                    res.push((stack.clone(), note.clone()));
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    async fn repair_stacks(&mut self) -> Result<()> {
+        let existing = self.notes.keys().cloned().collect::<BTreeSet<_>>();
+        for notes in self.stacks.values_mut() {
+            notes.retain(|note| existing.contains(note));
+        }
+        Ok(())
+    }
+
+    async fn dump(&self) -> Result<Dump> {
+        let notes = self
+            .notes
+            .iter()
+            .map(|(name, record)| DumpedNote {
+                name: name.clone(),
+                tag: record.filename.is_none(),
+                filename: record.filename.clone(),
+                created_at: record.created_at,
+                updated_at: record.updated_at,
+                opened_at: record.opened_at,
+                aliases: record.aliases.clone(),
+                properties: record.properties.clone(),
+            })
+            .collect();
+        let links = self
+            .notes
+            .iter()
+            .flat_map(|(from, record)| {
+                record.links.iter().map(|(to, label)| LinkRecord {
+                    from: from.clone(),
+                    to: to.clone(),
+                    label: label.clone(),
+                })
+            })
+            .collect();
+        let stacks = self
+            .stacks
+            .iter()
+            .map(|(stack, notes)| StackOrder {
+                stack: stack.clone(),
+                notes: notes.clone(),
+            })
+            .collect();
+
+        Ok(Dump {
+            version: DUMP_VERSION,
+            notes,
+            links,
+            stacks,
+        })
+    }
+
+    async fn restore_dump(&mut self, dump: &Dump) -> Result<()> {
+        log::debug!("restoring {} notes from dump", dump.notes.len());
+
+        if !self.notes.is_empty() {
+            return Err(violation(format!(
+                "database already contains {} notes",
+                self.notes.len()
+            )));
+        }
+        self.transaction(|memory| {
+            for note in &dump.notes {
+                let record = Record {
+                    filename: note.filename.clone(),
+                    created_at: note.created_at,
+                    updated_at: note.updated_at,
+                    opened_at: note.opened_at,
+                    ..Default::default()
+                };
+                memory.insert_note(&note.name, record)?;
+            }
+            for note in &dump.notes {
+                memory.set_aliases(&note.name, &note.aliases)?;
+                for (key, value) in &note.properties {
+                    memory.set_property(&note.name, key, Some(value))?;
+                }
+            }
+            for link in &dump.links {
+                memory.insert_link(&link.from, &link.to, link.label.as_deref())?;
+            }
+            for order in &dump.stacks {
+                memory.stacks.entry(order.stack.clone()).or_default();
+                memory.set_stack_order(&order.stack, &order.notes)?;
+            }
+            Ok(())
+        })
+    }
+
+    async fn set_aliases(&mut self, note: &str, aliases: &[String]) -> Result<()> {
+        log::debug!("setting aliases of {} to {:?}", note, aliases);
+
+        let before = self.find_aliases(note);
+        let mut after: Vec<String> = vec![];
+        for alias in aliases {
+            let alias = alias.trim();
+            if alias.is_empty() || alias == note || after.iter().any(|a| a == alias) {
+                continue;
+            }
+            match self.alias_owner(alias) {
+                Some(owner) if owner != note => {
+                    return Err(violation(format!(
+                        "`{}` is already a name or an alias of `{}`",
+                        alias, owner
+                    )));
+                }
+                _ => after.push(alias.to_string()),
+            }
+        }
+        after.sort();
+        if before != after {
+            self.set_aliases(note, &after)?;
+            self.record_operation(Operation::SetAliases {
+                note: note.to_string(),
+                before,
+                after,
+            });
+        }
+        Ok(())
+    }
+
+    async fn properties(&self, note: &str) -> Result<BTreeMap<String, String>> {
+        Ok(self.find_properties(note))
+    }
+
+    async fn set_property(&mut self, note: &str, key: &str, value: Option<&str>) -> Result<()> {
+        log::debug!("setting property {} of {} to {:?}", key, note, value);
+
+        let before = self.find_properties(note).remove(key);
+        if before.as_deref() != value {
+            self.set_property(note, key, value)?;
+            self.record_operation(Operation::SetProperty {
+                note: note.to_string(),
+                key: key.to_string(),
+                before,
+                after: value.map(str::to_string),
+            });
+        }
+        Ok(())
+    }
+
+    async fn find_by_property(&self, key: &str, value: Option<&str>) -> Result<Vec<String>> {
+        Ok(self
+            .notes
+            .iter()
+            .filter(|(_, record)| match (record.properties.get(key), value) {
+                (Some(own), Some(value)) => own == value,
+                (Some(_), None) => true,
+                (None, _) => false,
+            })
+            .map(|(name, _)| name.clone())
+            .collect())
+    }
+
+    async fn list_property_keys(&self) -> Result<Vec<String>> {
+        let keys = self
+            .notes
+            .values()
+            .flat_map(|record| record.properties.keys().cloned())
+            .collect::<BTreeSet<_>>();
+        Ok(keys.into_iter().collect())
+    }
+
+    async fn insert_synced_link(&mut self, from: &str, to: &str) -> Result<()> {
+        log::debug!("saving synced link {} -> {}", from, to);
+
+        self.insert_link(from, to, None)?;
+        self.touch_updated(from, to);
+        if let Some(record) = self.notes.get_mut(from) {
+            record.synced.insert(to.to_string());
+        }
+        self.record_operation(Operation::LinkSynced(LinkRecord {
+            from: from.to_string(),
+            to: to.to_string(),
+            label: None,
+        }));
+        Ok(())
+    }
+
+    async fn find_synced_links_from(&self, from: &str) -> Result<Vec<String>> {
+        Ok(self
+            .notes
+            .get(from)
+            .map(|record| record.synced.iter().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    async fn merge_notes(
+        &mut self,
+        source: &str,
+        target: &str,
+        rewrite: Option<&BodyRewrite>,
+    ) -> Result<()> {
+        log::debug!("merging note {} into {}", source, target);
+
+        if source == target {
+            return Err(violation(format!("can't merge `{}` into itself", source)));
+        }
+        let snapshot = self.transaction(|memory| {
+            memory.record(target)?;
+
+            let snapshot = memory.drop_note(source)?;
+            memory.trash_note(&snapshot);
+            let mut operations = vec![Operation::RemoveNote(snapshot.clone())];
+
+            let repoint = |name: &String| {
+                if name == source {
+                    target.to_string()
+                } else {
+                    name.clone()
+                }
+            };
+            for link in &snapshot.links {
+                let (from, to) = (repoint(&link.from), repoint(&link.to));
+                // no self-loops, and labels of already existing links of target win
+                let exists = memory
+                    .notes
+                    .get(&from)
+                    .is_some_and(|record| record.links.contains_key(&to));
+                if from == to || exists {
+                    continue;
+                }
+                memory.insert_link(&from, &to, link.label.as_deref())?;
+                operations.push(Operation::Link(LinkRecord {
+                    from,
+                    to,
+                    label: link.label.clone(),
+                }));
+            }
+
+            for order in &snapshot.stacks {
+                let before = memory.stack_order(&order.stack);
+                if before.iter().any(|note| note == target) {
+                    continue;
+                }
+                let index = order
+                    .notes
+                    .iter()
+                    .position(|note| note == source)
+                    .unwrap_or(0);
+                let mut after = before.clone();
+                after.insert(std::cmp::min(index, after.len()), target.to_string());
+                memory.set_stack_order(&order.stack, &after)?;
+                operations.push(Operation::ReorderStack {
+                    stack: order.stack.clone(),
+                    before,
+                    after,
+                });
+            }
+
+            // old name keeps resolving to the merged note
+            let before = memory.find_aliases(target);
+            let mut after = before.clone();
+            for alias in std::iter::once(&snapshot.name).chain(&snapshot.aliases) {
+                if !after.contains(alias) {
+                    after.push(alias.clone());
+                }
+            }
+            after.sort();
+            memory.set_aliases(target, &after)?;
+            operations.push(Operation::SetAliases {
+                note: target.to_string(),
+                before,
+                after,
+            });
+
+            let properties = memory.find_properties(target);
+            for (key, value) in &snapshot.properties {
+                if properties.contains_key(key) {
+                    continue;
+                }
+                memory.set_property(target, key, Some(value))?;
+                operations.push(Operation::SetProperty {
+                    note: target.to_string(),
+                    key: key.clone(),
+                    before: None,
+                    after: Some(value.clone()),
+                });
+            }
+            memory.touch_updated(target, target);
+
+            memory.record_operation(Operation::MergeNotes {
+                source: source.to_string(),
+                target: target.to_string(),
+                operations,
+                rewrite: rewrite.cloned(),
+            });
+            Ok(snapshot)
+        })?;
+
+        if let Some(filename) = &snapshot.filename {
+            trash::move_to_trash(&self.root, filename)?;
+        }
+
+        Ok(())
+    }
+
+    async fn split_note(
+        &mut self,
+        note: &str,
+        children: &[Note],
+        rewrite: Option<&BodyRewrite>,
+    ) -> Result<()> {
+        log::debug!("splitting {} notes out of {}", children.len(), note);
+
+        self.transaction(|memory| {
+            memory.record(note)?;
+
+            let mut operations = vec![];
+            for child in children {
+                let name = child.name();
+                if let Some(owner) = memory.alias_owner(&name) {
+                    return Err(violation(format!(
+                        "name `{}` is already taken by `{}`",
+                        name, owner
+                    )));
+                }
+                memory.save_note(child)?;
+                operations.push(Operation::CreateNote(NoteSnapshot {
+                    name: name.clone(),
+                    filename: child
+                        .file_path()
+                        .map(|path| path.to_string_lossy().to_string()),
+                    body: None,
+                    created_at: None,
+                    links: vec![],
+                    stacks: vec![],
+                    aliases: vec![],
+                    properties: BTreeMap::new(),
+                }));
+
+                memory.insert_link(note, &name, None)?;
+                operations.push(Operation::Link(LinkRecord {
+                    from: note.to_string(),
+                    to: name,
+                    label: None,
+                }));
+            }
+            memory.touch_updated(note, note);
+
+            memory.record_operation(Operation::SplitNote {
+                note: note.to_string(),
+                operations,
+                rewrite: rewrite.cloned(),
+            });
+            Ok(())
+        })
+    }
+
+    fn parse_cache(&self) -> ParseCache {
+        ParseCache::Memory(self.parsed_files.clone())
+    }
+}
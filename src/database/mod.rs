@@ -1,10 +1,97 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+    sync::Arc,
+    time::UNIX_EPOCH,
+};
+
+use async_std::sync::Mutex;
 use async_trait::async_trait;
 use sqlx::Result;
+use syntect::easy::HighlightLines;
 
-use crate::{config::color::ColorScheme, highlight::MarkdownStatic, note::Note};
+use crate::{
+    config::{color::ColorScheme, Storage},
+    highlight::MarkdownStatic,
+    note::Note,
+    parse_cache::ParseCache,
+};
 
+mod dump;
+mod files;
+mod journal;
+mod memory;
 mod sqlite;
-pub use sqlite::{Sqlite, SqliteAsyncHandle};
+mod trash;
+pub use dump::Dump;
+pub use files::Files;
+pub use journal::{BodyRewrite, JournalEntry, LinkRecord, StepReport};
+pub use memory::Memory;
+pub use sqlite::Sqlite;
+pub use trash::TrashEntry;
+
+/// handle, shared by commands, regardless of storage backend
+pub type DatabaseAsyncHandle = Arc<Mutex<dyn Database>>;
+
+/// opens storage, chosen in config, in notes' work dir
+pub async fn open(storage: Storage, create_if_missing: bool) -> Result<DatabaseAsyncHandle> {
+    let db: DatabaseAsyncHandle = match storage {
+        Storage::Sqlite => Sqlite::new(create_if_missing, "./.sqlite").await?,
+        Storage::Files => Files::new(create_if_missing, "./.mds")?,
+    };
+    Ok(db)
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoteTimestamps {
+    pub created: Option<i64>,
+    pub updated: Option<i64>,
+    pub opened: Option<i64>,
+}
+
+/// links, followed from each of notes, with their labels
+type LinksFrom = HashMap<String, Vec<(String, Option<String>)>>;
+
+/// notes, reachable from a note by following links, and links between them
+#[derive(Debug, Clone)]
+pub struct Subgraph {
+    /// names of these aren't highlighted, only of notes taken out of subgraph, as few of
+    /// them may get displayed
+    pub notes: HashMap<String, Note>,
+    /// ordered as in `find_labelled_links_from`
+    pub links: LinksFrom,
+    md_static: MarkdownStatic,
+}
+
+impl Subgraph {
+    fn new(notes: Vec<Note>, links: LinksFrom, md_static: MarkdownStatic) -> Self {
+        let notes = notes.into_iter().map(|note| (note.name(), note)).collect();
+        Self {
+            notes,
+            links,
+            md_static,
+        }
+    }
+
+    pub fn labelled_links_from(&self, from: &str) -> Vec<(Note, Option<String>)> {
+        let mut highlighter = HighlightLines::new(self.md_static.1, self.md_static.2);
+        self.links
+            .get(from)
+            .into_iter()
+            .flatten()
+            .filter_map(|(to, label)| {
+                let mut note = self.notes.get(to)?.clone();
+                note.set_markdown(&mut highlighter, self.md_static);
+                Some((note, label.clone()))
+            })
+            .collect()
+    }
+
+    pub async fn into_notes(self) -> Vec<Note> {
+        parse_names(self.notes.into_values().collect(), self.md_static).await
+    }
+}
 
 #[async_trait]
 pub trait Database: Send + Sync {
@@ -18,8 +105,20 @@ pub trait Database: Send + Sync {
         color_scheme: ColorScheme,
     ) -> Result<Note>;
     async fn remove_note(&mut self, note: &Note) -> Result<()>;
-    async fn rename_note(&mut self, note: &Note, new_name: &str) -> Result<()>;
-    async fn insert_link(&mut self, from: &str, to: &str, straight: bool) -> Result<()>;
+    /// `rewrites` of bodies, already written by caller, are only journaled
+    async fn rename_note(
+        &mut self,
+        note: &Note,
+        new_name: &str,
+        rewrites: &[BodyRewrite],
+    ) -> Result<()>;
+    async fn insert_link(
+        &mut self,
+        from: &str,
+        to: &str,
+        straight: bool,
+        label: Option<&str>,
+    ) -> Result<()>;
     async fn remove_link(&mut self, from: &str, to: &str, straight: bool) -> Result<()>;
     async fn find_links_from(
         &self,
@@ -35,6 +134,33 @@ pub trait Database: Send + Sync {
         color_scheme: ColorScheme,
         straight: bool,
     ) -> Result<Vec<Note>>;
+    async fn find_labelled_links_from(
+        &self,
+        from: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+        straight: bool,
+    ) -> Result<Vec<(Note, Option<String>)>>;
+    async fn find_labelled_links_to(
+        &self,
+        to: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+        straight: bool,
+    ) -> Result<Vec<(Note, Option<String>)>>;
+    /// all of subgraph, reachable from `from`, no further than `depth` links away, if
+    /// specified, in one query
+    async fn reachable_subgraph(
+        &self,
+        from: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+        straight: bool,
+        depth: Option<usize>,
+    ) -> Result<Subgraph>;
+    async fn list_link_labels(&self) -> Result<Vec<String>>;
+    /// every link, ordered by names of its notes, for commands which walk all of graph
+    async fn all_labelled_links(&self) -> Result<Vec<LinkRecord>>;
     async fn push_note_to_stack(&mut self, stack: &str, note: &str) -> Result<()>;
     async fn select_from_stack(
         &mut self,
@@ -46,4 +172,89 @@ pub trait Database: Send + Sync {
     async fn move_to_topmost(&mut self, stack: &str, note: &str) -> Result<()>;
     async fn swap_with_above(&mut self, stack: &str, note: &str) -> Result<()>;
     async fn swap_with_below(&mut self, stack: &str, note: &str) -> Result<()>;
+    async fn create_stack(&mut self, stack: &str) -> Result<()>;
+    async fn list_stacks(&self) -> Result<Vec<String>>;
+    async fn rename_stack(&mut self, stack: &str, new_name: &str) -> Result<()>;
+    async fn remove_stack(&mut self, stack: &str) -> Result<()>;
+    async fn refresh_search_index(&mut self) -> Result<usize>;
+    async fn search(
+        &self,
+        query: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+    ) -> Result<Vec<Note>>;
+    async fn mark_opened(&mut self, name: &str) -> Result<()>;
+    async fn timestamps(&self) -> Result<HashMap<String, NoteTimestamps>>;
+    async fn undo(&mut self) -> Result<Option<(JournalEntry, StepReport)>>;
+    async fn redo(&mut self) -> Result<Option<(JournalEntry, StepReport)>>;
+    async fn journal(&self, limit: usize) -> Result<Vec<JournalEntry>>;
+    async fn list_trash(&self) -> Result<Vec<TrashEntry>>;
+    async fn restore_from_trash(&mut self, id: i64) -> Result<Vec<LinkRecord>>;
+    async fn purge_trash(&mut self, id: Option<i64>) -> Result<usize>;
+    async fn set_filename(&mut self, name: &str, filename: Option<&str>) -> Result<()>;
+    async fn find_dangling_stack_entries(&self) -> Result<Vec<(String, String)>>;
+    async fn repair_stacks(&mut self) -> Result<()>;
+    async fn dump(&self) -> Result<Dump>;
+    async fn restore_dump(&mut self, dump: &Dump) -> Result<()>;
+    async fn set_aliases(&mut self, note: &str, aliases: &[String]) -> Result<()>;
+    async fn properties(&self, note: &str) -> Result<BTreeMap<String, String>>;
+    async fn set_property(&mut self, note: &str, key: &str, value: Option<&str>) -> Result<()>;
+    async fn find_by_property(&self, key: &str, value: Option<&str>) -> Result<Vec<String>>;
+    async fn list_property_keys(&self) -> Result<Vec<String>>;
+    async fn insert_synced_link(&mut self, from: &str, to: &str) -> Result<()>;
+    async fn find_synced_links_from(&self, from: &str) -> Result<Vec<String>>;
+    /// `rewrite` of target's body, already written by caller, is only journaled
+    async fn merge_notes(
+        &mut self,
+        source: &str,
+        target: &str,
+        rewrite: Option<&BodyRewrite>,
+    ) -> Result<()>;
+    /// `rewrite` of note's body, already written by caller, is only journaled
+    async fn split_note(
+        &mut self,
+        note: &str,
+        children: &[Note],
+        rewrite: Option<&BodyRewrite>,
+    ) -> Result<()>;
+    fn parse_cache(&self) -> ParseCache;
+}
+
+async fn parse_names(notes: Vec<Note>, md_static: MarkdownStatic) -> Vec<Note> {
+    let mut highlighter = HighlightLines::new(md_static.1, md_static.2);
+    notes
+        .into_iter()
+        .map(|mut note| {
+            note.set_markdown(&mut highlighter, md_static);
+            note
+        })
+        .collect::<Vec<_>>()
+}
+
+async fn parse_labelled_names(
+    notes: Vec<(Note, Option<String>)>,
+    md_static: MarkdownStatic,
+) -> Vec<(Note, Option<String>)> {
+    let mut highlighter = HighlightLines::new(md_static.1, md_static.2);
+    notes
+        .into_iter()
+        .map(|(mut note, label)| {
+            note.set_markdown(&mut highlighter, md_static);
+            (note, label)
+        })
+        .collect::<Vec<_>>()
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+// modification time of a note's body in millis, used to detect files edited outside of mds
+fn file_mtime(path: &Path) -> std::io::Result<i64> {
+    let modified = fs::metadata(path)?.modified()?;
+    let millis = modified
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0);
+    Ok(millis)
 }
@@ -1,4 +1,10 @@
-use std::{fs, path::Path, str::FromStr, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
 
 use async_std::sync::Mutex;
 use async_trait::async_trait;
@@ -9,16 +15,31 @@ use sqlx::{
 };
 use syntect::easy::HighlightLines;
 
-use crate::{config::color::ColorScheme, highlight::MarkdownStatic, note::Note};
+use crate::{
+    config::color::ColorScheme, highlight::MarkdownStatic, note::Note, parse_cache::ParseCache,
+};
 
-use super::Database;
+use super::{
+    dump::{Dump, DumpedNote, DUMP_VERSION},
+    file_mtime,
+    journal::{
+        BodyRewrite, JournalEntry, LinkRecord, NoteSnapshot, Operation, StackOrder, StepReport,
+    },
+    now, parse_labelled_names, parse_names,
+    trash::{self, TrashEntry},
+    Database, LinksFrom, NoteTimestamps, Subgraph,
+};
 
 #[derive(Debug)]
 pub struct Sqlite {
     pool: SqlitePool,
+    /// notes' work dir, database file is in; bodies are trashed under it
+    root: PathBuf,
 }
 
-pub type SqliteAsyncHandle = Arc<Mutex<Sqlite>>;
+/// aliases of note `n`, separated by newlines
+static ALIASES_FIELD: &str =
+    "(select group_concat(a.alias, char(10)) from aliases a where a.note = n.name) as aliases";
 
 impl Sqlite {
     pub async fn new(create_if_missing: bool, path: impl AsRef<Path>) -> Result<Arc<Mutex<Self>>> {
@@ -40,7 +61,14 @@ impl Sqlite {
 
         Self::setup_db(&pool).await?;
 
-        Ok(Arc::new(Mutex::new(Self { pool })))
+        let root = match path.parent() {
+            Some(dir) if dir != Path::new("") => dir,
+            _ => Path::new("."),
+        };
+        // not relative to cwd, which may change while database is open
+        let root = fs::canonicalize(root)?;
+
+        Ok(Arc::new(Mutex::new(Self { pool, root })))
     }
 
     async fn setup_db(pool: &SqlitePool) -> Result<()> {
@@ -56,11 +84,12 @@ impl Sqlite {
             .file_path()
             .map(|path| path.as_path().to_str().unwrap());
         sqlx::query(
-            "insert into notes(name, filename)
-                values(?1, ?2)",
+            "insert into notes(name, filename, created_at, updated_at)
+                values(?1, ?2, ?3, ?3)",
         )
         .bind(note.name().as_str())
         .bind(path)
+        .bind(now())
         .execute(tx)
         .await?;
 
@@ -71,9 +100,28 @@ impl Sqlite {
         tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
         from: &str,
         to: &str,
+        label: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            "insert into linkx(_from, _to, label)
+                values(?1, ?2, ?3)",
+        )
+        .bind(from)
+        .bind(to)
+        .bind(label)
+        .execute(tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn insert_synced(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        from: &str,
+        to: &str,
     ) -> Result<()> {
         sqlx::query(
-            "insert into linkx(_from, _to)
+            "insert into synced_links(_from, _to)
                 values(?1, ?2)",
         )
         .bind(from)
@@ -101,12 +149,43 @@ impl Sqlite {
         Ok(())
     }
 
-    async fn remove_note(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, note: &Note) -> Result<()> {
+    async fn touch_updated(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        from: &str,
+        to: &str,
+    ) -> Result<()> {
+        sqlx::query("update notes set updated_at = ?3 where name in (?1, ?2)")
+            .bind(from)
+            .bind(to)
+            .bind(now())
+            .execute(tx)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn remove_note(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, name: &str) -> Result<()> {
         sqlx::query(
             "delete from notes
             where name = ?1",
         )
-        .bind(note.name().as_str())
+        .bind(name)
+        .execute(tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// cached parse of note's body, which is no longer its body or is going to the trash
+    async fn forget_parsed_file(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        name: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            "delete from parse_cache
+            where file_path in (select filename from notes where name = ?1)",
+        )
+        .bind(name)
         .execute(tx)
         .await?;
 
@@ -115,12 +194,13 @@ impl Sqlite {
 
     async fn rename_note(
         tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
-        note: &Note,
+        name: &str,
         new_name: &str,
     ) -> Result<()> {
-        sqlx::query("update notes set name = ?2 where name = ?1")
-            .bind(note.name().as_str())
+        sqlx::query("update notes set name = ?2, updated_at = ?3 where name = ?1")
+            .bind(name)
             .bind(new_name)
+            .bind(now())
             .execute(tx)
             .await?;
 
@@ -168,8 +248,8 @@ impl Sqlite {
         let mut query = SqlBuilder::select_from(SqlName::new("stacked_notes").baquoted());
         query
             .field("stack_index")
-            .and_where_eq("note", &quote(note))
-            .and_where_eq("stack_tag", &quote(stack));
+            .and_where_eq("note", quote(note))
+            .and_where_eq("stack_tag", quote(stack));
 
         let query = query.sql().expect("bug in list query. please report");
 
@@ -366,6 +446,55 @@ impl Sqlite {
         }
         Ok(())
     }
+    async fn create_stack(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, stack: &str) -> Result<()> {
+        sqlx::query(
+            "insert into stack_tags(tag)
+                values(?1)",
+        )
+        .bind(stack)
+        .execute(tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn rename_stack(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        stack: &str,
+        new_name: &str,
+    ) -> Result<()> {
+        sqlx::query("update stack_tags set tag = ?2 where tag = ?1")
+            .bind(stack)
+            .bind(new_name)
+            .execute(tx)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn remove_stack(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, stack: &str) -> Result<()> {
+        sqlx::query(
+            "delete from stack_tags
+            where tag = ?1",
+        )
+        .bind(stack)
+        .execute(tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_all_stack_tags(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    ) -> Result<Vec<String>> {
+        let tags = sqlx::query("select tag from stack_tags")
+            .map(Self::query_tag)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        Ok(tags)
+    }
+
     async fn find_all_stacks(
         tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
         note: &str,
@@ -382,296 +511,1886 @@ impl Sqlite {
         Ok(tags)
     }
 
-    fn query_note(row: SqliteRow, color_scheme: ColorScheme) -> Note {
-        let file_path: Option<String> = row.get("filename");
-        Note::new(row.get("name"), file_path.map(|c| c.into()), color_scheme)
-    }
+    async fn index_body(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        name: &str,
+        body: &str,
+        mtime: i64,
+    ) -> Result<()> {
+        sqlx::query("delete from notes_fts where name = ?1")
+            .bind(name)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            "insert into notes_fts(name, body)
+                values(?1, ?2)",
+        )
+        .bind(name)
+        .bind(body)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "insert into fts_index_state(name, mtime)
+                values(?1, ?2)
+                on conflict(name) do update set mtime = excluded.mtime",
+        )
+        .bind(name)
+        .bind(mtime)
+        .execute(&mut *tx)
+        .await?;
 
-    fn query_stack_index(row: SqliteRow) -> i64 {
-        row.get("stack_index")
-    }
-    fn query_stack_tag(row: SqliteRow) -> String {
-        row.get("stack_tag")
+        Ok(())
     }
 
-    async fn find_links_from_inner(
-        &self,
-        from: &str,
-        md_static: MarkdownStatic,
-        color_scheme: ColorScheme,
-    ) -> Result<Vec<Note>> {
-        log::debug!("listing notes, linked by current");
+    async fn unindex_body(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, name: &str) -> Result<()> {
+        sqlx::query("delete from notes_fts where name = ?1")
+            .bind(name)
+            .execute(&mut *tx)
+            .await?;
 
-        let sql = SqlBuilder::select_from(name!("linkx"; "l"))
-            .field(name!("l", "_to"; "name"))
-            .field(name!("n", "filename"; "filename"))
-            .left()
-            .join(name!("notes"; "n"))
-            .on("l._to = n.name")
-            .and_where_eq("l._from", quote(from))
-            .order_desc("l._to")
-            .sql()
-            .map_err(|err| Error::Protocol(format!("{:?}", err)))?;
-        log::debug!("sql: {}", sql);
+        Ok(())
+    }
 
-        let res = sqlx::query(&sql)
-            .map(|row| Self::query_note(row, color_scheme))
-            .fetch_all(&self.pool)
+    async fn rename_indexed_body(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        name: &str,
+        new_name: &str,
+    ) -> Result<()> {
+        sqlx::query("update notes_fts set name = ?2 where name = ?1")
+            .bind(name)
+            .bind(new_name)
+            .execute(tx)
             .await?;
-        let res = parse_names(res, md_static).await;
 
-        Ok(res)
+        Ok(())
     }
 
-    async fn find_links_to_inner(
-        &self,
-        to: &str,
-        md_static: MarkdownStatic,
-        color_scheme: ColorScheme,
-    ) -> Result<Vec<Note>> {
-        log::debug!("listing notes, linked by current");
-
-        let sql = SqlBuilder::select_from(name!("linkx"; "l"))
-            .field(name!("l", "_from"; "name"))
-            .field(name!("n", "filename"; "filename"))
-            .left()
-            .join(name!("notes"; "n"))
-            .on("l._from = n.name")
-            .and_where_eq("l._to", quote(to))
-            .order_desc("l._from")
-            .sql()
+    async fn record(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        operation: &Operation,
+    ) -> Result<()> {
+        let operation = serde_json::to_string(operation)
             .map_err(|err| Error::Protocol(format!("{:?}", err)))?;
-        log::debug!("sql: {}", sql);
-
-        let res = sqlx::query(&sql)
-            .map(|row| Self::query_note(row, color_scheme))
-            .fetch_all(&self.pool)
+        // a new mutation invalidates everything, that could have been redone
+        sqlx::query("delete from journal where undone = 1")
+            .execute(&mut *tx)
             .await?;
-        let res = parse_names(res, md_static).await;
+        sqlx::query(
+            "insert into journal(operation, applied_at)
+                values(?1, ?2)",
+        )
+        .bind(operation)
+        .bind(now())
+        .execute(&mut *tx)
+        .await?;
 
-        Ok(res)
+        Ok(())
     }
 
-    async fn insert_link_inner(&mut self, from: &str, to: &str) -> Result<()> {
-        log::debug!("saving link {} -> {} ", from, to);
-
-        let mut tx = self.pool.begin().await?;
-        Self::insert_link(&mut tx, from, to).await?;
-        tx.commit().await?;
-
+    async fn record_reorder(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        stack: &str,
+        before: Vec<String>,
+    ) -> Result<()> {
+        let after = Self::stack_order(&mut *tx, stack).await?;
+        if before != after {
+            let operation = Operation::ReorderStack {
+                stack: stack.to_string(),
+                before,
+                after,
+            };
+            Self::record(tx, &operation).await?;
+        }
         Ok(())
     }
 
-    async fn remove_link_inner(&mut self, from: &str, to: &str) -> Result<()> {
-        log::debug!("removing link {} -> {} ", from, to);
+    async fn stack_order(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        stack: &str,
+    ) -> Result<Vec<String>> {
+        let notes = sqlx::query(
+            "select note from stacked_notes
+            where stack_tag = ?1 order by stack_index asc",
+        )
+        .bind(stack)
+        .map(|row: SqliteRow| row.get("note"))
+        .fetch_all(&mut *tx)
+        .await?;
 
-        let mut tx = self.pool.begin().await?;
-        Self::remove_link(&mut tx, from, to).await?;
-        tx.commit().await?;
+        Ok(notes)
+    }
+
+    async fn set_stack_order(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        stack: &str,
+        notes: &[String],
+    ) -> Result<()> {
+        sqlx::query("delete from stacked_notes where stack_tag = ?1")
+            .bind(stack)
+            .execute(&mut *tx)
+            .await?;
+        for (index, note) in notes.iter().enumerate() {
+            sqlx::query(
+                "insert into stacked_notes(stack_tag, stack_index, note)
+                    values(?1, ?2, ?3)",
+            )
+            .bind(stack)
+            .bind(index as i64)
+            .bind(note)
+            .execute(&mut *tx)
+            .await?;
+        }
 
         Ok(())
     }
-}
 
-async fn parse_names(notes: Vec<Note>, md_static: MarkdownStatic) -> Vec<Note> {
-    let mut highlighter = HighlightLines::new(md_static.1, md_static.2);
-    notes
-        .into_iter()
-        .map(|mut note| {
-            note.set_markdown(&mut highlighter, md_static);
-            note
-        })
-        .collect::<Vec<_>>()
-}
+    async fn find_aliases(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        note: &str,
+    ) -> Result<Vec<String>> {
+        let aliases = sqlx::query("select alias from aliases where note = ?1 order by alias asc")
+            .bind(note)
+            .map(|row: SqliteRow| row.get("alias"))
+            .fetch_all(&mut *tx)
+            .await?;
 
-#[async_trait]
-impl Database for Sqlite {
-    async fn save(&mut self, note: &Note) -> Result<()> {
-        log::debug!("saving note to sqlite {:?}", note);
+        Ok(aliases)
+    }
 
-        let mut tx = self.pool.begin().await?;
-        Self::save_note(&mut tx, note).await?;
-        tx.commit().await?;
+    async fn set_aliases(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        note: &str,
+        aliases: &[String],
+    ) -> Result<()> {
+        sqlx::query("delete from aliases where note = ?1")
+            .bind(note)
+            .execute(&mut *tx)
+            .await?;
+        for alias in aliases {
+            sqlx::query(
+                "insert into aliases(alias, note)
+                    values(?1, ?2)",
+            )
+            .bind(alias)
+            .bind(note)
+            .execute(&mut *tx)
+            .await?;
+        }
 
         Ok(())
     }
 
-    async fn list(
-        &self,
-        md_static: MarkdownStatic,
-        color_scheme: ColorScheme,
-    ) -> Result<Vec<Note>> {
-        log::debug!("listing notes");
-
-        let mut query = SqlBuilder::select_from(SqlName::new("notes").alias("n").baquoted());
-        query.field("*").order_desc("name");
-
-        let query = query.sql().expect("bug in list query. please report");
-
-        let res = sqlx::query(&query)
-            .map(|row| Self::query_note(row, color_scheme))
-            .fetch_all(&self.pool)
+    async fn find_properties(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        note: &str,
+    ) -> Result<BTreeMap<String, String>> {
+        let properties = sqlx::query("select key, value from properties where note = ?1")
+            .bind(note)
+            .map(|row: SqliteRow| (row.get("key"), row.get("value")))
+            .fetch_all(&mut *tx)
             .await?;
-        let res = parse_names(res, md_static).await;
 
-        Ok(res)
+        Ok(properties.into_iter().collect())
     }
 
-    async fn get(
-        &self,
-        name: &str,
-        md_static: MarkdownStatic,
-        color_scheme: ColorScheme,
+    async fn set_property(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        note: &str,
+        key: &str,
+        value: Option<&str>,
+    ) -> Result<()> {
+        match value {
+            Some(value) => {
+                sqlx::query(
+                    "insert into properties(note, key, value)
+                        values(?1, ?2, ?3)
+                        on conflict(note, key) do update set value = excluded.value",
+                )
+                .bind(note)
+                .bind(key)
+                .bind(value)
+                .execute(tx)
+                .await?;
+            }
+            None => {
+                sqlx::query("delete from properties where note = ?1 and key = ?2")
+                    .bind(note)
+                    .bind(key)
+                    .execute(tx)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// note, which is named `alias` or already has it as an alias
+    async fn alias_owner(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        alias: &str,
+    ) -> Result<Option<String>> {
+        let owner = sqlx::query(
+            "select name from notes where name = ?1
+            union select note as name from aliases where alias = ?1",
+        )
+        .bind(alias)
+        .map(|row: SqliteRow| row.get("name"))
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        Ok(owner)
+    }
+
+    async fn snapshot_note(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        name: &str,
+    ) -> Result<NoteSnapshot> {
+        let (filename, created_at): (Option<String>, Option<i64>) =
+            sqlx::query("select filename, created_at from notes where name = ?1")
+                .bind(name)
+                .map(|row: SqliteRow| (row.get("filename"), row.get("created_at")))
+                .fetch_one(&mut *tx)
+                .await?;
+        let links = sqlx::query(
+            "select _from, _to, label from linkx
+            where _from = ?1 or _to = ?1",
+        )
+        .bind(name)
+        .map(|row: SqliteRow| LinkRecord {
+            from: row.get("_from"),
+            to: row.get("_to"),
+            label: row.get("label"),
+        })
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut stacks = vec![];
+        for stack in Self::find_all_stacks(&mut *tx, name).await? {
+            let notes = Self::stack_order(&mut *tx, &stack).await?;
+            stacks.push(StackOrder { stack, notes });
+        }
+        let aliases = Self::find_aliases(&mut *tx, name).await?;
+        let properties = Self::find_properties(&mut *tx, name).await?;
+        let body = filename
+            .as_ref()
+            .and_then(|filename| fs::read_to_string(filename).ok());
+
+        Ok(NoteSnapshot {
+            name: name.to_string(),
+            filename,
+            body,
+            created_at,
+            links,
+            stacks,
+            aliases,
+            properties,
+        })
+    }
+
+    async fn drop_note(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        name: &str,
+    ) -> Result<NoteSnapshot> {
+        let snapshot = Self::snapshot_note(&mut *tx, name).await?;
+        for order in &snapshot.stacks {
+            Self::pop_note_from_stack(&mut *tx, &order.stack, name).await?;
+        }
+        Self::unindex_body(&mut *tx, name).await?;
+        Self::forget_parsed_file(&mut *tx, name).await?;
+        Self::remove_note(&mut *tx, name).await?;
+
+        Ok(snapshot)
+    }
+
+    async fn insert_restored_note(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        root: &Path,
+        snapshot: &NoteSnapshot,
+    ) -> Result<()> {
+        sqlx::query(
+            "insert into notes(name, filename, created_at, updated_at)
+                values(?1, ?2, ?3, ?4)",
+        )
+        .bind(&snapshot.name)
+        .bind(&snapshot.filename)
+        .bind(snapshot.created_at)
+        .bind(now())
+        .execute(&mut *tx)
+        .await?;
+
+        if let Some(filename) = &snapshot.filename {
+            let file_path = &root.join(filename);
+            trash::move_from_trash(root, filename)?;
+            if let (Some(body), false) = (&snapshot.body, file_path.exists()) {
+                fs::write(file_path, body)?;
+            }
+            let (body, mtime) = match file_mtime(file_path) {
+                Ok(mtime) => (fs::read_to_string(file_path)?, mtime),
+                Err(_) => (String::new(), 0),
+            };
+            Self::index_body(&mut *tx, &snapshot.name, &body, mtime).await?;
+        }
+        sqlx::query("delete from trash where name = ?1 and filename is ?2")
+            .bind(&snapshot.name)
+            .bind(&snapshot.filename)
+            .execute(&mut *tx)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn note_exists(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, name: &str) -> Result<bool> {
+        let count: i64 = sqlx::query("select count(*) as count from notes where name = ?1")
+            .bind(name)
+            .map(|row: SqliteRow| row.get("count"))
+            .fetch_one(tx)
+            .await?;
+        Ok(count > 0)
+    }
+
+    /// links to notes, which have been removed since, are skipped and put into `report`
+    async fn restore_note(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        root: &Path,
+        snapshot: &NoteSnapshot,
+        report: &mut StepReport,
+    ) -> Result<()> {
+        Self::insert_restored_note(&mut *tx, root, snapshot).await?;
+        for link in &snapshot.links {
+            if !Self::note_exists(&mut *tx, &link.from).await?
+                || !Self::note_exists(&mut *tx, &link.to).await?
+            {
+                report.skipped_links.push(link.clone());
+                continue;
+            }
+            Self::insert_link(&mut *tx, &link.from, &link.to, link.label.as_deref()).await?;
+        }
+        for order in &snapshot.stacks {
+            Self::set_stack_order(&mut *tx, &order.stack, &order.notes).await?;
+        }
+        Self::set_aliases(&mut *tx, &snapshot.name, &snapshot.aliases).await?;
+        for (key, value) in &snapshot.properties {
+            Self::set_property(&mut *tx, &snapshot.name, key, Some(value)).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn trash_note(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        snapshot: &NoteSnapshot,
+    ) -> Result<()> {
+        // body stays in trash dir
+        let snapshot = NoteSnapshot {
+            body: None,
+            ..snapshot.clone()
+        };
+        let serialized = serde_json::to_string(&snapshot)
+            .map_err(|err| Error::Protocol(format!("{:?}", err)))?;
+        sqlx::query(
+            "insert into trash(name, filename, snapshot, trashed_at)
+                values(?1, ?2, ?3, ?4)",
+        )
+        .bind(&snapshot.name)
+        .bind(&snapshot.filename)
+        .bind(serialized)
+        .bind(now())
+        .execute(tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn insert_into_stack(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        stack: &str,
+        note: &str,
+        index: usize,
+    ) -> Result<()> {
+        let mut notes = Self::stack_order(&mut *tx, stack).await?;
+        let index = std::cmp::min(index, notes.len());
+        notes.insert(index, note.to_string());
+        Self::set_stack_order(tx, stack, &notes).await
+    }
+
+    fn query_trash_entry(row: SqliteRow) -> Result<TrashEntry> {
+        let snapshot: String = row.get("snapshot");
+        let snapshot =
+            serde_json::from_str(&snapshot).map_err(|err| Error::Decode(Box::new(err)))?;
+        Ok(TrashEntry {
+            id: row.get("id"),
+            snapshot,
+            trashed_at: row.get("trashed_at"),
+        })
+    }
+
+    /// applies `operation` again if `forward`, or its inverse otherwise;
+    /// returns the operation to be stored back into journal and body files, which
+    /// have to be moved to trash once the transaction is committed
+    async fn apply(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        root: &Path,
+        mut operation: Operation,
+        forward: bool,
+        report: &mut StepReport,
+    ) -> Result<(Operation, Vec<String>)> {
+        let mut stale_files = vec![];
+        match (&mut operation, forward) {
+            (Operation::CreateNote(snapshot), true) | (Operation::RemoveNote(snapshot), false) => {
+                Self::restore_note(tx, root, snapshot, report).await?;
+            }
+            (Operation::CreateNote(snapshot), false) | (Operation::RemoveNote(snapshot), true) => {
+                *snapshot = Self::drop_note(&mut *tx, &snapshot.name).await?;
+                Self::trash_note(tx, snapshot).await?;
+                stale_files.extend(snapshot.filename.clone());
+            }
+            (Operation::RenameNote { from, to, rewrites }, forward) => {
+                let (from, to) = if forward { (from, to) } else { (to, from) };
+                Self::rename_note(&mut *tx, from, to).await?;
+                Self::rename_indexed_body(&mut *tx, from, to).await?;
+                for rewrite in rewrites.iter() {
+                    report.apply_rewrite(rewrite, forward)?;
+                }
+            }
+            (Operation::Link(link), true) | (Operation::Unlink(link), false) => {
+                Self::insert_link(tx, &link.from, &link.to, link.label.as_deref()).await?;
+            }
+            (Operation::Link(link), false) | (Operation::Unlink(link), true) => {
+                Self::remove_link(tx, &link.from, &link.to).await?;
+            }
+            (Operation::LinkSynced(link), true) | (Operation::UnlinkSynced(link), false) => {
+                Self::insert_link(&mut *tx, &link.from, &link.to, link.label.as_deref()).await?;
+                Self::insert_synced(tx, &link.from, &link.to).await?;
+            }
+            (Operation::LinkSynced(link), false) | (Operation::UnlinkSynced(link), true) => {
+                Self::remove_link(tx, &link.from, &link.to).await?;
+            }
+            (
+                Operation::ReorderStack {
+                    stack,
+                    before,
+                    after,
+                },
+                forward,
+            ) => {
+                let notes = if forward { after } else { before };
+                Self::set_stack_order(tx, stack, notes).await?;
+            }
+            (Operation::CreateStack { stack }, true) => {
+                Self::create_stack(tx, stack).await?;
+            }
+            (Operation::CreateStack { stack }, false) => {
+                Self::remove_stack(tx, stack).await?;
+            }
+            (Operation::RenameStack { from, to }, forward) => {
+                let (from, to) = if forward { (from, to) } else { (to, from) };
+                Self::rename_stack(tx, from, to).await?;
+            }
+            (Operation::RemoveStack(order), true) => {
+                Self::remove_stack(tx, &order.stack).await?;
+            }
+            (Operation::RemoveStack(order), false) => {
+                Self::create_stack(&mut *tx, &order.stack).await?;
+                Self::set_stack_order(tx, &order.stack, &order.notes).await?;
+            }
+            (
+                Operation::SetAliases {
+                    note,
+                    before,
+                    after,
+                },
+                forward,
+            ) => {
+                let aliases = if forward { after } else { before };
+                Self::set_aliases(tx, note, aliases).await?;
+            }
+            (
+                Operation::SetProperty {
+                    note,
+                    key,
+                    before,
+                    after,
+                },
+                forward,
+            ) => {
+                let value = if forward { after } else { before };
+                Self::set_property(tx, note, key, value.as_deref()).await?;
+            }
+            (Operation::MergeNotes { operations, .. }, forward)
+            | (Operation::SplitNote { operations, .. }, forward) => {
+                let mut steps = std::mem::take(operations);
+                if !forward {
+                    steps.reverse();
+                }
+                for step in steps {
+                    let (step, files) =
+                        Box::pin(Self::apply(&mut *tx, root, step, forward, report)).await?;
+                    operations.push(step);
+                    stale_files.extend(files);
+                }
+                if !forward {
+                    operations.reverse();
+                }
+            }
+        }
+        if let Operation::MergeNotes {
+            rewrite: Some(rewrite),
+            ..
+        }
+        | Operation::SplitNote {
+            rewrite: Some(rewrite),
+            ..
+        } = &operation
+        {
+            report.apply_rewrite(rewrite, forward)?;
+        }
+
+        Ok((operation, stale_files))
+    }
+
+    /// purged note shouldn't be recoverable from journal either
+    async fn forget_body(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        snapshot: &NoteSnapshot,
+    ) -> Result<()> {
+        let entries = sqlx::query("select * from journal")
+            .map(Self::query_journal_entry)
+            .fetch_all(&mut *tx)
+            .await?;
+        for entry in entries {
+            let mut entry = entry?;
+            if !entry
+                .operation
+                .forget_body(&snapshot.name, snapshot.filename.as_deref())
+            {
+                continue;
+            }
+            let operation = serde_json::to_string(&entry.operation)
+                .map_err(|err| Error::Protocol(format!("{:?}", err)))?;
+            sqlx::query("update journal set operation = ?2 where id = ?1")
+                .bind(entry.id)
+                .bind(operation)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    fn query_journal_entry(row: SqliteRow) -> Result<JournalEntry> {
+        let operation: String = row.get("operation");
+        let operation =
+            serde_json::from_str(&operation).map_err(|err| Error::Decode(Box::new(err)))?;
+        Ok(JournalEntry {
+            id: row.get("id"),
+            operation,
+            undone: row.get("undone"),
+            applied_at: row.get("applied_at"),
+        })
+    }
+
+    async fn step_journal(&mut self, forward: bool) -> Result<Option<(JournalEntry, StepReport)>> {
+        let query = if forward {
+            "select * from journal where undone = 1 order by id asc limit 1"
+        } else {
+            "select * from journal where undone = 0 order by id desc limit 1"
+        };
+
+        let mut tx = self.pool.begin().await?;
+        let entry = sqlx::query(query)
+            .map(Self::query_journal_entry)
+            .fetch_optional(&mut tx)
+            .await?
+            .transpose()?;
+        let Some(entry) = entry else {
+            return Ok(None);
+        };
+        log::debug!("journal step (forward: {}) of {:?}", forward, entry);
+
+        let mut report = StepReport::default();
+        let (operation, stale_files) =
+            Self::apply(&mut tx, &self.root, entry.operation, forward, &mut report).await?;
+        let serialized = serde_json::to_string(&operation)
+            .map_err(|err| Error::Protocol(format!("{:?}", err)))?;
+        sqlx::query("update journal set operation = ?2, undone = ?3 where id = ?1")
+            .bind(entry.id)
+            .bind(serialized)
+            .bind(!forward)
+            .execute(&mut tx)
+            .await?;
+        tx.commit().await?;
+
+        for file_path in stale_files {
+            trash::move_to_trash(&self.root, &file_path)?;
+        }
+
+        let entry = JournalEntry {
+            operation,
+            undone: !forward,
+            ..entry
+        };
+        Ok(Some((entry, report)))
+    }
+
+    fn query_note(row: SqliteRow, color_scheme: ColorScheme) -> Note {
+        let file_path: Option<String> = row.get("filename");
+        let mut note = Note::new(row.get("name"), file_path.map(|c| c.into()), color_scheme);
+        // not every query selects aliases
+        if let Ok(Some(aliases)) = row.try_get::<Option<String>, _>("aliases") {
+            note.set_aliases(aliases.lines().map(str::to_string).collect());
+        }
+        note
+    }
+
+    fn query_labelled_note(row: SqliteRow, color_scheme: ColorScheme) -> (Note, Option<String>) {
+        let label: Option<String> = row.get("label");
+        (Self::query_note(row, color_scheme), label)
+    }
+
+    fn query_stack_index(row: SqliteRow) -> i64 {
+        row.get("stack_index")
+    }
+    fn query_stack_tag(row: SqliteRow) -> String {
+        row.get("stack_tag")
+    }
+    fn query_tag(row: SqliteRow) -> String {
+        row.get("tag")
+    }
+
+    async fn find_links_from_inner(
+        &self,
+        from: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+    ) -> Result<Vec<(Note, Option<String>)>> {
+        log::debug!("listing notes, linked by current");
+
+        let sql = SqlBuilder::select_from(name!("linkx"; "l"))
+            .field(name!("l", "_to"; "name"))
+            .field(name!("n", "filename"; "filename"))
+            .field(name!("l", "label"; "label"))
+            .field(ALIASES_FIELD)
+            .left()
+            .join(name!("notes"; "n"))
+            .on("l._to = n.name")
+            .and_where_eq("l._from", quote(from))
+            .order_desc("l._to")
+            .sql()
+            .map_err(|err| Error::Protocol(format!("{:?}", err)))?;
+        log::debug!("sql: {}", sql);
+
+        let res = sqlx::query(&sql)
+            .map(|row| Self::query_labelled_note(row, color_scheme))
+            .fetch_all(&self.pool)
+            .await?;
+        let res = parse_labelled_names(res, md_static).await;
+
+        Ok(res)
+    }
+
+    async fn find_links_to_inner(
+        &self,
+        to: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+    ) -> Result<Vec<(Note, Option<String>)>> {
+        log::debug!("listing notes, linking to current");
+
+        let sql = SqlBuilder::select_from(name!("linkx"; "l"))
+            .field(name!("l", "_from"; "name"))
+            .field(name!("n", "filename"; "filename"))
+            .field(name!("l", "label"; "label"))
+            .field(ALIASES_FIELD)
+            .left()
+            .join(name!("notes"; "n"))
+            .on("l._from = n.name")
+            .and_where_eq("l._to", quote(to))
+            .order_desc("l._from")
+            .sql()
+            .map_err(|err| Error::Protocol(format!("{:?}", err)))?;
+        log::debug!("sql: {}", sql);
+
+        let res = sqlx::query(&sql)
+            .map(|row| Self::query_labelled_note(row, color_scheme))
+            .fetch_all(&self.pool)
+            .await?;
+        let res = parse_labelled_names(res, md_static).await;
+
+        Ok(res)
+    }
+
+    async fn insert_link_inner(&mut self, from: &str, to: &str, label: Option<&str>) -> Result<()> {
+        log::debug!("saving link {} -> {} ({:?})", from, to, label);
+
+        let mut tx = self.pool.begin().await?;
+        Self::insert_link(&mut tx, from, to, label).await?;
+        Self::touch_updated(&mut tx, from, to).await?;
+        let link = LinkRecord {
+            from: from.to_string(),
+            to: to.to_string(),
+            label: label.map(str::to_string),
+        };
+        Self::record(&mut tx, &Operation::Link(link)).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn remove_link_inner(&mut self, from: &str, to: &str) -> Result<()> {
+        log::debug!("removing link {} -> {} ", from, to);
+
+        let mut tx = self.pool.begin().await?;
+        let label: Option<Option<String>> =
+            sqlx::query("select label from linkx where _from = ?1 and _to = ?2")
+                .bind(from)
+                .bind(to)
+                .map(|row: SqliteRow| row.get("label"))
+                .fetch_optional(&mut tx)
+                .await?;
+        let (synced,): (bool,) =
+            sqlx::query_as("select count(*) > 0 from synced_links where _from = ?1 and _to = ?2")
+                .bind(from)
+                .bind(to)
+                .fetch_one(&mut tx)
+                .await?;
+        Self::remove_link(&mut tx, from, to).await?;
+        Self::touch_updated(&mut tx, from, to).await?;
+        if let Some(label) = label {
+            let link = LinkRecord {
+                from: from.to_string(),
+                to: to.to_string(),
+                label,
+            };
+            let operation = if synced {
+                Operation::UnlinkSynced(link)
+            } else {
+                Operation::Unlink(link)
+            };
+            Self::record(&mut tx, &operation).await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Database for Sqlite {
+    async fn save(&mut self, note: &Note) -> Result<()> {
+        log::debug!("saving note to sqlite {:?}", note);
+
+        let mut tx = self.pool.begin().await?;
+        Self::save_note(&mut tx, note).await?;
+        let snapshot = NoteSnapshot {
+            name: note.name(),
+            filename: note
+                .file_path()
+                .map(|path| path.to_string_lossy().to_string()),
+            body: None,
+            created_at: None,
+            links: vec![],
+            stacks: vec![],
+            aliases: vec![],
+            properties: BTreeMap::new(),
+        };
+        Self::record(&mut tx, &Operation::CreateNote(snapshot)).await?;
+        if let Some(file_path) = note.file_path() {
+            // body may not be persisted yet, zero mtime marks it stale for next refresh
+            let (body, mtime) = match file_mtime(file_path) {
+                Ok(mtime) => (fs::read_to_string(file_path)?, mtime),
+                Err(_) => (String::new(), 0),
+            };
+            Self::index_body(&mut tx, &note.name(), &body, mtime).await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn list(
+        &self,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+    ) -> Result<Vec<Note>> {
+        log::debug!("listing notes");
+
+        let mut query = SqlBuilder::select_from(SqlName::new("notes").alias("n").baquoted());
+        query.field("*").field(ALIASES_FIELD).order_desc("name");
+
+        let query = query.sql().expect("bug in list query. please report");
+
+        let res = sqlx::query(&query)
+            .map(|row| Self::query_note(row, color_scheme))
+            .fetch_all(&self.pool)
+            .await?;
+        let res = parse_names(res, md_static).await;
+
+        Ok(res)
+    }
+
+    async fn get(
+        &self,
+        name: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
     ) -> Result<Note> {
         log::debug!("listing notes");
 
         let mut query = SqlBuilder::select_from(SqlName::new("notes").alias("n").baquoted());
-        query.field("*").and_where_eq("name", &quote(name));
+        query
+            .field("*")
+            .field(ALIASES_FIELD)
+            .and_where_eq("name", quote(name))
+            .or_where(format!(
+                "name in (select note from aliases where alias = {})",
+                quote(name)
+            ))
+            // exact name wins over an alias
+            .order_by(format!("name = {}", quote(name)), true);
 
         let query = query.sql().expect("bug in list query. please report");
 
-        let mut res = sqlx::query(&query)
+        let mut res = sqlx::query(&query)
+            .map(|row| Self::query_note(row, color_scheme))
+            .fetch_one(&self.pool)
+            .await?;
+        let mut highlighter = HighlightLines::new(md_static.1, md_static.2);
+        res.set_markdown(&mut highlighter, md_static);
+
+        Ok(res)
+    }
+
+    async fn find_links_from(
+        &self,
+        from: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+        straight: bool,
+    ) -> Result<Vec<Note>> {
+        let res = self
+            .find_labelled_links_from(from, md_static, color_scheme, straight)
+            .await?;
+        Ok(res.into_iter().map(|(note, _label)| note).collect())
+    }
+
+    async fn find_links_to(
+        &self,
+        to: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+        straight: bool,
+    ) -> Result<Vec<Note>> {
+        let res = self
+            .find_labelled_links_to(to, md_static, color_scheme, straight)
+            .await?;
+        Ok(res.into_iter().map(|(note, _label)| note).collect())
+    }
+
+    async fn find_labelled_links_from(
+        &self,
+        from: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+        straight: bool,
+    ) -> Result<Vec<(Note, Option<String>)>> {
+        if straight {
+            self.find_links_from_inner(from, md_static, color_scheme)
+                .await
+        } else {
+            self.find_links_to_inner(from, md_static, color_scheme)
+                .await
+        }
+    }
+
+    async fn find_labelled_links_to(
+        &self,
+        to: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+        straight: bool,
+    ) -> Result<Vec<(Note, Option<String>)>> {
+        if straight {
+            self.find_links_to_inner(to, md_static, color_scheme).await
+        } else {
+            self.find_links_from_inner(to, md_static, color_scheme)
+                .await
+        }
+    }
+
+    async fn reachable_subgraph(
+        &self,
+        from: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+        straight: bool,
+        depth: Option<usize>,
+    ) -> Result<Subgraph> {
+        log::debug!("selecting subgraph, reachable from {}", from);
+
+        let (near, far) = if straight {
+            ("_from", "_to")
+        } else {
+            ("_to", "_from")
+        };
+        // without depth limit a note is visited once; with it, once per depth, at which
+        // it's reached, so that the nearest one is found
+        let reach = match depth {
+            None => format!(
+                "reach(name, depth) as (
+                    select ?1, 0
+                    union
+                    select l.{far}, 0 from reach r join linkx l on l.{near} = r.name
+                ),
+                nearest(name, depth) as (select name, depth from reach)"
+            ),
+            Some(_) => format!(
+                "reach(name, depth) as (
+                    select ?1, 0
+                    union
+                    select l.{far}, r.depth + 1 from reach r join linkx l on l.{near} = r.name
+                    where r.depth < ?2
+                ),
+                nearest(name, depth) as (select name, min(depth) from reach group by name)"
+            ),
+        };
+        let sql = format!(
+            "with recursive {reach}
+            select r.name as name, n.filename as filename, {ALIASES_FIELD},
+                l.{far} as next, l.label as label
+            from nearest r
+            join notes n on n.name = r.name
+            left join linkx l on l.{near} = r.name and (?2 is null or r.depth < ?2)
+            order by r.depth asc, r.name asc, l.{far} desc"
+        );
+
+        let rows = sqlx::query(&sql)
+            .bind(from)
+            .bind(depth.map(|depth| depth as i64))
+            .fetch_all(&self.pool)
+            .await?;
+        if rows.is_empty() {
+            return Err(Error::RowNotFound);
+        }
+
+        let mut notes: Vec<Note> = vec![];
+        let mut links: LinksFrom = HashMap::new();
+        for row in rows {
+            let next: Option<String> = row.get("next");
+            let label: Option<String> = row.get("label");
+            let note = Self::query_note(row, color_scheme);
+            let from = links.entry(note.name()).or_default();
+            if let Some(next) = next {
+                from.push((next, label));
+            }
+            if notes.last() != Some(&note) {
+                notes.push(note);
+            }
+        }
+        Ok(Subgraph::new(notes, links, md_static))
+    }
+
+    async fn list_link_labels(&self) -> Result<Vec<String>> {
+        let res = sqlx::query(
+            "select distinct label from linkx
+            where label is not null order by label asc",
+        )
+        .map(|row: SqliteRow| row.get("label"))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    async fn all_labelled_links(&self) -> Result<Vec<LinkRecord>> {
+        let res = sqlx::query("select _from, _to, label from linkx order by _from, _to")
+            .map(|row: SqliteRow| LinkRecord {
+                from: row.get("_from"),
+                to: row.get("_to"),
+                label: row.get("label"),
+            })
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(res)
+    }
+
+    async fn insert_link(
+        &mut self,
+        mut from: &str,
+        mut to: &str,
+        straight: bool,
+        label: Option<&str>,
+    ) -> Result<()> {
+        if !straight {
+            std::mem::swap(&mut from, &mut to);
+        }
+        self.insert_link_inner(from, to, label).await
+    }
+
+    async fn remove_link(&mut self, mut from: &str, mut to: &str, straight: bool) -> Result<()> {
+        if !straight {
+            std::mem::swap(&mut from, &mut to);
+        }
+        self.remove_link_inner(from, to).await
+    }
+
+    async fn remove_note(&mut self, note: &Note) -> Result<()> {
+        log::debug!("removing note {:?}", note);
+
+        let mut tx = self.pool.begin().await?;
+        let snapshot = Self::drop_note(&mut tx, &note.name()).await?;
+        Self::trash_note(&mut tx, &snapshot).await?;
+        Self::record(&mut tx, &Operation::RemoveNote(snapshot)).await?;
+        tx.commit().await?;
+
+        if let Some(file_path) = note.file_path() {
+            trash::move_to_trash(&self.root, &file_path.to_string_lossy())?;
+        }
+
+        Ok(())
+    }
+
+    async fn rename_note(
+        &mut self,
+        note: &Note,
+        new_name: &str,
+        rewrites: &[BodyRewrite],
+    ) -> Result<()> {
+        log::debug!("renaming note {:?} -> {}", note, new_name);
+
+        let mut tx = self.pool.begin().await?;
+        Self::rename_note(&mut tx, &note.name(), new_name).await?;
+        Self::rename_indexed_body(&mut tx, &note.name(), new_name).await?;
+        let operation = Operation::RenameNote {
+            from: note.name(),
+            to: new_name.to_string(),
+            rewrites: rewrites.to_vec(),
+        };
+        Self::record(&mut tx, &operation).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+    async fn push_note_to_stack(&mut self, stack: &str, note: &str) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let before = Self::stack_order(&mut tx, stack).await?;
+        Self::push_note_to_stack(&mut tx, stack, note).await?;
+        Self::record_reorder(&mut tx, stack, before).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn pop_note_from_stack(&mut self, stack: &str, note: &str) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let before = Self::stack_order(&mut tx, stack).await?;
+        Self::pop_note_from_stack(&mut tx, stack, note).await?;
+        Self::record_reorder(&mut tx, stack, before).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+    async fn move_to_topmost(&mut self, stack: &str, note: &str) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let before = Self::stack_order(&mut tx, stack).await?;
+        Self::move_to_topmost(&mut tx, stack, note).await?;
+        Self::record_reorder(&mut tx, stack, before).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+    async fn swap_with_above(&mut self, stack: &str, note: &str) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let before = Self::stack_order(&mut tx, stack).await?;
+        Self::swap_with_above(&mut tx, stack, note).await?;
+        Self::record_reorder(&mut tx, stack, before).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+    async fn swap_with_below(&mut self, stack: &str, note: &str) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let before = Self::stack_order(&mut tx, stack).await?;
+        Self::swap_with_below(&mut tx, stack, note).await?;
+        Self::record_reorder(&mut tx, stack, before).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn create_stack(&mut self, stack: &str) -> Result<()> {
+        log::debug!("creating stack {}", stack);
+
+        let mut tx = self.pool.begin().await?;
+        Self::create_stack(&mut tx, stack).await?;
+        let operation = Operation::CreateStack {
+            stack: stack.to_string(),
+        };
+        Self::record(&mut tx, &operation).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn list_stacks(&self) -> Result<Vec<String>> {
+        let res = sqlx::query("select tag from stack_tags order by tag asc")
+            .map(Self::query_tag)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(res)
+    }
+
+    async fn rename_stack(&mut self, stack: &str, new_name: &str) -> Result<()> {
+        log::debug!("renaming stack {} -> {}", stack, new_name);
+
+        let mut tx = self.pool.begin().await?;
+        Self::rename_stack(&mut tx, stack, new_name).await?;
+        let operation = Operation::RenameStack {
+            from: stack.to_string(),
+            to: new_name.to_string(),
+        };
+        Self::record(&mut tx, &operation).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn remove_stack(&mut self, stack: &str) -> Result<()> {
+        log::debug!("removing stack {}", stack);
+
+        let mut tx = self.pool.begin().await?;
+        let notes = Self::stack_order(&mut tx, stack).await?;
+        Self::remove_stack(&mut tx, stack).await?;
+        let order = StackOrder {
+            stack: stack.to_string(),
+            notes,
+        };
+        Self::record(&mut tx, &Operation::RemoveStack(order)).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn select_from_stack(
+        &mut self,
+        stack: &str,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+    ) -> Result<Vec<Note>> {
+        let sql = SqlBuilder::select_from(name!("stacked_notes"; "st"))
+            .field(name!("st", "note"; "name"))
+            .field(name!("n", "filename"; "filename"))
+            .field(ALIASES_FIELD)
+            .left()
+            .join(name!("notes"; "n"))
+            .on("st.note = n.name")
+            .and_where_eq("st.stack_tag", quote(stack))
+            .order_asc("st.stack_index")
+            .sql()
+            .map_err(|err| Error::Protocol(format!("{:?}", err)))?;
+        log::debug!("sql: {}", sql);
+
+        let res = sqlx::query(&sql)
             .map(|row| Self::query_note(row, color_scheme))
-            .fetch_one(&self.pool)
+            .fetch_all(&self.pool)
             .await?;
-        let mut highlighter = HighlightLines::new(md_static.1, md_static.2);
-        res.set_markdown(&mut highlighter, md_static);
+        let res = parse_names(res, md_static).await;
 
         Ok(res)
     }
 
-    async fn find_links_from(
-        &self,
-        from: &str,
-        md_static: MarkdownStatic,
-        color_scheme: ColorScheme,
-        straight: bool,
-    ) -> Result<Vec<Note>> {
-        if straight {
-            self.find_links_from_inner(from, md_static, color_scheme)
-                .await
-        } else {
-            self.find_links_to_inner(from, md_static, color_scheme)
-                .await
+    async fn refresh_search_index(&mut self) -> Result<usize> {
+        log::debug!("refreshing full-text search index");
+
+        let notes = sqlx::query(
+            "select n.name as name, n.filename as filename, s.mtime as mtime from notes n
+            left join fts_index_state s on s.name = n.name
+            where n.filename is not null",
+        )
+        .map(|row: SqliteRow| {
+            let name: String = row.get("name");
+            let filename: String = row.get("filename");
+            let mtime: Option<i64> = row.get("mtime");
+            (name, PathBuf::from(filename), mtime)
+        })
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut tx = self.pool.begin().await?;
+        let mut refreshed = 0;
+        for (name, file_path, indexed_mtime) in notes {
+            let mtime = match file_mtime(&file_path) {
+                Ok(mtime) => mtime,
+                Err(err) => {
+                    log::debug!("skipping index of {:?}: {:?}", file_path, err);
+                    continue;
+                }
+            };
+            if indexed_mtime == Some(mtime) {
+                continue;
+            }
+            // e.g. a file in another encoding shouldn't prevent indexing of the rest
+            let body = match fs::read_to_string(&file_path) {
+                Ok(body) => body,
+                Err(err) => {
+                    eprintln!("skipping index of {}: {}", file_path.display(), err);
+                    continue;
+                }
+            };
+            Self::index_body(&mut tx, &name, &body, mtime).await?;
+            refreshed += 1;
         }
+        tx.commit().await?;
+
+        Ok(refreshed)
     }
 
-    async fn find_links_to(
+    async fn search(
         &self,
-        to: &str,
+        query: &str,
         md_static: MarkdownStatic,
         color_scheme: ColorScheme,
-        straight: bool,
     ) -> Result<Vec<Note>> {
-        if straight {
-            self.find_links_to_inner(to, md_static, color_scheme).await
-        } else {
-            self.find_links_from_inner(to, md_static, color_scheme)
-                .await
-        }
+        log::debug!("full-text search of `{}`", query);
+
+        let res = sqlx::query(
+            "select n.name as name, n.filename as filename,
+                (select group_concat(a.alias, char(10)) from aliases a where a.note = n.name) as aliases
+            from notes_fts
+            join notes n on n.name = notes_fts.name
+            where notes_fts match ?1
+            order by bm25(notes_fts)",
+        )
+        .bind(query)
+        .map(|row| Self::query_note(row, color_scheme))
+        .fetch_all(&self.pool)
+        .await?;
+        let res = parse_names(res, md_static).await;
+
+        Ok(res)
     }
 
-    async fn insert_link(&mut self, mut from: &str, mut to: &str, straight: bool) -> Result<()> {
-        if !straight {
-            std::mem::swap(&mut from, &mut to);
-        }
-        self.insert_link_inner(from, to).await
+    async fn mark_opened(&mut self, name: &str) -> Result<()> {
+        sqlx::query("update notes set opened_at = ?2 where name = ?1")
+            .bind(name)
+            .bind(now())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
     }
 
-    async fn remove_link(&mut self, mut from: &str, mut to: &str, straight: bool) -> Result<()> {
-        if !straight {
-            std::mem::swap(&mut from, &mut to);
+    async fn timestamps(&self) -> Result<HashMap<String, NoteTimestamps>> {
+        let res = sqlx::query("select name, created_at, updated_at, opened_at from notes")
+            .map(|row: SqliteRow| {
+                let timestamps = NoteTimestamps {
+                    created: row.get("created_at"),
+                    updated: row.get("updated_at"),
+                    opened: row.get("opened_at"),
+                };
+                (row.get("name"), timestamps)
+            })
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(res.into_iter().collect())
+    }
+
+    async fn undo(&mut self) -> Result<Option<(JournalEntry, StepReport)>> {
+        self.step_journal(false).await
+    }
+
+    async fn redo(&mut self) -> Result<Option<(JournalEntry, StepReport)>> {
+        self.step_journal(true).await
+    }
+
+    async fn journal(&self, limit: usize) -> Result<Vec<JournalEntry>> {
+        let res = sqlx::query("select * from journal order by id desc limit ?1")
+            .bind(limit as i64)
+            .map(Self::query_journal_entry)
+            .fetch_all(&self.pool)
+            .await?;
+
+        res.into_iter().collect()
+    }
+
+    async fn list_trash(&self) -> Result<Vec<TrashEntry>> {
+        let res = sqlx::query("select * from trash order by id desc")
+            .map(Self::query_trash_entry)
+            .fetch_all(&self.pool)
+            .await?;
+
+        res.into_iter().collect()
+    }
+
+    async fn restore_from_trash(&mut self, id: i64) -> Result<Vec<LinkRecord>> {
+        let mut tx = self.pool.begin().await?;
+        let entry = sqlx::query("select * from trash where id = ?1")
+            .bind(id)
+            .map(Self::query_trash_entry)
+            .fetch_one(&mut tx)
+            .await??;
+        let snapshot = entry.snapshot;
+        log::debug!("restoring from trash {:?}", snapshot);
+
+        Self::insert_restored_note(&mut tx, &self.root, &snapshot).await?;
+
+        let existing: Vec<String> = sqlx::query("select name from notes")
+            .map(|row: SqliteRow| row.get("name"))
+            .fetch_all(&mut tx)
+            .await?;
+        let mut skipped = vec![];
+        for link in snapshot.links {
+            if existing.contains(&link.from) && existing.contains(&link.to) {
+                Self::insert_link(&mut tx, &link.from, &link.to, link.label.as_deref()).await?;
+            } else {
+                skipped.push(link);
+            }
         }
-        self.remove_link_inner(from, to).await
+
+        let stacks = Self::find_all_stack_tags(&mut tx).await?;
+        for order in snapshot.stacks {
+            if !stacks.contains(&order.stack) {
+                continue;
+            }
+            let index = order
+                .notes
+                .iter()
+                .position(|note| note == &snapshot.name)
+                .unwrap_or(0);
+            Self::insert_into_stack(&mut tx, &order.stack, &snapshot.name, index).await?;
+        }
+        let mut aliases = vec![];
+        for alias in snapshot.aliases {
+            // may have been taken, while the note was in trash
+            if Self::alias_owner(&mut tx, &alias).await?.is_none() {
+                aliases.push(alias);
+            }
+        }
+        Self::set_aliases(&mut tx, &snapshot.name, &aliases).await?;
+        for (key, value) in &snapshot.properties {
+            Self::set_property(&mut tx, &snapshot.name, key, Some(value)).await?;
+        }
+        sqlx::query("delete from trash where id = ?1")
+            .bind(id)
+            .execute(&mut tx)
+            .await?;
+
+        let restored = Self::snapshot_note(&mut tx, &snapshot.name).await?;
+        Self::record(&mut tx, &Operation::CreateNote(restored)).await?;
+        tx.commit().await?;
+
+        Ok(skipped)
     }
 
-    async fn remove_note(&mut self, note: &Note) -> Result<()> {
-        log::debug!("removing note {:?}", note);
+    async fn purge_trash(&mut self, id: Option<i64>) -> Result<usize> {
+        let entries = match id {
+            Some(id) => {
+                sqlx::query("select * from trash where id = ?1")
+                    .bind(id)
+                    .map(Self::query_trash_entry)
+                    .fetch_all(&self.pool)
+                    .await?
+            }
+            None => {
+                sqlx::query("select * from trash")
+                    .map(Self::query_trash_entry)
+                    .fetch_all(&self.pool)
+                    .await?
+            }
+        };
 
         let mut tx = self.pool.begin().await?;
+        let mut purged = 0;
+        for entry in entries {
+            let entry = entry?;
+            sqlx::query("delete from trash where id = ?1")
+                .bind(entry.id)
+                .execute(&mut tx)
+                .await?;
+            Self::forget_body(&mut tx, &entry.snapshot).await?;
+            if let Some(filename) = &entry.snapshot.filename {
+                trash::remove_from_trash(&self.root, filename)?;
+            }
+            purged += 1;
+        }
+        tx.commit().await?;
 
-        let stack_tags = Self::find_all_stacks(&mut tx, &note.name()).await?;
-        for stack in stack_tags {
-            Self::pop_note_from_stack(&mut tx, &stack, &note.name()).await?;
+        Ok(purged)
+    }
+
+    async fn set_filename(&mut self, name: &str, filename: Option<&str>) -> Result<()> {
+        log::debug!("setting body of {} to {:?}", name, filename);
+
+        let mut tx = self.pool.begin().await?;
+        Self::forget_parsed_file(&mut tx, name).await?;
+        sqlx::query("update notes set filename = ?2, updated_at = ?3 where name = ?1")
+            .bind(name)
+            .bind(filename)
+            .bind(now())
+            .execute(&mut tx)
+            .await?;
+        Self::unindex_body(&mut tx, name).await?;
+        if filename.is_some() {
+            // zero mtime marks it stale for next refresh
+            Self::index_body(&mut tx, name, "", 0).await?;
         }
-        Self::remove_note(&mut tx, note).await?;
         tx.commit().await?;
 
         Ok(())
     }
 
-    async fn rename_note(&mut self, note: &Note, new_name: &str) -> Result<()> {
-        log::debug!("renaming note {:?} -> {}", note, new_name);
+    async fn find_dangling_stack_entries(&self) -> Result<Vec<(String, String)>> {
+        let res = sqlx::query(
+            "select stack_tag, note from stacked_notes
+            where note not in (select name from notes)
+                or stack_tag not in (select tag from stack_tags)
+            order by stack_tag, stack_index",
+        )
+        .map(|row: SqliteRow| (row.get("stack_tag"), row.get("note")))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(res)
+    }
 
+    async fn repair_stacks(&mut self) -> Result<()> {
         let mut tx = self.pool.begin().await?;
-        Self::rename_note(&mut tx, note, new_name).await?;
+        sqlx::query(
+            "delete from stacked_notes
+            where note not in (select name from notes)
+                or stack_tag not in (select tag from stack_tags)",
+        )
+        .execute(&mut tx)
+        .await?;
+        // close gaps in indices, left by removed entries
+        for stack in Self::find_all_stack_tags(&mut tx).await? {
+            let notes = Self::stack_order(&mut tx, &stack).await?;
+            Self::set_stack_order(&mut tx, &stack, &notes).await?;
+        }
         tx.commit().await?;
 
         Ok(())
     }
-    async fn push_note_to_stack(&mut self, stack: &str, note: &str) -> Result<()> {
+
+    async fn dump(&self) -> Result<Dump> {
+        let mut notes = sqlx::query(
+            "select name, filename, created_at, updated_at, opened_at,
+                (select group_concat(a.alias, char(10)) from aliases a where a.note = n.name) as aliases
+            from notes n
+            order by name asc",
+        )
+        .map(|row: SqliteRow| {
+            let filename: Option<String> = row.get("filename");
+            let aliases: Option<String> = row.get("aliases");
+            let mut aliases = aliases
+                .map(|aliases| aliases.lines().map(str::to_string).collect::<Vec<_>>())
+                .unwrap_or_default();
+            aliases.sort();
+            DumpedNote {
+                aliases,
+                name: row.get("name"),
+                tag: filename.is_none(),
+                filename,
+                created_at: row.get("created_at"),
+                updated_at: row.get("updated_at"),
+                opened_at: row.get("opened_at"),
+                properties: BTreeMap::new(),
+            }
+        })
+        .fetch_all(&self.pool)
+        .await?;
+        let properties: Vec<(String, String, String)> =
+            sqlx::query_as("select note, key, value from properties")
+                .fetch_all(&self.pool)
+                .await?;
+        for (name, key, value) in properties {
+            if let Some(note) = notes.iter_mut().find(|note| note.name == name) {
+                note.properties.insert(key, value);
+            }
+        }
+        let links = sqlx::query("select _from, _to, label from linkx order by _from, _to")
+            .map(|row: SqliteRow| LinkRecord {
+                from: row.get("_from"),
+                to: row.get("_to"),
+                label: row.get("label"),
+            })
+            .fetch_all(&self.pool)
+            .await?;
+
         let mut tx = self.pool.begin().await?;
+        let mut stacks = vec![];
+        let mut tags = Self::find_all_stack_tags(&mut tx).await?;
+        tags.sort();
+        for stack in tags {
+            let notes = Self::stack_order(&mut tx, &stack).await?;
+            stacks.push(StackOrder { stack, notes });
+        }
+        tx.commit().await?;
 
-        Self::push_note_to_stack(&mut tx, stack, note).await?;
+        Ok(Dump {
+            version: DUMP_VERSION,
+            notes,
+            links,
+            stacks,
+        })
+    }
+
+    async fn restore_dump(&mut self, dump: &Dump) -> Result<()> {
+        log::debug!("restoring {} notes from dump", dump.notes.len());
+
+        let mut tx = self.pool.begin().await?;
+        let (count,): (i64,) = sqlx::query_as("select count(*) from notes")
+            .fetch_one(&mut tx)
+            .await?;
+        if count > 0 {
+            return Err(Error::Protocol(format!(
+                "database already contains {} notes",
+                count
+            )));
+        }
+        for note in &dump.notes {
+            sqlx::query(
+                "insert into notes(name, filename, created_at, updated_at, opened_at)
+                    values(?1, ?2, ?3, ?4, ?5)",
+            )
+            .bind(&note.name)
+            .bind(&note.filename)
+            .bind(note.created_at)
+            .bind(note.updated_at)
+            .bind(note.opened_at)
+            .execute(&mut tx)
+            .await?;
+            if let Some(filename) = &note.filename {
+                let file_path = Path::new(filename);
+                // missing bodies are left to `doctor`
+                let (body, mtime) = match file_mtime(file_path) {
+                    Ok(mtime) => (fs::read_to_string(file_path)?, mtime),
+                    Err(_) => (String::new(), 0),
+                };
+                Self::index_body(&mut tx, &note.name, &body, mtime).await?;
+            }
+        }
+        for note in &dump.notes {
+            Self::set_aliases(&mut tx, &note.name, &note.aliases).await?;
+            for (key, value) in &note.properties {
+                Self::set_property(&mut tx, &note.name, key, Some(value)).await?;
+            }
+        }
+        for link in &dump.links {
+            Self::insert_link(&mut tx, &link.from, &link.to, link.label.as_deref()).await?;
+        }
+        let existing = Self::find_all_stack_tags(&mut tx).await?;
+        for order in &dump.stacks {
+            if !existing.contains(&order.stack) {
+                Self::create_stack(&mut tx, &order.stack).await?;
+            }
+            Self::set_stack_order(&mut tx, &order.stack, &order.notes).await?;
+        }
         tx.commit().await?;
+
         Ok(())
     }
 
-    async fn pop_note_from_stack(&mut self, stack: &str, note: &str) -> Result<()> {
-        let mut tx = self.pool.begin().await?;
+    async fn set_aliases(&mut self, note: &str, aliases: &[String]) -> Result<()> {
+        log::debug!("setting aliases of {} to {:?}", note, aliases);
 
-        Self::pop_note_from_stack(&mut tx, stack, note).await?;
+        let mut tx = self.pool.begin().await?;
+        let before = Self::find_aliases(&mut tx, note).await?;
+        let mut after = vec![];
+        for alias in aliases {
+            let alias = alias.trim();
+            if alias.is_empty() || alias == note || after.iter().any(|a| a == alias) {
+                continue;
+            }
+            match Self::alias_owner(&mut tx, alias).await? {
+                Some(owner) if owner != note => {
+                    return Err(Error::Protocol(format!(
+                        "`{}` is already a name or an alias of `{}`",
+                        alias, owner
+                    )));
+                }
+                _ => after.push(alias.to_string()),
+            }
+        }
+        after.sort();
+        if before != after {
+            Self::set_aliases(&mut tx, note, &after).await?;
+            let operation = Operation::SetAliases {
+                note: note.to_string(),
+                before,
+                after,
+            };
+            Self::record(&mut tx, &operation).await?;
+        }
         tx.commit().await?;
+
         Ok(())
     }
-    async fn move_to_topmost(&mut self, stack: &str, note: &str) -> Result<()> {
+
+    async fn properties(&self, note: &str) -> Result<BTreeMap<String, String>> {
         let mut tx = self.pool.begin().await?;
+        let properties = Self::find_properties(&mut tx, note).await?;
+        tx.commit().await?;
 
-        Self::move_to_topmost(&mut tx, stack, note).await?;
+        Ok(properties)
+    }
+
+    async fn set_property(&mut self, note: &str, key: &str, value: Option<&str>) -> Result<()> {
+        log::debug!("setting property {} of {} to {:?}", key, note, value);
+
+        let mut tx = self.pool.begin().await?;
+        let before = Self::find_properties(&mut tx, note).await?.remove(key);
+        if before.as_deref() != value {
+            Self::set_property(&mut tx, note, key, value).await?;
+            let operation = Operation::SetProperty {
+                note: note.to_string(),
+                key: key.to_string(),
+                before,
+                after: value.map(str::to_string),
+            };
+            Self::record(&mut tx, &operation).await?;
+        }
         tx.commit().await?;
+
         Ok(())
     }
-    async fn swap_with_above(&mut self, stack: &str, note: &str) -> Result<()> {
-        let mut tx = self.pool.begin().await?;
 
-        Self::swap_with_above(&mut tx, stack, note).await?;
+    async fn find_by_property(&self, key: &str, value: Option<&str>) -> Result<Vec<String>> {
+        let res = sqlx::query(
+            "select note from properties
+            where key = ?1 and (?2 is null or value = ?2)
+            order by note asc",
+        )
+        .bind(key)
+        .bind(value)
+        .map(|row: SqliteRow| row.get("note"))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    async fn list_property_keys(&self) -> Result<Vec<String>> {
+        let res = sqlx::query("select distinct key from properties order by key asc")
+            .map(|row: SqliteRow| row.get("key"))
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(res)
+    }
+
+    async fn insert_synced_link(&mut self, from: &str, to: &str) -> Result<()> {
+        log::debug!("saving synced link {} -> {}", from, to);
+
+        let mut tx = self.pool.begin().await?;
+        Self::insert_link(&mut tx, from, to, None).await?;
+        Self::touch_updated(&mut tx, from, to).await?;
+        Self::insert_synced(&mut tx, from, to).await?;
+        let link = LinkRecord {
+            from: from.to_string(),
+            to: to.to_string(),
+            label: None,
+        };
+        Self::record(&mut tx, &Operation::LinkSynced(link)).await?;
         tx.commit().await?;
 
         Ok(())
     }
-    async fn swap_with_below(&mut self, stack: &str, note: &str) -> Result<()> {
+
+    async fn find_synced_links_from(&self, from: &str) -> Result<Vec<String>> {
+        let res = sqlx::query("select _to from synced_links where _from = ?1 order by _to asc")
+            .bind(from)
+            .map(|row: SqliteRow| row.get("_to"))
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(res)
+    }
+
+    async fn merge_notes(
+        &mut self,
+        source: &str,
+        target: &str,
+        rewrite: Option<&BodyRewrite>,
+    ) -> Result<()> {
+        log::debug!("merging note {} into {}", source, target);
+
+        if source == target {
+            return Err(Error::Protocol(format!(
+                "can't merge `{}` into itself",
+                source
+            )));
+        }
         let mut tx = self.pool.begin().await?;
+        sqlx::query("select name from notes where name = ?1")
+            .bind(target)
+            .fetch_one(&mut tx)
+            .await?;
 
-        Self::swap_with_below(&mut tx, stack, note).await?;
+        let snapshot = Self::drop_note(&mut tx, source).await?;
+        Self::trash_note(&mut tx, &snapshot).await?;
+        let mut operations = vec![Operation::RemoveNote(snapshot.clone())];
+
+        let mut existing: Vec<(String, String)> =
+            sqlx::query_as("select _from, _to from linkx where _from = ?1 or _to = ?1")
+                .bind(target)
+                .fetch_all(&mut tx)
+                .await?;
+        let repoint = |name: &String| {
+            if name == source {
+                target.to_string()
+            } else {
+                name.clone()
+            }
+        };
+        for link in &snapshot.links {
+            let (from, to) = (repoint(&link.from), repoint(&link.to));
+            // no self-loops, and labels of already existing links of target win
+            if from == to || existing.contains(&(from.clone(), to.clone())) {
+                continue;
+            }
+            Self::insert_link(&mut tx, &from, &to, link.label.as_deref()).await?;
+            existing.push((from.clone(), to.clone()));
+            operations.push(Operation::Link(LinkRecord {
+                from,
+                to,
+                label: link.label.clone(),
+            }));
+        }
+
+        for order in &snapshot.stacks {
+            let before = Self::stack_order(&mut tx, &order.stack).await?;
+            if before.iter().any(|note| note == target) {
+                continue;
+            }
+            let index = order
+                .notes
+                .iter()
+                .position(|note| note == source)
+                .unwrap_or(0);
+            let mut after = before.clone();
+            after.insert(std::cmp::min(index, after.len()), target.to_string());
+            Self::set_stack_order(&mut tx, &order.stack, &after).await?;
+            operations.push(Operation::ReorderStack {
+                stack: order.stack.clone(),
+                before,
+                after,
+            });
+        }
+
+        // old name keeps resolving to the merged note
+        let before = Self::find_aliases(&mut tx, target).await?;
+        let mut after = before.clone();
+        for alias in std::iter::once(&snapshot.name).chain(&snapshot.aliases) {
+            if !after.contains(alias) {
+                after.push(alias.clone());
+            }
+        }
+        after.sort();
+        Self::set_aliases(&mut tx, target, &after).await?;
+        operations.push(Operation::SetAliases {
+            note: target.to_string(),
+            before,
+            after,
+        });
+
+        let properties = Self::find_properties(&mut tx, target).await?;
+        for (key, value) in &snapshot.properties {
+            if properties.contains_key(key) {
+                continue;
+            }
+            Self::set_property(&mut tx, target, key, Some(value)).await?;
+            operations.push(Operation::SetProperty {
+                note: target.to_string(),
+                key: key.clone(),
+                before: None,
+                after: Some(value.clone()),
+            });
+        }
+        Self::touch_updated(&mut tx, target, target).await?;
+
+        let operation = Operation::MergeNotes {
+            source: source.to_string(),
+            target: target.to_string(),
+            operations,
+            rewrite: rewrite.cloned(),
+        };
+        Self::record(&mut tx, &operation).await?;
         tx.commit().await?;
 
+        if let Some(filename) = &snapshot.filename {
+            trash::move_to_trash(&self.root, filename)?;
+        }
+
         Ok(())
     }
 
-    async fn select_from_stack(
+    async fn split_note(
         &mut self,
-        stack: &str,
-        md_static: MarkdownStatic,
-        color_scheme: ColorScheme,
-    ) -> Result<Vec<Note>> {
-        let sql = SqlBuilder::select_from(name!("stacked_notes"; "st"))
-            .field(name!("st", "note"; "name"))
-            .field(name!("n", "filename"; "filename"))
-            .left()
-            .join(name!("notes"; "n"))
-            .on("st.note = n.name")
-            .and_where_eq("st.stack_tag", quote(stack))
-            .order_asc("st.stack_index")
-            .sql()
-            .map_err(|err| Error::Protocol(format!("{:?}", err)))?;
-        log::debug!("sql: {}", sql);
+        note: &str,
+        children: &[Note],
+        rewrite: Option<&BodyRewrite>,
+    ) -> Result<()> {
+        log::debug!("splitting {} notes out of {}", children.len(), note);
 
-        let res = sqlx::query(&sql)
-            .map(|row| Self::query_note(row, color_scheme))
-            .fetch_all(&self.pool)
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("select name from notes where name = ?1")
+            .bind(note)
+            .fetch_one(&mut tx)
             .await?;
-        let res = parse_names(res, md_static).await;
 
-        Ok(res)
+        let mut operations = vec![];
+        for child in children {
+            let name = child.name();
+            if let Some(owner) = Self::alias_owner(&mut tx, &name).await? {
+                return Err(Error::Protocol(format!(
+                    "name `{}` is already taken by `{}`",
+                    name, owner
+                )));
+            }
+            Self::save_note(&mut tx, child).await?;
+            if let Some(file_path) = child.file_path() {
+                let body = fs::read_to_string(file_path)?;
+                Self::index_body(&mut tx, &name, &body, file_mtime(file_path)?).await?;
+            }
+            operations.push(Operation::CreateNote(NoteSnapshot {
+                name: name.clone(),
+                filename: child
+                    .file_path()
+                    .map(|path| path.to_string_lossy().to_string()),
+                body: None,
+                created_at: None,
+                links: vec![],
+                stacks: vec![],
+                aliases: vec![],
+                properties: BTreeMap::new(),
+            }));
+
+            Self::insert_link(&mut tx, note, &name, None).await?;
+            operations.push(Operation::Link(LinkRecord {
+                from: note.to_string(),
+                to: name,
+                label: None,
+            }));
+        }
+        Self::touch_updated(&mut tx, note, note).await?;
+
+        let operation = Operation::SplitNote {
+            note: note.to_string(),
+            operations,
+            rewrite: rewrite.cloned(),
+        };
+        Self::record(&mut tx, &operation).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    fn parse_cache(&self) -> ParseCache {
+        ParseCache::Sqlite(self.pool.clone())
     }
 }
@@ -0,0 +1,66 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::journal::NoteSnapshot;
+
+/// directory in notes' work dir, where bodies of removed notes are kept
+pub static TRASH_DIR: &str = ".trash";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub id: i64,
+    pub snapshot: NoteSnapshot,
+    pub trashed_at: i64,
+}
+
+/// bodies may be registered outside of work dir, so the whole path is encoded into file name;
+/// `%` is escaped first, so that no two paths share one
+fn trashed_path(root: &Path, filename: &str) -> PathBuf {
+    let file_name = filename
+        .trim_start_matches("./")
+        .replace('%', "%25")
+        .replace('/', "%2F")
+        .replace('\\', "%5C");
+    root.join(TRASH_DIR).join(file_name)
+}
+
+fn move_file(from: impl AsRef<Path>, to: impl AsRef<Path>) -> io::Result<()> {
+    if fs::rename(&from, &to).is_err() {
+        // e.g. `from` and `to` are on different filesystems
+        fs::copy(&from, &to)?;
+        fs::remove_file(&from)?;
+    }
+    Ok(())
+}
+
+/// `filename` of a body, as registered, is relative to notes' work dir `root`
+pub(super) fn move_to_trash(root: &Path, filename: &str) -> io::Result<()> {
+    let file_path = root.join(filename);
+    if !file_path.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(root.join(TRASH_DIR))?;
+    move_file(file_path, trashed_path(root, filename))
+}
+
+/// puts body back in place, if it's still in trash and nothing occupies its place
+pub(super) fn move_from_trash(root: &Path, filename: &str) -> io::Result<()> {
+    let file_path = root.join(filename);
+    let trashed = trashed_path(root, filename);
+    if file_path.exists() || !trashed.exists() {
+        return Ok(());
+    }
+    move_file(trashed, file_path)
+}
+
+pub(super) fn remove_from_trash(root: &Path, filename: &str) -> io::Result<()> {
+    let trashed = trashed_path(root, filename);
+    if trashed.exists() {
+        fs::remove_file(trashed)?;
+    }
+    Ok(())
+}
@@ -23,11 +23,7 @@ pub fn fetch_content_range(
     line: u64,
 ) -> Option<String> {
     if let Some(file_path) = file_path {
-        let first = if line - 20 > line {
-            1
-        } else {
-            std::cmp::max(1, line - 20)
-        };
+        let first = std::cmp::max(1, line.saturating_sub(20));
         let last = line + 5;
 
         file_line_cmd
@@ -0,0 +1,54 @@
+use std::{fs, io, path::Path};
+
+static DELIMITER: &str = "---";
+
+/// yaml front matter of `content` (without `---` delimiters), if it starts with one,
+/// and the rest of `content`
+pub fn split(content: &str) -> (Option<&str>, &str) {
+    let Some(rest) = content.strip_prefix(DELIMITER).and_then(|rest| {
+        rest.strip_prefix('\n')
+            .or_else(|| rest.strip_prefix("\r\n"))
+    }) else {
+        return (None, content);
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == DELIMITER {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    (None, content)
+}
+
+/// `body` with `front_matter` put in front of it
+pub fn join(front_matter: Option<&str>, body: &str) -> String {
+    match front_matter {
+        Some(front_matter) => {
+            let newline = if front_matter.ends_with('\n') || front_matter.is_empty() {
+                ""
+            } else {
+                "\n"
+            };
+            format!(
+                "{}\n{}{}{}\n{}",
+                DELIMITER, front_matter, newline, DELIMITER, body
+            )
+        }
+        None => body.to_string(),
+    }
+}
+
+/// text of body file without its front matter
+pub fn read_body(file_path: impl AsRef<Path>) -> io::Result<String> {
+    let content = fs::read_to_string(file_path)?;
+    Ok(split(&content).1.to_string())
+}
+
+/// replaces text of body file, keeping front matter, which it currently has
+pub fn write_body(file_path: impl AsRef<Path>, body: &str) -> io::Result<()> {
+    let file_path = file_path.as_ref();
+    let current = fs::read_to_string(file_path).unwrap_or_default();
+    let (front_matter, _) = split(&current);
+    fs::write(file_path, join(front_matter, split(body).1))
+}
@@ -0,0 +1,324 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// strongly connected components of a directed graph, in reverse topological order
+/// (iterative Tarjan's algorithm)
+pub fn strongly_connected_components(
+    nodes: &[String],
+    edges: &[(String, String)],
+) -> Vec<Vec<String>> {
+    let index_of: HashMap<&str, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| (node.as_str(), index))
+        .collect();
+    let mut adjacent = vec![vec![]; nodes.len()];
+    for (from, to) in edges {
+        if let (Some(&from), Some(&to)) = (index_of.get(from.as_str()), index_of.get(to.as_str())) {
+            adjacent[from].push(to);
+        }
+    }
+
+    let mut next_index = 0;
+    let mut indices: Vec<Option<usize>> = vec![None; nodes.len()];
+    let mut low_links = vec![0; nodes.len()];
+    let mut on_stack = vec![false; nodes.len()];
+    let mut stack = vec![];
+    let mut result = vec![];
+
+    for start in 0..nodes.len() {
+        if indices[start].is_some() {
+            continue;
+        }
+        // (node, position of next child to visit)
+        let mut call_stack = vec![(start, 0)];
+        while let Some((node, child)) = call_stack.pop() {
+            if child == 0 {
+                indices[node] = Some(next_index);
+                low_links[node] = next_index;
+                next_index += 1;
+                stack.push(node);
+                on_stack[node] = true;
+            }
+            if let Some(&next) = adjacent[node].get(child) {
+                call_stack.push((node, child + 1));
+                match indices[next] {
+                    None => call_stack.push((next, 0)),
+                    Some(next_index) if on_stack[next] => {
+                        low_links[node] = low_links[node].min(next_index);
+                    }
+                    Some(_) => {}
+                }
+                continue;
+            }
+
+            if Some(low_links[node]) == indices[node] {
+                let mut component = vec![];
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(nodes[member].clone());
+                    if member == node {
+                        break;
+                    }
+                }
+                result.push(component);
+            }
+            if let Some(&(parent, _)) = call_stack.last() {
+                low_links[parent] = low_links[parent].min(low_links[node]);
+            }
+        }
+    }
+    result
+}
+
+/// nodes, which belong to at least one cycle, including self-loops
+pub fn cyclic_nodes(nodes: &[String], edges: &[(String, String)]) -> HashSet<String> {
+    let mut result: HashSet<String> = edges
+        .iter()
+        .filter(|(from, to)| from == to)
+        .map(|(from, _to)| from.clone())
+        .collect();
+    for component in strongly_connected_components(nodes, edges) {
+        if component.len() > 1 {
+            result.extend(component);
+        }
+    }
+    result
+}
+
+fn adjacency(edges: &[(String, String)]) -> HashMap<&str, Vec<&str>> {
+    let mut adjacent: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (from, to) in edges {
+        adjacent.entry(from).or_default().push(to);
+    }
+    for next in adjacent.values_mut() {
+        next.sort_unstable();
+        next.dedup();
+    }
+    adjacent
+}
+
+/// one of the shortest paths from `from` to `to` along directed `edges`, both ends included
+/// (breadth-first search)
+pub fn shortest_path(edges: &[(String, String)], from: &str, to: &str) -> Option<Vec<String>> {
+    let adjacent = adjacency(edges);
+    let mut previous: HashMap<&str, &str> = HashMap::new();
+    let mut queue = VecDeque::from([from]);
+    let mut visited = HashSet::from([from]);
+
+    while let Some(node) = queue.pop_front() {
+        if node == to {
+            let mut path = vec![to.to_string()];
+            let mut current = to;
+            while let Some(&prev) = previous.get(current) {
+                path.push(prev.to_string());
+                current = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for &next in adjacent.get(node).into_iter().flatten() {
+            if visited.insert(next) {
+                previous.insert(next, node);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// all paths from `from` to `to` along directed `edges`, which visit no node twice and
+/// are at most `max_len` edges long, shortest first
+pub fn simple_paths(
+    edges: &[(String, String)],
+    from: &str,
+    to: &str,
+    max_len: usize,
+) -> Vec<Vec<String>> {
+    let adjacent = adjacency(edges);
+    let mut result = vec![];
+    // (path so far, position of next child to visit)
+    let mut path = vec![from];
+    let mut children = vec![0];
+    while let Some(child) = children.pop() {
+        let node = path[path.len() - 1];
+        if node == to {
+            result.push(path.iter().map(|node| node.to_string()).collect::<Vec<_>>());
+            path.pop();
+            continue;
+        }
+        let next = adjacent
+            .get(node)
+            .and_then(|next| next.get(child))
+            .copied()
+            .filter(|_| path.len() <= max_len);
+        match next {
+            Some(next) => {
+                children.push(child + 1);
+                if !path.contains(&next) {
+                    path.push(next);
+                    children.push(0);
+                }
+            }
+            None => {
+                path.pop();
+            }
+        }
+    }
+    result.sort_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+    result
+}
+
+/// nodes, which no edge leads to from another node
+pub fn sources(nodes: &[String], edges: &[(String, String)]) -> Vec<String> {
+    let targets: HashSet<&str> = edges
+        .iter()
+        .filter(|(from, to)| from != to)
+        .map(|(_from, to)| to.as_str())
+        .collect();
+    nodes
+        .iter()
+        .filter(|node| !targets.contains(node.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// nodes, which no edge leads from to another node
+pub fn sinks(nodes: &[String], edges: &[(String, String)]) -> Vec<String> {
+    let reversed = edges
+        .iter()
+        .map(|(from, to)| (to.clone(), from.clone()))
+        .collect::<Vec<_>>();
+    sources(nodes, &reversed)
+}
+
+/// nodes, which can't be reached from `from` along directed `edges`, `from` excluded
+pub fn unreachable(nodes: &[String], edges: &[(String, String)], from: &str) -> Vec<String> {
+    let adjacent = adjacency(edges);
+    let mut queue = VecDeque::from([from]);
+    let mut visited = HashSet::from([from]);
+    while let Some(node) = queue.pop_front() {
+        for &next in adjacent.get(node).into_iter().flatten() {
+            if visited.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+    nodes
+        .iter()
+        .filter(|node| !visited.contains(node.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// strongly connected components, which contain at least one cycle, members sorted;
+/// largest first
+pub fn cyclic_components(nodes: &[String], edges: &[(String, String)]) -> Vec<Vec<String>> {
+    let self_loops: HashSet<&str> = edges
+        .iter()
+        .filter(|(from, to)| from == to)
+        .map(|(from, _to)| from.as_str())
+        .collect();
+    let mut result = strongly_connected_components(nodes, edges)
+        .into_iter()
+        .filter(|component| component.len() > 1 || self_loops.contains(component[0].as_str()))
+        .map(|mut component| {
+            component.sort();
+            component
+        })
+        .collect::<Vec<_>>();
+    result.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+    result
+}
+
+/// cycles along directed `edges`, which visit no node twice, each starting at its least
+/// node, shortest first; no more than `limit` of them, `true` tells that some were left out
+/// (Johnson's algorithm: nodes, which can't lead back to start, stay blocked until a node
+/// after them gets into a cycle, so that search time is bounded by number of cycles found)
+pub fn elementary_cycles(
+    nodes: &[String],
+    edges: &[(String, String)],
+    limit: usize,
+) -> (Vec<Vec<String>>, bool) {
+    let adjacent = adjacency(edges);
+    let mut result = vec![];
+    for component in cyclic_components(nodes, edges) {
+        for (index, start) in component.iter().enumerate() {
+            // cycles through less nodes of component have been found already
+            let allowed: HashSet<&str> = component[index..].iter().map(String::as_str).collect();
+            let mut blocked = HashSet::from([start.as_str()]);
+            // nodes to unblock along with each one, once it gets unblocked
+            let mut blocked_by: HashMap<&str, Vec<&str>> = HashMap::new();
+            let mut path = vec![start.as_str()];
+            let mut children = vec![0];
+            // whether a cycle has been found through each node of path
+            let mut found = vec![false];
+            while let Some(child) = children.pop() {
+                let node = path[path.len() - 1];
+                let next = adjacent.get(node).and_then(|next| next.get(child)).copied();
+                match next {
+                    Some(next) => {
+                        children.push(child + 1);
+                        if next == start {
+                            if result.len() == limit {
+                                sort_cycles(&mut result);
+                                return (result, true);
+                            }
+                            result.push(path.iter().map(|node| node.to_string()).collect());
+                            *found.last_mut().unwrap() = true;
+                        } else if allowed.contains(next) && blocked.insert(next) {
+                            path.push(next);
+                            children.push(0);
+                            found.push(false);
+                        }
+                    }
+                    None => {
+                        path.pop();
+                        let in_cycle = found.pop().unwrap();
+                        if in_cycle {
+                            let mut unblocked = vec![node];
+                            while let Some(node) = unblocked.pop() {
+                                if blocked.remove(node) {
+                                    unblocked.extend(blocked_by.remove(node).unwrap_or_default());
+                                }
+                            }
+                        } else {
+                            for &next in adjacent.get(node).into_iter().flatten() {
+                                let by = blocked_by.entry(next).or_default();
+                                if !by.contains(&node) {
+                                    by.push(node);
+                                }
+                            }
+                        }
+                        if let Some(parent) = found.last_mut() {
+                            *parent |= in_cycle;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    sort_cycles(&mut result);
+    (result, false)
+}
+
+fn sort_cycles(cycles: &mut [Vec<String>]) {
+    cycles.sort_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+}
+
+/// number of edges on the longest of shortest paths from `from` to other nodes, reachable
+/// along directed `edges`
+pub fn max_depth(edges: &[(String, String)], from: &str) -> usize {
+    let adjacent = adjacency(edges);
+    let mut queue = VecDeque::from([(from, 0)]);
+    let mut visited = HashSet::from([from]);
+    let mut result = 0;
+    while let Some((node, depth)) = queue.pop_front() {
+        result = result.max(depth);
+        for &next in adjacent.get(node).into_iter().flatten() {
+            if visited.insert(next) {
+                queue.push_back((next, depth + 1));
+            }
+        }
+    }
+    result
+}
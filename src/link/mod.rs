@@ -1,7 +1,6 @@
 use std::{fmt::Display, io, path::PathBuf};
 
 use colored::Colorize;
-use comrak::nodes::Sourcepos;
 use duct::cmd;
 use regex::Regex;
 use skim::AnsiString;
@@ -15,6 +14,8 @@ use crate::{
 mod parse;
 mod skim_item;
 
+pub use parse::ParsedLink;
+
 #[derive(Clone, Debug)]
 pub enum Destination {
     Url(String),
@@ -35,6 +36,30 @@ pub enum Destination {
     },
 }
 
+impl Destination {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Url(..) => "url",
+            Self::File { .. } => "file",
+            Self::Dir { .. } => "dir",
+            Self::Broken(..) => "broken",
+            Self::CodeBlock { .. } => "code_block",
+            Self::FileLine { .. } => "file_line",
+        }
+    }
+
+    pub fn target(&self) -> String {
+        match self {
+            Self::Url(url) => url.clone(),
+            Self::File { file } => file.display().to_string(),
+            Self::Dir { dir } => dir.display().to_string(),
+            Self::Broken(broken, _line) => broken.display().to_string(),
+            Self::CodeBlock { syntax_label, .. } => syntax_label.clone(),
+            Self::FileLine { file, line_number } => format!("{}:{}", file.display(), line_number),
+        }
+    }
+}
+
 impl Open for Link {
     fn open(&self, mut cfg: OpenCfg) -> io::Result<Option<std::process::ExitStatus>> {
         match &self.link {
@@ -249,7 +274,7 @@ impl Link {
         description: String,
         code_block: String,
         syntax_label: String,
-        source_position: Sourcepos,
+        start: EditorPosition,
         color_scheme: ColorScheme,
     ) -> Self {
         Self {
@@ -261,10 +286,7 @@ impl Link {
             },
             preview_item: None,
             display_item: None,
-            start: EditorPosition {
-                line: source_position.start.line,
-                column: source_position.start.column,
-            },
+            start,
             containing_file_name: parent_note,
             color_scheme,
         }
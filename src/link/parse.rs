@@ -1,7 +1,9 @@
-use std::{fs, path::PathBuf};
+use serde::{Deserialize, Serialize};
 
 use crate::config::color::ColorScheme;
-use crate::lines::find_position;
+use crate::database::DatabaseAsyncHandle;
+use crate::lines::{find_position, EditorPosition};
+use crate::parse_cache::ParsedBody;
 use crate::{config::SurfParsing, note::Note};
 
 use super::Link;
@@ -9,6 +11,22 @@ use super::Link;
 use comrak::nodes::{AstNode, NodeValue};
 use comrak::{parse_document, Arena, ComrakOptions};
 
+/// link, as found in note's body, before its destination is resolved against file system
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ParsedLink {
+    Reference {
+        description: String,
+        destination: String,
+        start: EditorPosition,
+    },
+    CodeBlock {
+        description: String,
+        code_block: String,
+        syntax_label: String,
+        start: EditorPosition,
+    },
+}
+
 fn iter_nodes<'a, F>(node: &'a AstNode<'a>, counter: &mut usize, f: &mut F)
 where
     F: FnMut(&'a AstNode<'a>, &mut usize),
@@ -19,82 +37,23 @@ where
     }
 }
 
-impl
-    From<(
-        regex::Captures<'_>,
-        PathBuf,
-        String,
-        &'_ regex::Regex,
-        &str,
-        ColorScheme,
-        &'_ regex::Regex,
-    )> for Link
-{
-    fn from(
-        value: (
-            regex::Captures<'_>,
-            PathBuf,
-            String,
-            &'_ regex::Regex,
-            &str,
-            ColorScheme,
-            &'_ regex::Regex,
-        ),
-    ) -> Self {
-        let captures = value.0;
-        let start = captures.name("description").unwrap();
-        let start = start.start();
-        let start = find_position(value.4, start);
-        Link::new(
-            captures["description"].to_string(),
-            captures["url"].to_string(),
-            value.1,
-            value.2,
-            value.3,
-            value.6,
-            start,
-            value.5,
-        )
-    }
-}
-
-#[allow(clippy::ptr_arg)]
-impl Link {
-    fn reference_link_parse(
-        note: &Note,
-        result: &mut Vec<Link>,
-        surf: &SurfParsing,
-        file_path: &PathBuf,
-        file_content: &str,
-        color_scheme: ColorScheme,
-    ) {
-        for link in surf
+impl ParsedLink {
+    fn reference_link_parse(result: &mut Vec<Self>, surf: &SurfParsing, file_content: &str) {
+        for captures in surf
             .markdown_reference_link_regex
             .0
             .captures_iter(file_content)
         {
-            result.push(
-                (
-                    link,
-                    file_path.clone(),
-                    note.name(),
-                    &surf.url_regex.0,
-                    file_content,
-                    color_scheme,
-                    &surf.has_line_regex.0,
-                )
-                    .into(),
-            );
+            let start = captures.name("description").unwrap().start();
+            result.push(Self::Reference {
+                description: captures["description"].to_string(),
+                destination: captures["url"].to_string(),
+                start: find_position(file_content, start),
+            });
         }
     }
 
-    fn ast_parse_code_blocks(
-        note: &Note,
-        result: &mut Vec<Link>,
-        file_path: &PathBuf,
-        file_content: &str,
-        color_scheme: ColorScheme,
-    ) {
+    fn ast_parse_code_blocks(result: &mut Vec<Self>, file_content: &str) {
         let arena = Arena::new();
 
         let root = parse_document(&arena, file_content, &ComrakOptions::default());
@@ -110,41 +69,82 @@ impl Link {
                 } else {
                     format!("snippet[{}]", counter)
                 };
-                result.push(Link::new_code_block(
-                    file_path.clone(),
-                    note.name(),
+                result.push(Self::CodeBlock {
                     description,
                     code_block,
                     syntax_label,
-                    source_position,
-                    color_scheme,
-                ));
+                    start: EditorPosition {
+                        line: source_position.start.line,
+                        column: source_position.start.column,
+                    },
+                });
                 *counter += 1;
             }
         });
     }
-    pub fn parse(
+
+    pub fn parse(surf: &SurfParsing, file_content: &str) -> Vec<Self> {
+        let mut result = vec![];
+        Self::reference_link_parse(&mut result, surf, file_content);
+        Self::ast_parse_code_blocks(&mut result, file_content);
+        result
+    }
+}
+
+impl Link {
+    /// links of `note`, with destinations of `parsed` resolved
+    pub fn resolve(
         note: &Note,
+        parsed: &[ParsedLink],
         surf: &SurfParsing,
         color_scheme: ColorScheme,
-    ) -> std::io::Result<Vec<Link>> {
-        if let Some(file_path) = note.file_path() {
-            let mut result = vec![];
-            let file_content = fs::read_to_string(file_path)?;
-
-            Self::reference_link_parse(
-                note,
-                &mut result,
-                surf,
-                file_path,
-                &file_content,
-                color_scheme,
-            );
-            Self::ast_parse_code_blocks(note, &mut result, file_path, &file_content, color_scheme);
+    ) -> Vec<Link> {
+        let Some(file_path) = note.file_path() else {
+            return vec![];
+        };
+        parsed
+            .iter()
+            .cloned()
+            .map(|link| match link {
+                ParsedLink::Reference {
+                    description,
+                    destination,
+                    start,
+                } => Link::new(
+                    description,
+                    destination,
+                    file_path.clone(),
+                    note.name(),
+                    &surf.url_regex.0,
+                    &surf.has_line_regex.0,
+                    start,
+                    color_scheme,
+                ),
+                ParsedLink::CodeBlock {
+                    description,
+                    code_block,
+                    syntax_label,
+                    start,
+                } => Link::new_code_block(
+                    file_path.clone(),
+                    note.name(),
+                    description,
+                    code_block,
+                    syntax_label,
+                    start,
+                    color_scheme,
+                ),
+            })
+            .collect()
+    }
 
-            Ok(result)
-        } else {
-            Ok(vec![])
-        }
+    pub async fn parse(
+        note: &Note,
+        surf: &SurfParsing,
+        color_scheme: ColorScheme,
+        db: &DatabaseAsyncHandle,
+    ) -> sqlx::Result<Vec<Link>> {
+        let body = ParsedBody::of(note, surf, db).await?;
+        Ok(Self::resolve(note, &body.links, surf, color_scheme))
     }
 }
@@ -72,7 +72,7 @@ impl super::Link {
 }
 
 impl SkimItem for super::Link {
-    fn text(&self) -> Cow<str> {
+    fn text(&self) -> Cow<'_, str> {
         Cow::Owned(format!("{}", self))
     }
     fn display<'a>(&'a self, _context: DisplayContext<'a>) -> AnsiString<'a> {
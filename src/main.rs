@@ -8,7 +8,7 @@ use config::{color::Color, Open as OpenCfg};
 use highlight::static_markdown_syntax;
 use std::{
     env, io,
-    path::PathBuf,
+    path::{Path, PathBuf},
     process::{exit, ExitStatus},
 };
 use syntect::highlighting::{Theme, ThemeSet};
@@ -17,15 +17,19 @@ mod commands;
 mod config;
 mod database;
 mod external_commands;
+mod front_matter;
+mod graph;
 mod highlight;
 mod lines;
 mod link;
 mod note;
+mod parse_cache;
 mod print;
 mod skim;
+mod stats;
 mod task_item;
-
-pub(crate) use database::Sqlite;
+#[cfg(test)]
+mod tests;
 
 trait Open {
     fn open(&self, cfg: OpenCfg) -> io::Result<Option<ExitStatus>>;
@@ -65,7 +69,7 @@ async fn main() {
         .subcommand(clap::command!("debug-cfg").about("print Debug representtion of config"))
         .subcommand(
             clap::command!("init")
-                .about("`initialize` .sqlite database in notes dir, specified by config"),
+                .about("`initialize` storage (.sqlite database or .mds dir) in notes dir, specified by config"),
         )
         .subcommand(
             clap::command!("note")
@@ -88,11 +92,43 @@ async fn main() {
                 ),
         )
         .subcommand(clap::command!("select").about("select note S, i.e. print its name to stdout"))
+        .subcommand(
+            clap::command!("search")
+                .visible_alias("sr")
+                .about("full-text search over notes' bodies, print names of matching notes ranked by relevance")
+                .arg(
+                    clap::arg!([query] "fts5 query, e.g. `borrow AND checker` or `tokio*`")
+                        .value_parser(clap::value_parser!(String))
+                        .required(true),
+                ),
+        )
         .subcommand(
             clap::command!("link")
                 .visible_alias("l")
                 .about("link 2 notes A -> B, selected twice in skim interface"),
         )
+        .subcommand(
+            clap::command!("sync-links")
+                .about("create links from `[[wikilinks]]` in notes' bodies, report stale ones")
+                .arg(
+                    clap::arg!(-n --name <NOTE_NAME> "sync only this note")
+                        .value_parser(clap::value_parser!(String))
+                        .required(false),
+                )
+                .arg(
+                    clap::arg!(--prune "remove links, synced earlier, whose wikilinks are gone")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            clap::command!("merge")
+                .about("merge note S into note T, both selected in skim interface"),
+        )
+        .subcommand(
+            clap::command!("split").about(
+                "split sections under headings of note N, selected in skim interface, into linked notes",
+            ),
+        )
         .subcommand(
             clap::command!("unlink")
                 .visible_alias("ul")
@@ -118,6 +154,108 @@ async fn main() {
                         .required(false),
                 ),
         )
+        .subcommand(
+            clap::command!("export")
+                .about("export graph of notes and links as Graphviz DOT, Mermaid or JSON")
+                .arg(
+                    clap::arg!(-f --format <FORMAT> "output format")
+                        .value_parser(["dot", "mermaid", "json"])
+                        .default_value("dot"),
+                )
+                .arg(
+                    clap::arg!(-r --root <NOTE_NAME> "export only notes reachable from this note")
+                        .value_parser(clap::value_parser!(String))
+                        .required(false),
+                )
+                .arg(
+                    clap::arg!(--up "follow links backwards from root")
+                        .action(ArgAction::SetTrue)
+                        .requires("root"),
+                )
+                .arg(
+                    clap::arg!(-d --depth <DEPTH> "max number of links away from root")
+                        .value_parser(clap::value_parser!(usize))
+                        .required(false)
+                        .requires("root"),
+                )
+                .arg(
+                    clap::arg!(--destinations "include links and code blocks parsed from bodies")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    clap::arg!(--tasks "include counts of task items parsed from bodies")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            clap::command!("stats")
+                .about("print statistics of notes, links, their destinations and task items")
+                .arg(
+                    clap::arg!(-f --format <FORMAT> "output format")
+                        .value_parser(["table", "json"])
+                        .default_value("table"),
+                )
+                .arg(
+                    clap::arg!(-r --root <NOTE_NAME> "count only notes reachable from this note")
+                        .value_parser(clap::value_parser!(String))
+                        .required(false),
+                )
+                .arg(
+                    clap::arg!(--up "follow links backwards from root")
+                        .action(ArgAction::SetTrue)
+                        .requires("root"),
+                ),
+        )
+        .subcommand(
+            clap::command!("path")
+                .about("print shortest path of links between two notes and explore notes on it")
+                .arg(
+                    clap::arg!(<FROM> "name of note, where path starts")
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(
+                    clap::arg!(<TO> "name of note, where path ends")
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(
+                    clap::arg!(-u --undirected "follow links in both directions")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    clap::arg!(-a --all <MAX_LEN> "list all paths, which visit no note twice, up to MAX_LEN links")
+                        .value_parser(clap::value_parser!(usize))
+                        .required(false),
+                ),
+        )
+        .subcommand(
+            clap::command!("orphans")
+                .about("list notes, which no other note links to, and explore them"),
+        )
+        .subcommand(
+            clap::command!("leaves").about("list notes, which link to no other note, and explore them"),
+        )
+        .subcommand(
+            clap::command!("unreachable")
+                .about("list notes, which can't be reached by following links from a note, and explore them")
+                .arg(
+                    clap::arg!(-f --from <NOTE_NAME> "name of note, where links are followed from")
+                        .value_parser(clap::value_parser!(String))
+                        .default_value("root"),
+                ),
+        )
+        .subcommand(
+            clap::command!("cycles")
+                .about("list strongly connected components or cycles of notes and links, and explore notes in them")
+                .arg(
+                    clap::arg!(-e --elementary "list every cycle, which visits no note twice, instead of components")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    clap::arg!(-l --label <LABEL> "follow only links with this label")
+                        .value_parser(clap::value_parser!(String))
+                        .required(false),
+                ),
+        )
         .subcommand(
             clap::command!("explore")
                 .arg(
@@ -141,7 +279,154 @@ async fn main() {
         .subcommand(
             clap::command!("stack")
                 .visible_alias("st")
-                .about("browse GLOBAL stack of notes"),
+                .about("browse a stack of notes, GLOBAL by default")
+                .arg(
+                    clap::arg!(-n --name <STACK_NAME> "stack name")
+                        .value_parser(clap::value_parser!(String))
+                        .required(false),
+                ),
+        )
+        .subcommand(
+            clap::command!("stacks")
+                .about("manage named stacks of notes")
+                .subcommand_required(true)
+                .subcommand(clap::command!("list").about("list names of all stacks"))
+                .subcommand(
+                    clap::command!("create").about("create a new empty stack").arg(
+                        clap::arg!([name] "stack name")
+                            .value_parser(clap::value_parser!(String))
+                            .required(true),
+                    ),
+                )
+                .subcommand(
+                    clap::command!("rename")
+                        .about("rename a stack")
+                        .arg(
+                            clap::arg!([name] "current stack name")
+                                .value_parser(clap::value_parser!(String))
+                                .required(true),
+                        )
+                        .arg(
+                            clap::arg!([new_name] "new stack name")
+                                .value_parser(clap::value_parser!(String))
+                                .required(true),
+                        ),
+                )
+                .subcommand(
+                    clap::command!("delete")
+                        .about("delete a stack, notes in it are kept")
+                        .arg(
+                            clap::arg!([name] "stack name")
+                                .value_parser(clap::value_parser!(String))
+                                .required(true),
+                        ),
+                ),
+        )
+        .subcommand(
+            clap::command!("import")
+                .about("register existing markdown files in a directory as notes")
+                .arg(
+                    clap::arg!(<dir> "directory to import, searched recursively")
+                        .value_parser(clap::value_parser!(PathBuf)),
+                )
+                .arg(
+                    clap::arg!(-l --links "link notes, referencing each other by relative `[..](other.md)` links")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    clap::arg!(--"dry-run" "print what would be imported without changing anything")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    clap::arg!(--"on-conflict" <ACTION> "what to do with a file, whose name is already taken")
+                        .value_parser(["suffix", "skip"])
+                        .default_value("suffix"),
+                ),
+        )
+        .subcommand(
+            clap::command!("doctor")
+                .about("check consistency of database and notes' work dir, offer repairs")
+                .arg(
+                    clap::arg!(--fix "apply all available repairs without asking")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            clap::command!("dump")
+                .about("dump notes, links and stacks as versioned JSON, to stdout by default")
+                .arg(
+                    clap::arg!(-o --output <FILE> "file to write dump to")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .required(false),
+                ),
+        )
+        .subcommand(
+            clap::command!("restore")
+                .about("rebuild an empty database from JSON, produced by `dump`")
+                .arg(
+                    clap::arg!([file] "dump file")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            clap::command!("trash")
+                .about("manage removed notes, kept in trash along with their links and stacks")
+                .subcommand_required(true)
+                .subcommand(clap::command!("list").about("list removed notes, latest first"))
+                .subcommand(
+                    clap::command!("restore")
+                        .about("restore a removed note, selected in prompt if ID is omitted")
+                        .arg(
+                            clap::arg!([id] "id of trash entry")
+                                .value_parser(clap::value_parser!(i64))
+                                .required(false),
+                        ),
+                )
+                .subcommand(
+                    clap::command!("purge")
+                        .about("delete removed notes for good, all of them if ID is omitted")
+                        .arg(
+                            clap::arg!([id] "id of trash entry")
+                                .value_parser(clap::value_parser!(i64))
+                                .required(false),
+                        ),
+                ),
+        )
+        .subcommand(
+            clap::command!("prop")
+                .about("manage key=value properties of notes")
+                .subcommand_required(true)
+                .subcommand(
+                    clap::command!("set")
+                        .about("set property KEY of note to VALUE")
+                        .arg(clap::arg!(<NOTE> "note name or alias"))
+                        .arg(clap::arg!(<KEY> "property key"))
+                        .arg(clap::arg!(<VALUE> "property value")),
+                )
+                .subcommand(
+                    clap::command!("get")
+                        .about("print property KEY of note, or all of its properties")
+                        .arg(clap::arg!(<NOTE> "note name or alias"))
+                        .arg(clap::arg!([KEY] "property key")),
+                )
+                .subcommand(
+                    clap::command!("unset")
+                        .about("remove property KEY of note")
+                        .arg(clap::arg!(<NOTE> "note name or alias"))
+                        .arg(clap::arg!(<KEY> "property key")),
+                ),
+        )
+        .subcommand(clap::command!("undo").about("undo last change of notes, links or stacks"))
+        .subcommand(clap::command!("redo").about("redo last undone change"))
+        .subcommand(
+            clap::command!("log")
+                .about("print journal of changes of notes, links and stacks, latest first")
+                .arg(
+                    clap::arg!(-n --number <NUMBER> "number of entries to print")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("20"),
+                ),
         )
         .subcommand(clap::command!("checkmark").visible_alias("k").about(
             "checkmark, toggle state TODO/DONE of multiple task items, found in a selected note C",
@@ -172,6 +457,8 @@ async fn body(matches: &ArgMatches) -> anyhow::Result<String> {
     };
     let loaded_theme = load_theme(config.color.clone());
 
+    // paths in arguments are relative to where `mds` was invoked from
+    let invocation_dir = env::current_dir()?;
     if let Err(err) = env::set_current_dir(&config.work_dir.0) {
         eprintln!(
             "{}",
@@ -180,18 +467,24 @@ async fn body(matches: &ArgMatches) -> anyhow::Result<String> {
         return Err(err)?;
     }
 
-    let db_dir = PathBuf::from("./.sqlite");
     let md_static = static_markdown_syntax(loaded_theme);
-    let surf_bindings = config.keymap.surf.clone().try_into()?;
-    let checkmark_bindings = config.keymap.checkmark.clone().try_into()?;
-    let stack_bindings = config.keymap.stack.clone().try_into()?;
-    let explore_bindings = config.keymap.explore.clone().try_into()?;
+    let surf_bindings: config::keymap::surf::Bindings = config.keymap.surf.clone().into();
+    let checkmark_bindings: config::keymap::checkmark::Bindings =
+        config.keymap.checkmark.clone().into();
+    let stack_bindings: config::keymap::stack::Bindings = config.keymap.stack.clone().into();
+    let explore_bindings: config::keymap::explore::Bindings = config.keymap.explore.clone().into();
 
     let result = match matches.subcommand() {
-        Some(("init", _matches)) => commands::init_db::exec(db_dir).await,
+        Some(("init", _matches)) => commands::init_db::exec(config.storage).await,
         Some(("debug-cfg", _matches)) => commands::debug_cfg::exec(config),
+        Some(("restore", matches)) => {
+            let file = matches
+                .get_one::<PathBuf>("file")
+                .ok_or(anyhow::anyhow!("empty file"))?;
+            commands::dump::restore(&invocation_dir.join(file), config.storage).await
+        }
         Some((subcommand, matches)) => {
-            let db = match Sqlite::new(false, db_dir).await {
+            let db = match database::open(config.storage, false).await {
                 Ok(db) => db,
                 Err(err) => return Err(err.into()),
             };
@@ -266,6 +559,26 @@ async fn body(matches: &ArgMatches) -> anyhow::Result<String> {
                     )
                     .await
                 }
+                "merge" => {
+                    commands::merge::exec(
+                        db,
+                        config.external_commands,
+                        config.surf_parsing,
+                        md_static,
+                        config.color.elements,
+                    )
+                    .await
+                }
+                "split" => {
+                    commands::split::exec(
+                        db,
+                        config.external_commands,
+                        config.surf_parsing,
+                        md_static,
+                        config.color.elements,
+                    )
+                    .await
+                }
                 "rename" => {
                     commands::rename::exec(
                         db,
@@ -288,6 +601,122 @@ async fn body(matches: &ArgMatches) -> anyhow::Result<String> {
                     )
                     .await
                 }
+                "export" => {
+                    let format = match matches.get_one::<String>("format").map(String::as_str) {
+                        Some("mermaid") => commands::export::Format::Mermaid,
+                        Some("json") => commands::export::Format::Json,
+                        _ => commands::export::Format::Dot,
+                    };
+                    let details = commands::export::Details {
+                        destinations: matches.get_flag("destinations"),
+                        tasks: matches.get_flag("tasks"),
+                    };
+                    commands::export::exec(
+                        db,
+                        format,
+                        matches.get_one::<String>("root").cloned(),
+                        matches.get_flag("up"),
+                        matches.get_one::<usize>("depth").cloned(),
+                        details,
+                        &config.surf_parsing,
+                        md_static,
+                        config.color.elements,
+                    )
+                    .await
+                }
+                "stats" => {
+                    let format = match matches.get_one::<String>("format").map(String::as_str) {
+                        Some("json") => commands::stats::Format::Json,
+                        _ => commands::stats::Format::Table,
+                    };
+                    commands::stats::exec(
+                        db,
+                        format,
+                        matches.get_one::<String>("root").cloned(),
+                        matches.get_flag("up"),
+                        &config.surf_parsing,
+                        md_static,
+                        config.color.elements,
+                    )
+                    .await
+                }
+                "path" => {
+                    let from = matches
+                        .get_one::<String>("FROM")
+                        .ok_or(anyhow::anyhow!("empty from"))?;
+                    let to = matches
+                        .get_one::<String>("TO")
+                        .ok_or(anyhow::anyhow!("empty to"))?;
+                    let query = commands::path::PathQuery {
+                        undirected: matches.get_flag("undirected"),
+                        all_up_to: matches.get_one::<usize>("all").cloned(),
+                    };
+                    commands::path::exec(
+                        db,
+                        from,
+                        to,
+                        query,
+                        config.external_commands,
+                        config.surf_parsing,
+                        md_static,
+                        config.color.elements,
+                        surf_bindings,
+                        checkmark_bindings,
+                        stack_bindings,
+                        explore_bindings,
+                    )
+                    .await
+                }
+                cmd @ "orphans" | cmd @ "leaves" | cmd @ "unreachable" => {
+                    let report = match cmd {
+                        "orphans" => commands::hygiene::Report::Orphans,
+                        "leaves" => commands::hygiene::Report::Leaves,
+                        _ => commands::hygiene::Report::Unreachable(
+                            matches
+                                .get_one::<String>("from")
+                                .ok_or(anyhow::anyhow!("empty from"))?
+                                .clone(),
+                        ),
+                    };
+                    commands::hygiene::exec(
+                        db,
+                        report,
+                        config.external_commands,
+                        config.surf_parsing,
+                        md_static,
+                        config.color.elements,
+                        surf_bindings,
+                        checkmark_bindings,
+                        stack_bindings,
+                        explore_bindings,
+                    )
+                    .await
+                }
+                "cycles" => {
+                    let query = commands::cycles::CyclesQuery {
+                        elementary: matches.get_flag("elementary"),
+                        label: matches.get_one::<String>("label").cloned(),
+                    };
+                    commands::cycles::exec(
+                        db,
+                        query,
+                        config.external_commands,
+                        config.surf_parsing,
+                        md_static,
+                        config.color.elements,
+                        surf_bindings,
+                        checkmark_bindings,
+                        stack_bindings,
+                        explore_bindings,
+                    )
+                    .await
+                }
+                "search" => {
+                    let query = matches
+                        .get_one::<String>("query")
+                        .ok_or(anyhow::anyhow!("empty query"))?;
+                    commands::search::exec(query, db, md_static, config.color.elements).await
+                }
                 "select" => {
                     commands::select::exec(
                         db,
@@ -311,8 +740,13 @@ async fn body(matches: &ArgMatches) -> anyhow::Result<String> {
                     .await
                 }
                 "stack" => {
+                    let stack = matches
+                        .get_one::<String>("name")
+                        .cloned()
+                        .unwrap_or(commands::explore::GLOBAL_STACK.to_string());
                     commands::stack::exec(
                         db,
+                        stack,
                         vec![],
                         config.external_commands,
                         config.surf_parsing,
@@ -322,6 +756,141 @@ async fn body(matches: &ArgMatches) -> anyhow::Result<String> {
                     )
                     .await
                 }
+                "stacks" => match matches.subcommand() {
+                    Some(("list", _matches)) => commands::stacks::list(db).await,
+                    Some(("create", matches)) => {
+                        let name = matches
+                            .get_one::<String>("name")
+                            .ok_or(anyhow::anyhow!("empty name"))?;
+                        commands::stacks::create(db, name).await
+                    }
+                    Some(("rename", matches)) => {
+                        let name = matches
+                            .get_one::<String>("name")
+                            .ok_or(anyhow::anyhow!("empty name"))?;
+                        let new_name = matches
+                            .get_one::<String>("new_name")
+                            .ok_or(anyhow::anyhow!("empty new name"))?;
+                        commands::stacks::rename(db, name, new_name).await
+                    }
+                    Some(("delete", matches)) => {
+                        let name = matches
+                            .get_one::<String>("name")
+                            .ok_or(anyhow::anyhow!("empty name"))?;
+                        commands::stacks::delete(db, name).await
+                    }
+                    _ => unreachable!("clap should ensure we don't get here"),
+                },
+                "import" => {
+                    let dir = matches
+                        .get_one::<PathBuf>("dir")
+                        .ok_or(anyhow::anyhow!("empty dir"))?;
+                    let dir = invocation_dir.join(dir);
+                    let on_conflict =
+                        match matches.get_one::<String>("on-conflict").map(String::as_str) {
+                            Some("skip") => commands::import::OnConflict::Skip,
+                            _ => commands::import::OnConflict::Suffix,
+                        };
+                    commands::import::exec(
+                        &dir,
+                        db,
+                        &config.surf_parsing,
+                        matches.get_flag("links"),
+                        matches.get_flag("dry-run"),
+                        on_conflict,
+                        md_static,
+                        config.color.elements,
+                    )
+                    .await
+                }
+                "dump" => {
+                    let output = matches
+                        .get_one::<PathBuf>("output")
+                        .map(|output| invocation_dir.join(output));
+                    commands::dump::dump(db, output).await
+                }
+                "sync-links" => {
+                    commands::sync_links::exec(
+                        db,
+                        &config.surf_parsing,
+                        matches.get_one::<String>("name").cloned(),
+                        matches.get_flag("prune"),
+                        md_static,
+                        config.color.elements,
+                    )
+                    .await
+                }
+                "doctor" => {
+                    let fix = matches.get_flag("fix");
+                    commands::doctor::exec(
+                        db,
+                        fix,
+                        Path::new("."),
+                        md_static,
+                        config.color.elements,
+                    )
+                    .await
+                }
+                "trash" => match matches.subcommand() {
+                    Some(("list", _matches)) => commands::trash::list(db).await,
+                    Some(("restore", matches)) => {
+                        let id = matches.get_one::<i64>("id").cloned();
+                        commands::trash::restore(db, id, md_static, config.color.elements).await
+                    }
+                    Some(("purge", matches)) => {
+                        let id = matches.get_one::<i64>("id").cloned();
+                        commands::trash::purge(db, id).await
+                    }
+                    _ => unreachable!("clap should ensure we don't get here"),
+                },
+                "prop" => {
+                    let color_scheme = config.color.elements;
+                    let note_key =
+                        |matches: &ArgMatches| -> anyhow::Result<(String, Option<String>)> {
+                            let note = matches
+                                .get_one::<String>("NOTE")
+                                .ok_or(anyhow::anyhow!("empty note"))?;
+                            Ok((note.clone(), matches.get_one::<String>("KEY").cloned()))
+                        };
+                    match matches.subcommand() {
+                        Some(("get", matches)) => {
+                            let (note, key) = note_key(matches)?;
+                            commands::prop::get(db, &note, key.as_deref(), md_static, color_scheme)
+                                .await
+                        }
+                        Some(("set", matches)) => {
+                            let (note, key) = note_key(matches)?;
+                            let key = key.ok_or(anyhow::anyhow!("empty key"))?;
+                            let value = matches
+                                .get_one::<String>("VALUE")
+                                .ok_or(anyhow::anyhow!("empty value"))?;
+                            commands::prop::set(
+                                db,
+                                &note,
+                                &key,
+                                Some(value),
+                                md_static,
+                                color_scheme,
+                            )
+                            .await
+                        }
+                        Some(("unset", matches)) => {
+                            let (note, key) = note_key(matches)?;
+                            let key = key.ok_or(anyhow::anyhow!("empty key"))?;
+                            commands::prop::set(db, &note, &key, None, md_static, color_scheme)
+                                .await
+                        }
+                        _ => unreachable!("clap should ensure we don't get here"),
+                    }
+                }
+                "undo" => commands::journal::undo(db).await,
+                "redo" => commands::journal::redo(db).await,
+                "log" => {
+                    let limit = *matches
+                        .get_one::<usize>("number")
+                        .ok_or(anyhow::anyhow!("empty number"))?;
+                    commands::journal::log(db, limit).await
+                }
                 _ => unreachable!("clap should ensure we don't get here"),
             }
         }
@@ -1,16 +1,19 @@
-use std::{collections::HashSet, fmt::Display};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
 
-use async_recursion::async_recursion;
-use bidir_termtree::{Down, Tree, Up};
+use bidir_termtree::{Direction, Down, Tree, Up};
 use colored::Colorize;
 
 use sqlx::Result as SqlxResult;
 
 use crate::{
-    config::{color::ColorScheme, ExternalCommands, SurfParsing},
-    database::{Database, SqliteAsyncHandle},
+    config::{color::ColorScheme, SurfParsing},
+    database::{DatabaseAsyncHandle, Subgraph},
     highlight::MarkdownStatic,
     link::Link,
+    parse_cache::ParsedBody,
 };
 
 use super::Note;
@@ -22,6 +25,7 @@ pub enum NoteLinkTerm {
     Link(Link),
     LinkHint(bool, usize, ColorScheme),
     Cycle(String, ColorScheme),
+    Labelled(Box<NoteLinkTerm>, String, ColorScheme),
 }
 
 impl Display for NoteLinkTerm {
@@ -56,91 +60,101 @@ impl Display for NoteLinkTerm {
                 let c = color.links.cycle;
                 write!(f, "⟳ {}", cycle.truecolor(c.0.r, c.0.g, c.0.b))
             }
+            Self::Labelled(term, label, color) => {
+                let c = color.links.label;
+                let label = format!("[{}]", label);
+                write!(f, "{} {}", label.truecolor(c.0.r, c.0.g, c.0.b), term)
+            }
+        }
+    }
+}
+
+impl NoteLinkTerm {
+    fn labelled(self, label: Option<String>, color_scheme: ColorScheme) -> Self {
+        match label {
+            Some(label) => Self::Labelled(Box::new(self), label, color_scheme),
+            None => self,
         }
     }
 }
 
 impl Note {
     #[allow(clippy::too_many_arguments)]
-    #[async_recursion]
     pub async fn construct_link_term_tree(
         &self,
         level: usize,
         nested_threshold: usize,
-        mut all_reachable: HashSet<Note>,
-        external_commands: ExternalCommands,
+        all_reachable: HashSet<Note>,
         surf_parsing: SurfParsing,
-        db: SqliteAsyncHandle,
+        db: DatabaseAsyncHandle,
         md_static: MarkdownStatic,
         color_scheme: ColorScheme,
     ) -> SqlxResult<(Tree<NoteLinkTerm, Down>, HashSet<Note>)> {
-        let straight = true;
-        let mut tree = Tree::new(NoteLinkTerm::Note(self.clone()));
-        all_reachable.insert(self.clone());
-
-        let links = Link::parse(self, &surf_parsing, color_scheme)?;
-
-        if !links.is_empty() {
-            if level >= nested_threshold {
-                tree.push(NoteLinkTerm::LinkHint(true, links.len(), color_scheme));
-            } else {
-                let hint = NoteLinkTerm::LinkHint(false, links.len(), color_scheme);
-                let mut hint_tree = Tree::new(hint);
-                for link in links {
-                    hint_tree.push(NoteLinkTerm::Link(link));
-                }
-                tree.push(hint_tree);
-            }
-        }
-
-        let forward_links = db
+        let subgraph = db
             .lock()
             .await
-            .find_links_from(&self.name(), md_static, color_scheme, straight)
+            .reachable_subgraph(&self.name(), md_static, color_scheme, true, None)
             .await?;
-
-        for next in forward_links.into_iter().rev() {
-            if all_reachable.contains(&next) {
-                tree.push(Tree::new(NoteLinkTerm::Cycle(next.name(), color_scheme)));
-            } else {
-                let (next_tree, roundtrip_reachable) = next
-                    .construct_link_term_tree(
-                        level + 1,
-                        nested_threshold,
-                        all_reachable,
-                        external_commands.clone(),
-                        surf_parsing.clone(),
-                        db.clone(),
-                        md_static,
-                        color_scheme,
-                    )
-                    .await?;
-                all_reachable = roundtrip_reachable;
-                tree.push(next_tree);
-            }
-        }
-
-        Ok((tree, all_reachable))
+        let parsed = ParsedBody::of_all(subgraph.notes.values(), &surf_parsing, &db).await?;
+        self.link_term_tree(
+            &subgraph,
+            &parsed,
+            level,
+            nested_threshold,
+            all_reachable,
+            &surf_parsing,
+            color_scheme,
+        )
     }
 
     #[allow(clippy::too_many_arguments)]
-    #[async_recursion]
     pub async fn construct_link_term_tree_up(
         &self,
         level: usize,
         nested_threshold: usize,
-        mut all_reachable: HashSet<Note>,
-        external_commands: ExternalCommands,
+        all_reachable: HashSet<Note>,
         surf_parsing: SurfParsing,
-        db: SqliteAsyncHandle,
+        db: DatabaseAsyncHandle,
         md_static: MarkdownStatic,
         color_scheme: ColorScheme,
     ) -> SqlxResult<(Tree<NoteLinkTerm, Up>, HashSet<Note>)> {
-        let straight = false;
+        let subgraph = db
+            .lock()
+            .await
+            .reachable_subgraph(&self.name(), md_static, color_scheme, false, None)
+            .await?;
+        let parsed = ParsedBody::of_all(subgraph.notes.values(), &surf_parsing, &db).await?;
+        self.link_term_tree(
+            &subgraph,
+            &parsed,
+            level,
+            nested_threshold,
+            all_reachable,
+            &surf_parsing,
+            color_scheme,
+        )
+    }
+
+    /// tree of notes and links, found in their bodies, built from `subgraph`, fetched
+    /// in direction of `V`
+    #[allow(clippy::too_many_arguments)]
+    fn link_term_tree<V: Direction>(
+        &self,
+        subgraph: &Subgraph,
+        parsed: &HashMap<String, ParsedBody>,
+        level: usize,
+        nested_threshold: usize,
+        mut all_reachable: HashSet<Note>,
+        surf_parsing: &SurfParsing,
+        color_scheme: ColorScheme,
+    ) -> SqlxResult<(Tree<NoteLinkTerm, V>, HashSet<Note>)> {
         let mut tree = Tree::new(NoteLinkTerm::Note(self.clone()));
         all_reachable.insert(self.clone());
 
-        let links = Link::parse(self, &surf_parsing, color_scheme)?;
+        let links = match parsed.get(&self.name()) {
+            Some(body) => Link::resolve(self, &body.links, surf_parsing, color_scheme),
+            None => vec![],
+        };
 
         if !links.is_empty() {
             if level >= nested_threshold {
@@ -155,29 +169,24 @@ impl Note {
             }
         }
 
-        let forward_links = db
-            .lock()
-            .await
-            .find_links_from(&self.name(), md_static, color_scheme, straight)
-            .await?;
+        let forward_links = subgraph.labelled_links_from(&self.name());
 
-        for next in forward_links.into_iter().rev() {
+        for (next, label) in forward_links.into_iter().rev() {
             if all_reachable.contains(&next) {
-                tree.push(Tree::new(NoteLinkTerm::Cycle(next.name(), color_scheme)));
+                let cycle = NoteLinkTerm::Cycle(next.name(), color_scheme);
+                tree.push(Tree::new(cycle.labelled(label, color_scheme)));
             } else {
-                let (next_tree, roundtrip_reachable) = next
-                    .construct_link_term_tree_up(
-                        level + 1,
-                        nested_threshold,
-                        all_reachable,
-                        external_commands.clone(),
-                        surf_parsing.clone(),
-                        db.clone(),
-                        md_static,
-                        color_scheme,
-                    )
-                    .await?;
+                let (mut next_tree, roundtrip_reachable) = next.link_term_tree(
+                    subgraph,
+                    parsed,
+                    level + 1,
+                    nested_threshold,
+                    all_reachable,
+                    surf_parsing,
+                    color_scheme,
+                )?;
                 all_reachable = roundtrip_reachable;
+                next_tree.root = next_tree.root.labelled(label, color_scheme);
                 tree.push(next_tree);
             }
         }
@@ -1,14 +1,15 @@
 use colored::Colorize;
+use std::collections::HashSet;
 use std::fmt::Display;
 use std::hash::{Hash, Hasher};
-use std::io::Write;
-use std::{fs::File, io, path::PathBuf};
+use std::{io, path::PathBuf};
 use syntect::easy::HighlightLines;
 
 use crate::config::color::ColorScheme;
 use crate::config::Open as OpenCfg;
 use crate::config::{ExternalCommands, SurfParsing};
-use crate::database::SqliteAsyncHandle;
+use crate::database::DatabaseAsyncHandle;
+use crate::front_matter;
 use crate::highlight::{highlight, MarkdownStatic};
 use crate::Open;
 mod links_term_tree;
@@ -16,16 +17,19 @@ mod random;
 mod reachable;
 mod skim_item;
 mod task_items_term_tree;
+pub use self::links_term_tree::NoteLinkTerm;
+pub use self::skim_item::{LazyPreview, PreviewCache};
 pub use self::task_items_term_tree::NoteTaskItemTerm;
-use crate::database::Database;
 use duct::cmd;
 use sqlx::Result as SqlxResult;
 
-#[derive(Clone, Debug, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Hash, Default)]
 pub enum PreviewType {
     Details,
+    #[default]
     LinkStructure,
     TaskStructure,
+    Stats,
 }
 
 impl PreviewType {
@@ -33,14 +37,69 @@ impl PreviewType {
         match self {
             Self::Details => Self::LinkStructure,
             Self::LinkStructure => Self::TaskStructure,
-            Self::TaskStructure => Self::Details,
+            Self::TaskStructure => Self::Stats,
+            Self::Stats => Self::Details,
         }
     }
 }
 
-impl Default for PreviewType {
-    fn default() -> Self {
-        Self::LinkStructure
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Default)]
+pub enum ListOrder {
+    #[default]
+    Name,
+    RecentlyCreated,
+    RecentlyModified,
+    RecentlyOpened,
+}
+
+impl ListOrder {
+    pub fn toggle(&self) -> Self {
+        match self {
+            Self::Name => Self::RecentlyCreated,
+            Self::RecentlyCreated => Self::RecentlyModified,
+            Self::RecentlyModified => Self::RecentlyOpened,
+            Self::RecentlyOpened => Self::Name,
+        }
+    }
+
+    pub async fn sort(&self, notes: &mut [Note], db: &DatabaseAsyncHandle) -> SqlxResult<()> {
+        if let Self::Name = self {
+            notes.sort_by_key(|note| std::cmp::Reverse(note.name()));
+            return Ok(());
+        }
+        let timestamps = db.lock().await.timestamps().await?;
+        let key = |note: &Note| -> Option<i64> {
+            let stamps = timestamps.get(&note.name()).copied().unwrap_or_default();
+            match self {
+                Self::Name => None,
+                Self::RecentlyCreated => stamps.created,
+                Self::RecentlyModified => {
+                    // bodies may be edited outside of `mds`
+                    let mtime = note.file_path().and_then(|path| {
+                        let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+                        let since = modified.duration_since(std::time::UNIX_EPOCH).ok()?;
+                        Some(since.as_secs() as i64)
+                    });
+                    std::cmp::max(stamps.updated, mtime)
+                }
+                Self::RecentlyOpened => stamps.opened,
+            }
+        };
+        // most recent first, notes without a timestamp last
+        notes.sort_by_cached_key(|note| std::cmp::Reverse(key(note)));
+        Ok(())
+    }
+}
+
+impl Display for ListOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let repr = match self {
+            Self::Name => "name",
+            Self::RecentlyCreated => "recently created",
+            Self::RecentlyModified => "recently modified",
+            Self::RecentlyOpened => "recently opened",
+        };
+        write!(f, "{}", repr)
     }
 }
 
@@ -50,6 +109,8 @@ pub struct DynResources {
     pub surf_parsing: SurfParsing,
     pub preview_type: PreviewType,
     pub preview_result: Option<String>,
+    /// rendered on focus instead, if `preview_result` isn't prepared
+    pub lazy_preview: Option<LazyPreview>,
 }
 
 #[derive(Clone, Debug)]
@@ -59,11 +120,14 @@ pub enum Note {
         file_path: PathBuf,
         resources: Option<DynResources>,
         name_markdown: Option<String>,
+        aliases: Vec<String>,
+        #[allow(dead_code)]
         color_scheme: ColorScheme,
     },
     Tag {
         name: String,
         resources: Option<DynResources>,
+        aliases: Vec<String>,
         color_scheme: ColorScheme,
     },
 }
@@ -144,11 +208,13 @@ impl Note {
                 file_path,
                 resources: None,
                 name_markdown: None,
+                aliases: vec![],
                 color_scheme,
             },
             None => Self::Tag {
                 name,
                 resources: None,
+                aliases: vec![],
                 color_scheme,
             },
         }
@@ -202,7 +268,7 @@ impl Note {
 
         color_scheme: ColorScheme,
     ) -> Self {
-        let time_str = chrono::Utc::now().naive_utc().timestamp().to_string();
+        let time_str = chrono::Utc::now().timestamp().to_string();
         let suffix = random::rand_suffix();
         let fname = format!("{}_{}.md", time_str, suffix);
 
@@ -214,11 +280,15 @@ impl Note {
 
     pub(crate) fn persist(&self) -> Result<(), io::Error> {
         if let Self::MdFile { file_path, .. } = &self {
-            let mut output = File::create(file_path.as_path())?;
-            writeln!(output, "# 💖 {}", self.name())?;
+            // front matter may have been written by storage on save
+            front_matter::write_body(file_path, &format!("{}\n", Self::heading(&self.name())))?;
         }
         Ok(())
     }
+    pub(crate) fn heading(name: &str) -> String {
+        format!("# 💖 {}", name)
+    }
+
     pub fn name(&self) -> String {
         match &self {
             Self::MdFile { name, .. } => name.clone(),
@@ -232,6 +302,24 @@ impl Note {
             Self::Tag { .. } => None,
         }
     }
+    pub fn aliases(&self) -> &[String] {
+        match self {
+            Self::MdFile { aliases, .. } => aliases,
+            Self::Tag { aliases, .. } => aliases,
+        }
+    }
+
+    pub fn set_aliases(&mut self, to_set: Vec<String>) {
+        match self {
+            Self::MdFile { aliases, .. } => {
+                *aliases = to_set;
+            }
+            Self::Tag { aliases, .. } => {
+                *aliases = to_set;
+            }
+        }
+    }
+
     pub fn set_resources(&mut self, to_set: DynResources) {
         match self {
             Self::MdFile { resources, .. } => {
@@ -262,7 +350,7 @@ impl Note {
 
     pub async fn fetch_forward_links(
         &self,
-        db: &SqliteAsyncHandle,
+        db: &DatabaseAsyncHandle,
         md_static: MarkdownStatic,
         color_scheme: ColorScheme,
         straight: bool,
@@ -273,9 +361,35 @@ impl Note {
             .await
     }
 
+    pub async fn fetch_labelled_forward_links(
+        &self,
+        db: &DatabaseAsyncHandle,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+        straight: bool,
+    ) -> SqlxResult<Vec<(Note, Option<String>)>> {
+        db.lock()
+            .await
+            .find_labelled_links_from(&self.name(), md_static, color_scheme, straight)
+            .await
+    }
+
+    pub async fn fetch_labelled_backlinks(
+        &self,
+        db: &DatabaseAsyncHandle,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+        straight: bool,
+    ) -> SqlxResult<Vec<(Note, Option<String>)>> {
+        db.lock()
+            .await
+            .find_labelled_links_to(&self.name(), md_static, color_scheme, straight)
+            .await
+    }
+
     pub async fn fetch_backlinks(
         &self,
-        db: &SqliteAsyncHandle,
+        db: &DatabaseAsyncHandle,
         md_static: MarkdownStatic,
         color_scheme: ColorScheme,
         straight: bool,
@@ -286,3 +400,52 @@ impl Note {
             .await
     }
 }
+
+/// index of the first `# heading` line of a body, lines of fenced code blocks, e.g. shell
+/// comments, are skipped
+fn heading_line(body: &str) -> Option<usize> {
+    let mut fenced = false;
+    for (index, line) in body.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+        } else if !fenced && line.starts_with("# ") {
+            return Some(index);
+        }
+    }
+    None
+}
+
+/// name of a note, parsed from the first `# heading` of its body
+pub fn parse_heading_name(body: &str) -> Option<String> {
+    let line = body.lines().nth(heading_line(body)?)?;
+    let name = line["# ".len()..].trim().trim_start_matches('💖').trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// replaces the first `# heading` of a body with the one, generated for `name`
+pub fn rewrite_heading(body: &str, name: &str) -> String {
+    let heading = heading_line(body);
+    let replaced = heading.is_some();
+    let mut lines = body.lines().map(str::to_string).collect::<Vec<_>>();
+    match heading {
+        Some(index) => lines[index] = Note::heading(name),
+        None => lines.insert(0, Note::heading(name)),
+    }
+    let mut result = lines.join("\n");
+    if body.ends_with('\n') || !replaced {
+        result.push('\n');
+    }
+    result
+}
+
+/// `name`, suffixed with a number if it's already taken
+pub fn unique_name(name: &str, taken: &HashSet<String>) -> String {
+    if !taken.contains(name) {
+        return name.to_string();
+    }
+    (2..)
+        .map(|index| format!("{} ({})", name, index))
+        .find(|candidate| !taken.contains(candidate))
+        .expect("infinite range")
+}
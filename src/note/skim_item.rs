@@ -2,16 +2,19 @@ use std::borrow::Cow;
 
 use skim::{AnsiString, DisplayContext, ItemPreview, PreviewContext, SkimItem};
 
-use crate::{config::color::ColorScheme, database::SqliteAsyncHandle, highlight::MarkdownStatic};
+use crate::{config::color::ColorScheme, database::DatabaseAsyncHandle, highlight::MarkdownStatic};
 
 use super::PreviewType;
 
+mod lazy;
 mod preview;
 
+pub use self::lazy::{LazyPreview, PreviewCache};
+
 impl super::Note {
     async fn compute_preview(
         &self,
-        db: &SqliteAsyncHandle,
+        db: &DatabaseAsyncHandle,
         md_static: MarkdownStatic,
         color_scheme: ColorScheme,
         straight: bool,
@@ -31,6 +34,7 @@ impl super::Note {
                         self.task_structure(db, md_static, color_scheme, straight, nested_threshold)
                             .await
                     }
+                    PreviewType::Stats => self.stats(db, md_static, color_scheme, straight).await,
                 };
                 Some(result)
             }
@@ -39,7 +43,7 @@ impl super::Note {
     }
     pub async fn prepare_preview(
         &mut self,
-        db: &SqliteAsyncHandle,
+        db: &DatabaseAsyncHandle,
         md_static: MarkdownStatic,
         color_scheme: ColorScheme,
         straight: bool,
@@ -55,13 +59,19 @@ impl super::Note {
 }
 
 impl SkimItem for super::Note {
-    fn text(&self) -> Cow<str> {
-        Cow::Owned(self.name())
+    fn text(&self) -> Cow<'_, str> {
+        // aliases are matched, but not displayed
+        let mut text = self.name();
+        for alias in self.aliases() {
+            text.push(' ');
+            text.push_str(alias);
+        }
+        Cow::Owned(text)
     }
     fn display<'a>(&'a self, _context: DisplayContext<'a>) -> AnsiString<'a> {
         let input = format!("{}", self);
-        let ansistring = AnsiString::parse(&input);
-        ansistring
+
+        AnsiString::parse(&input)
     }
 
     fn preview(&self, _context: PreviewContext) -> ItemPreview {
@@ -69,6 +79,8 @@ impl SkimItem for super::Note {
             Some(resources) => {
                 if let Some(ref result) = resources.preview_result {
                     ItemPreview::AnsiText(result.clone())
+                } else if let Some(ref lazy) = resources.lazy_preview {
+                    lazy.preview(self, resources.preview_type)
                 } else {
                     ItemPreview::Text("<empty preview_result>".to_string())
                 }
@@ -0,0 +1,213 @@
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{Arc, Mutex},
+};
+
+use skim::ItemPreview;
+use tokio::{runtime::Handle, task::JoinHandle};
+
+use crate::{
+    config::color::ColorScheme, database::DatabaseAsyncHandle, highlight::MarkdownStatic,
+    note::Note,
+};
+
+use super::super::PreviewType;
+
+static DIR_PREFIX: &str = "mds-previews-";
+/// how long preview command waits for rendering, in polls of 0.05s each
+const WAIT_POLLS: usize = 600;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Key {
+    name: String,
+    preview_type: PreviewType,
+    straight: bool,
+    nested_threshold: usize,
+}
+
+struct Pending {
+    key: Key,
+    path: PathBuf,
+    handle: JoinHandle<()>,
+}
+
+struct Cache {
+    rendered: HashMap<Key, String>,
+    /// preview of the focused note, which is still being rendered
+    pending: Option<Pending>,
+    /// rendered previews are also written here, for skim's preview command to pick up
+    dir: PathBuf,
+    next_file: usize,
+}
+
+impl Drop for Cache {
+    fn drop(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            pending.handle.abort();
+        }
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// previews of notes, rendered in background, when a note gets focused in skim,
+/// and kept, until cleared
+#[derive(Clone)]
+pub struct PreviewCache(Arc<Mutex<Cache>>);
+
+impl PreviewCache {
+    pub fn new() -> io::Result<Self> {
+        let temp_dir = std::env::temp_dir();
+        remove_stale_dirs(&temp_dir);
+        let dir = temp_dir.join(format!("{}{}", DIR_PREFIX, std::process::id()));
+        // left by a dead process with the same id, its files mustn't be taken for previews
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir)?;
+        Ok(Self(Arc::new(Mutex::new(Cache {
+            rendered: HashMap::new(),
+            pending: None,
+            dir,
+            next_file: 0,
+        }))))
+    }
+
+    /// drop all rendered previews, as notes may have changed
+    pub fn clear(&self) {
+        let mut cache = self.0.lock().unwrap();
+        cache.rendered.clear();
+        if let Some(pending) = cache.pending.take() {
+            pending.handle.abort();
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct LazyPreview {
+    pub cache: PreviewCache,
+    pub db: DatabaseAsyncHandle,
+    pub md_static: MarkdownStatic,
+    pub color_scheme: ColorScheme,
+    pub straight: bool,
+    pub nested_threshold: usize,
+    pub runtime: Handle,
+}
+
+impl fmt::Debug for LazyPreview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyPreview")
+            .field("straight", &self.straight)
+            .field("nested_threshold", &self.nested_threshold)
+            .finish_non_exhaustive()
+    }
+}
+
+impl LazyPreview {
+    /// rendered preview of `note`, if cached; otherwise rendering is started, cancelling
+    /// the one of previously focused note, and a command, waiting for its result, is returned
+    pub(super) fn preview(&self, note: &Note, preview_type: PreviewType) -> ItemPreview {
+        let key = Key {
+            name: note.name(),
+            preview_type,
+            straight: self.straight,
+            nested_threshold: self.nested_threshold,
+        };
+        let mut cache = self.cache.0.lock().unwrap();
+        if let Some(rendered) = cache.rendered.get(&key) {
+            return ItemPreview::AnsiText(rendered.clone());
+        }
+        if let Some(pending) = cache.pending.take() {
+            if pending.key == key && !pending.handle.is_finished() {
+                let command = wait_command(&pending.path);
+                cache.pending = Some(pending);
+                return ItemPreview::Command(command);
+            }
+            pending.handle.abort();
+        }
+
+        let path = cache.dir.join(format!("{}.ansi", cache.next_file));
+        cache.next_file += 1;
+        let handle = self.runtime.spawn({
+            let note = note.clone();
+            let lazy = self.clone();
+            let key = key.clone();
+            let path = path.clone();
+            async move {
+                let result = note
+                    .compute_preview(
+                        &lazy.db,
+                        lazy.md_static,
+                        lazy.color_scheme,
+                        lazy.straight,
+                        lazy.nested_threshold,
+                    )
+                    .await;
+                let text = match &result {
+                    Some(result) => result.as_str(),
+                    // the waiting command is released all the same
+                    None => "no preview of this item",
+                };
+                let written = path.with_extension("tmp");
+                if fs::write(&written, text).is_ok() {
+                    let _ = fs::rename(&written, &path);
+                }
+                if let Some(result) = result {
+                    lazy.cache.0.lock().unwrap().rendered.insert(key, result);
+                }
+            }
+        });
+        let command = wait_command(&path);
+        cache.pending = Some(Pending { key, path, handle });
+        ItemPreview::Command(command)
+    }
+}
+
+/// skim kills this command, when cursor moves on to another note; it gives up, if
+/// rendering takes too long or has died without writing a result
+fn wait_command(path: &Path) -> String {
+    let path = shell_quote(&path.to_string_lossy());
+    let script = format!(
+        "n=0; until [ -f {path} ] || [ $n -ge {polls} ]; do n=$((n+1)); sleep 0.05; done; \
+        cat {path} 2>/dev/null || echo 'preview timed out'",
+        path = path,
+        polls = WAIT_POLLS,
+    );
+    // run by user's $SHELL, which may be not a posix one
+    format!("sh -c {}", shell_quote(&script))
+}
+
+/// single quoted word for posix shell
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+/// dirs of previews of processes, which are no longer running, e.g. after a crash
+fn remove_stale_dirs(temp_dir: &Path) {
+    let Ok(entries) = fs::read_dir(temp_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let file_name = entry.file_name();
+        let Some(pid) = file_name
+            .to_str()
+            .and_then(|name| name.strip_prefix(DIR_PREFIX))
+            .and_then(|pid| pid.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        if pid == std::process::id() {
+            continue;
+        }
+        let running = Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(true);
+        if !running {
+            log::debug!("removing stale previews dir {:?}", entry.path());
+            let _ = fs::remove_dir_all(entry.path());
+        }
+    }
+}
@@ -1,18 +1,19 @@
 use colored::Colorize;
 
 use crate::config::color::ColorScheme;
-use crate::database::SqliteAsyncHandle;
+use crate::database::DatabaseAsyncHandle;
 use crate::highlight::MarkdownStatic;
 use crate::note::Note;
 use comfy_table::presets::UTF8_FULL;
 use comfy_table::{Cell, Color, ContentArrangement, Table};
 
 use sqlx::Error;
+use std::collections::BTreeMap;
 
 use crate::external_commands::fetch_content;
 use crate::print::format_two_tokens;
 
-fn map_db_result(received: Result<Vec<Note>, Error>) -> String {
+fn map_db_result(received: Result<Vec<(Note, Option<String>)>, Error>) -> String {
     match received {
         Ok(list) => {
             if !list.is_empty() {
@@ -24,8 +25,9 @@ fn map_db_result(received: Result<Vec<Note>, Error>) -> String {
                     .set_header(vec![
                         Cell::new("Name").fg(Color::Blue),
                         Cell::new("Type").fg(Color::Blue),
+                        Cell::new("Label").fg(Color::Blue),
                     ]);
-                list.into_iter().for_each(|note| {
+                list.into_iter().for_each(|(note, label)| {
                     let is_tag = note.file_path().is_none();
                     let color = if is_tag {
                         Color::Cyan
@@ -36,6 +38,7 @@ fn map_db_result(received: Result<Vec<Note>, Error>) -> String {
                     table.add_row(vec![
                         Cell::new(note.name()),
                         Cell::new(type_column).fg(color),
+                        Cell::new(label.unwrap_or_default()).fg(Color::DarkMagenta),
                     ]);
                 });
                 format!("{}\n", table)
@@ -47,7 +50,34 @@ fn map_db_result(received: Result<Vec<Note>, Error>) -> String {
     }
 }
 
-fn map_result(query_result: Result<Vec<Note>, Error>, tag: String) -> String {
+fn map_properties(received: Result<BTreeMap<String, String>, Error>) -> String {
+    match received {
+        Ok(properties) => {
+            if properties.is_empty() {
+                return String::new();
+            }
+            let mut table = Table::new();
+            table
+                .load_preset(UTF8_FULL)
+                .set_content_arrangement(ContentArrangement::Dynamic)
+                .set_width(80)
+                .set_header(vec![
+                    Cell::new("Key").fg(Color::Blue),
+                    Cell::new("Value").fg(Color::Blue),
+                ]);
+            properties.into_iter().for_each(|(key, value)| {
+                table.add_row(vec![
+                    Cell::new(key).fg(Color::DarkMagenta),
+                    Cell::new(value),
+                ]);
+            });
+            format!("Properties:\n{}\n\n", table)
+        }
+        Err(err) => format!("db err {:?}", err).truecolor(255, 0, 0).to_string(),
+    }
+}
+
+fn map_result(query_result: Result<Vec<(Note, Option<String>)>, Error>, tag: String) -> String {
     let links_to = map_db_result(query_result);
 
     let mut string = String::new();
@@ -63,17 +93,18 @@ fn map_result(query_result: Result<Vec<Note>, Error>, tag: String) -> String {
 impl Note {
     pub async fn details(
         &self,
-        db: &SqliteAsyncHandle,
+        db: &DatabaseAsyncHandle,
         md_static: MarkdownStatic,
         color_scheme: ColorScheme,
         straight: bool,
     ) -> String {
         let result_from = self
-            .fetch_forward_links(db, md_static, color_scheme, straight)
+            .fetch_labelled_forward_links(db, md_static, color_scheme, straight)
             .await;
         let result_to = self
-            .fetch_backlinks(db, md_static, color_scheme, straight)
+            .fetch_labelled_backlinks(db, md_static, color_scheme, straight)
             .await;
+        let properties = map_properties(db.lock().await.properties(&self.name()).await);
         let links_to = map_result(result_from, "Links to:".to_string());
         let linked_by = map_result(result_to, "Linked by:".to_string());
         let mut string = String::new();
@@ -83,7 +114,12 @@ impl Note {
             format_two_tokens("it's a tag:", &self.name())
         };
         string.push_str(&title);
+        if !self.aliases().is_empty() {
+            string.push('\n');
+            string.push_str(&format_two_tokens("aka:", &self.aliases().join(", ")));
+        }
         string.push_str("\n\n");
+        string.push_str(&properties);
         string.push_str(&linked_by);
         string.push_str(&links_to);
         if let Some(resources) = self.resources() {
@@ -91,8 +127,8 @@ impl Note {
                 resources.external_commands.preview.file_cmd.clone(),
                 self.file_path(),
             );
-            if body.is_some() {
-                string.push_str(&body.unwrap());
+            if let Some(body) = body {
+                string.push_str(&body);
             }
         }
         string
@@ -0,0 +1,36 @@
+use colored::Colorize;
+
+use crate::{
+    config::color::ColorScheme, database::DatabaseAsyncHandle, highlight::MarkdownStatic,
+    note::Note, print::format_two_tokens, stats::Stats,
+};
+
+impl Note {
+    pub async fn stats(
+        &self,
+        db: &DatabaseAsyncHandle,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+        straight: bool,
+    ) -> String {
+        let rs = self.resources().unwrap();
+        let result = Stats::collect(
+            db.clone(),
+            Some(self),
+            straight,
+            &rs.surf_parsing,
+            md_static,
+            color_scheme,
+        )
+        .await;
+        match result {
+            Ok(stats) => {
+                let title = format_two_tokens("reachable from:", &self.name());
+                format!("{}\n\n{}", title, stats.table())
+            }
+            Err(err) => format!("stats err {:?}", err)
+                .truecolor(255, 0, 0)
+                .to_string(),
+        }
+    }
+}
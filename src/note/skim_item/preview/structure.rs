@@ -1,7 +1,8 @@
 use colored::Colorize;
 
 use crate::{
-    config::color::ColorScheme, database::SqliteAsyncHandle, highlight::MarkdownStatic, note::Note,
+    config::color::ColorScheme, database::DatabaseAsyncHandle, highlight::MarkdownStatic,
+    note::Note,
 };
 
 use std::collections::HashSet;
@@ -9,7 +10,7 @@ use std::collections::HashSet;
 impl Note {
     pub async fn link_structure(
         &self,
-        db: &SqliteAsyncHandle,
+        db: &DatabaseAsyncHandle,
         md_static: MarkdownStatic,
         color_scheme: ColorScheme,
         straight: bool,
@@ -23,7 +24,6 @@ impl Note {
                     0,
                     nested_threshold,
                     HashSet::new(),
-                    rs.external_commands.clone(),
                     rs.surf_parsing.clone(),
                     db.clone(),
                     md_static,
@@ -40,7 +40,6 @@ impl Note {
                     0,
                     nested_threshold,
                     HashSet::new(),
-                    rs.external_commands.clone(),
                     rs.surf_parsing.clone(),
                     db.clone(),
                     md_static,
@@ -56,7 +55,7 @@ impl Note {
 
     pub async fn task_structure(
         &self,
-        db: &SqliteAsyncHandle,
+        db: &DatabaseAsyncHandle,
         md_static: MarkdownStatic,
         color_scheme: ColorScheme,
         straight: bool,
@@ -1,14 +1,19 @@
-use std::{collections::HashSet, fmt::Display, fs, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    fs,
+    path::PathBuf,
+};
 
 use crate::{
     config::{color::ColorScheme, SurfParsing},
-    database::{Database, SqliteAsyncHandle},
+    database::{DatabaseAsyncHandle, Subgraph},
     highlight::MarkdownStatic,
     lines::find_position,
+    parse_cache::ParsedBody,
     task_item::TaskItem,
     Jump,
 };
-use async_recursion::async_recursion;
 use bidir_termtree::{Down, Tree};
 use colored::Colorize;
 use syntect::easy::HighlightLines;
@@ -178,24 +183,56 @@ impl Jump for NoteTaskItemTerm {
 
 impl Note {
     #[allow(clippy::too_many_arguments)]
-    #[async_recursion]
     pub async fn construct_task_item_term_tree(
         &self,
         level: usize,
         nested_threshold: usize,
-        mut all_reachable: HashSet<Note>,
+        all_reachable: HashSet<Note>,
         surf_parsing: SurfParsing,
-        db: SqliteAsyncHandle,
+        db: DatabaseAsyncHandle,
         md_static: MarkdownStatic,
         color_scheme: ColorScheme,
         straight: bool,
+    ) -> SqlxResult<(Tree<NoteTaskItemTerm, Down>, HashSet<Note>)> {
+        let subgraph = db
+            .lock()
+            .await
+            .reachable_subgraph(&self.name(), md_static, color_scheme, straight, None)
+            .await?;
+        let parsed = ParsedBody::of_all(subgraph.notes.values(), &surf_parsing, &db).await?;
+        let mut highlighter = HighlightLines::new(md_static.1, md_static.2);
+        self.task_item_term_tree(
+            &subgraph,
+            &parsed,
+            level,
+            nested_threshold,
+            all_reachable,
+            &mut highlighter,
+            md_static,
+            color_scheme,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn task_item_term_tree(
+        &self,
+        subgraph: &Subgraph,
+        parsed: &HashMap<String, ParsedBody>,
+        level: usize,
+        nested_threshold: usize,
+        mut all_reachable: HashSet<Note>,
+        highlighter: &mut HighlightLines,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
     ) -> SqlxResult<(Tree<NoteTaskItemTerm, Down>, HashSet<Note>)> {
         let mut tree = Tree::new(NoteTaskItemTerm::Note(self.clone()));
         all_reachable.insert(self.clone());
 
-        let tasks = {
-            let mut highlighter = HighlightLines::new(md_static.1, md_static.2);
-            TaskItem::parse(self, &surf_parsing, &mut highlighter, md_static)?
+        let tasks = match (self.file_path(), parsed.get(&self.name())) {
+            (Some(file_path), Some(body)) => {
+                TaskItem::resolve(file_path, &body.task_items, highlighter, md_static)
+            }
+            _ => vec![],
         };
 
         let task_trees = NoteTaskItemTerm::parse(&tasks, true, false);
@@ -215,31 +252,25 @@ impl Note {
             }
         }
 
-        let forward_links = db
-            .lock()
-            .await
-            .find_links_from(&self.name(), md_static, color_scheme, straight)
-            .await?;
+        let forward_links = subgraph.labelled_links_from(&self.name());
 
-        for next in forward_links.into_iter().rev() {
+        for (next, _label) in forward_links.into_iter().rev() {
             if all_reachable.contains(&next) {
                 tree.push(Tree::new(NoteTaskItemTerm::Cycle(
                     next.name(),
                     color_scheme,
                 )));
             } else {
-                let (next_tree, roundtrip_reachable) = next
-                    .construct_task_item_term_tree(
-                        level + 1,
-                        nested_threshold,
-                        all_reachable,
-                        surf_parsing.clone(),
-                        db.clone(),
-                        md_static,
-                        color_scheme,
-                        straight,
-                    )
-                    .await?;
+                let (next_tree, roundtrip_reachable) = next.task_item_term_tree(
+                    subgraph,
+                    parsed,
+                    level + 1,
+                    nested_threshold,
+                    all_reachable,
+                    highlighter,
+                    md_static,
+                    color_scheme,
+                )?;
                 all_reachable = roundtrip_reachable;
                 tree.push(next_tree);
             }
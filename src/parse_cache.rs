@@ -0,0 +1,162 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::UNIX_EPOCH,
+};
+
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqliteRow, Error, Row, SqlitePool};
+
+use crate::{
+    config::SurfParsing, database::DatabaseAsyncHandle, link::ParsedLink, note::Note,
+    task_item::ParsedTaskItem,
+};
+
+/// links and task items, parsed from a note's file, serialized as json; valid while file's
+/// mtime, size and the regexes used for parsing are the same
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedFile {
+    pub mtime: i64,
+    pub size: i64,
+    pub regexes: String,
+    pub body: String,
+}
+
+/// parsed files by path, for storages other than sqlite, kept for the duration of process
+pub type ParsedFiles = Arc<Mutex<HashMap<String, ParsedFile>>>;
+
+/// cache of parsed files beside storage of notes, handed out by it, so that it's shared
+/// by all of its readers and isn't locked along with notes
+#[derive(Debug, Clone)]
+pub enum ParseCache {
+    Sqlite(SqlitePool),
+    Memory(ParsedFiles),
+}
+
+impl ParseCache {
+    pub async fn get(&self, file_path: &str) -> sqlx::Result<Option<ParsedFile>> {
+        match self {
+            Self::Sqlite(pool) => {
+                sqlx::query("select * from parse_cache where file_path = ?1")
+                    .bind(file_path)
+                    .map(|row: SqliteRow| ParsedFile {
+                        mtime: row.get("mtime"),
+                        size: row.get("size"),
+                        regexes: row.get("regexes"),
+                        body: row.get("body"),
+                    })
+                    .fetch_optional(pool)
+                    .await
+            }
+            Self::Memory(files) => Ok(files.lock().unwrap().get(file_path).cloned()),
+        }
+    }
+
+    pub async fn save(&self, file_path: &str, parsed: &ParsedFile) -> sqlx::Result<()> {
+        log::debug!("caching parsed body of {}", file_path);
+
+        match self {
+            Self::Sqlite(pool) => {
+                sqlx::query(
+                    "insert into parse_cache(file_path, mtime, size, regexes, body)
+                        values(?1, ?2, ?3, ?4, ?5)
+                        on conflict(file_path) do update set mtime = excluded.mtime,
+                            size = excluded.size, regexes = excluded.regexes, body = excluded.body",
+                )
+                .bind(file_path)
+                .bind(parsed.mtime)
+                .bind(parsed.size)
+                .bind(&parsed.regexes)
+                .bind(&parsed.body)
+                .execute(pool)
+                .await?;
+            }
+            Self::Memory(files) => {
+                files
+                    .lock()
+                    .unwrap()
+                    .insert(file_path.to_string(), parsed.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// links and task items, parsed from a note's file, which is only re-read and re-parsed
+/// when its mtime or size changes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ParsedBody {
+    pub links: Vec<ParsedLink>,
+    pub task_items: Vec<ParsedTaskItem>,
+}
+
+impl ParsedBody {
+    pub async fn of(
+        note: &Note,
+        surf: &SurfParsing,
+        db: &DatabaseAsyncHandle,
+    ) -> sqlx::Result<Self> {
+        let Some(file_path) = note.file_path() else {
+            return Ok(Self::default());
+        };
+        let key = file_path.to_string_lossy().to_string();
+        let (mtime, size) = stamp(file_path)?;
+        // parsed body depends on these as much as on file's content
+        let regexes = format!(
+            "{}\n{}",
+            surf.markdown_reference_link_regex.0.as_str(),
+            surf.task_item_regex.0.as_str()
+        );
+
+        let cache = db.lock().await.parse_cache();
+        let cached = cache.get(&key).await?;
+        if let Some(cached) = cached {
+            if cached.mtime == mtime && cached.size == size && cached.regexes == regexes {
+                if let Ok(body) = serde_json::from_str(&cached.body) {
+                    return Ok(body);
+                }
+            }
+        }
+
+        let file_content = fs::read_to_string(file_path)?;
+        let body = Self {
+            links: ParsedLink::parse(surf, &file_content),
+            task_items: ParsedTaskItem::parse(&file_content, &surf.task_item_regex.0),
+        };
+        let parsed = ParsedFile {
+            mtime,
+            size,
+            regexes,
+            body: serde_json::to_string(&body).map_err(|err| Error::Protocol(err.to_string()))?,
+        };
+        cache.save(&key, &parsed).await?;
+        Ok(body)
+    }
+
+    /// parsed bodies of all `notes` by name
+    pub async fn of_all<'a>(
+        notes: impl IntoIterator<Item = &'a Note>,
+        surf: &SurfParsing,
+        db: &DatabaseAsyncHandle,
+    ) -> sqlx::Result<HashMap<String, Self>> {
+        let mut result = HashMap::new();
+        for note in notes {
+            result.insert(note.name(), Self::of(note, surf, db).await?);
+        }
+        Ok(result)
+    }
+}
+
+/// modification time in nanos, so that a rewrite soon after parsing, e.g. toggling a task
+/// item, isn't missed, and size of file
+fn stamp(path: &Path) -> std::io::Result<(i64, i64)> {
+    let metadata = fs::metadata(path)?;
+    let nanos = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as i64)
+        .unwrap_or(0);
+    Ok((nanos, metadata.len() as i64))
+}
@@ -7,13 +7,14 @@ use skim::{
 
 use crate::{
     config::{color::ColorScheme, keymap, ExternalCommands, SurfParsing},
-    database::SqliteAsyncHandle,
+    database::DatabaseAsyncHandle,
     highlight::MarkdownStatic,
-    note::{DynResources, Note, PreviewType},
+    note::{DynResources, LazyPreview, Note, PreviewCache, PreviewType},
 };
+use tokio::runtime::Handle;
 
 pub(crate) struct Iteration {
-    db: SqliteAsyncHandle,
+    db: DatabaseAsyncHandle,
     items: Option<Vec<Note>>,
     external_commands: ExternalCommands,
     surf_parsing: SurfParsing,
@@ -23,6 +24,7 @@ pub(crate) struct Iteration {
     straight: bool,
     nested_threshold: usize,
     bindings_map: keymap::explore::Bindings,
+    previews: PreviewCache,
 }
 
 pub enum Action {
@@ -46,6 +48,45 @@ pub enum Action {
     DecreaseUnlistedThreshold,
     PushToStack(Note),
     SwitchToStack,
+    Search,
+    FilterLinksByLabel(Note),
+    CycleListOrder,
+    Undo,
+    Redo,
+    EditAliases(Note),
+    FilterByProperty,
+    Merge(Note),
+    Split(Note),
+    Path(Note),
+    Orphans,
+    Leaves,
+    Unreachable(Note),
+    Component(Note),
+}
+
+impl Action {
+    /// whether notes may have been changed, when action is done
+    pub fn modifies_notes(&self) -> bool {
+        matches!(
+            self,
+            Self::Open(..)
+                | Self::OpenXDG(..)
+                | Self::Link(..)
+                | Self::Unlink(..)
+                | Self::Rename(..)
+                | Self::Remove(..)
+                | Self::CreateLinkedFrom(..)
+                | Self::Surf(..)
+                | Self::Checkmark(..)
+                | Self::PushToStack(..)
+                | Self::SwitchToStack
+                | Self::Undo
+                | Self::Redo
+                | Self::EditAliases(..)
+                | Self::Merge(..)
+                | Self::Split(..)
+        )
+    }
 }
 
 pub struct Out {
@@ -57,7 +98,7 @@ impl Iteration {
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         items: Vec<Note>,
-        db: SqliteAsyncHandle,
+        db: DatabaseAsyncHandle,
         external_commands: ExternalCommands,
         surf_parsing: SurfParsing,
         preview_type: PreviewType,
@@ -66,6 +107,7 @@ impl Iteration {
         straight: bool,
         nested_threshold: usize,
         bindings_map: keymap::explore::Bindings,
+        previews: PreviewCache,
     ) -> Self {
         Self {
             items: Some(items),
@@ -78,6 +120,7 @@ impl Iteration {
             straight,
             nested_threshold,
             bindings_map,
+            previews,
         }
     }
     pub(crate) async fn run(mut self) -> anyhow::Result<Out> {
@@ -86,35 +129,26 @@ impl Iteration {
         let (tx, rx): (SkimItemSender, SkimItemReceiver) = unbounded();
 
         let db = self.db.clone();
-        let cloned = items.clone();
-
-        for mut note in cloned {
-            let db_double = db.clone();
-            let ext_double = self.external_commands.clone();
-            let surf_parsing = self.surf_parsing.clone();
-            let tx_double = tx.clone();
-            tokio::task::spawn(async move {
-                note.set_resources(DynResources {
-                    external_commands: ext_double,
-                    surf_parsing,
-                    preview_type: self.preview_type,
-                    preview_result: None,
-                });
-                note.prepare_preview(
-                    &db_double,
-                    self.md_static,
-                    self.color_scheme,
-                    self.straight,
-                    self.nested_threshold,
-                )
-                .await;
-
-                let result = tx_double.send(Arc::new(note));
-                if result.is_err() {
-                    // eat up errors on receiver closed
-                    // eprintln!("{}", format!("very bad {:?}", result).red());
-                }
+        let lazy_preview = LazyPreview {
+            cache: self.previews.clone(),
+            db: db.clone(),
+            md_static: self.md_static,
+            color_scheme: self.color_scheme,
+            straight: self.straight,
+            nested_threshold: self.nested_threshold,
+            runtime: Handle::current(),
+        };
+        // previews are rendered only for focused note, see `LazyPreview`
+        for mut note in items.clone() {
+            note.set_resources(DynResources {
+                external_commands: self.external_commands.clone(),
+                surf_parsing: self.surf_parsing.clone(),
+                preview_type: self.preview_type,
+                preview_result: None,
+                lazy_preview: Some(lazy_preview.clone()),
             });
+            // eat up errors on receiver closed
+            let _ = tx.send(Arc::new(note));
         }
         drop(tx);
 
@@ -367,6 +401,104 @@ impl Iteration {
                         Err(anyhow::anyhow!("no item selected"))
                     }
                 }
+                keymap::explore::Action::FullTextSearch => Ok(Out {
+                    action: Action::Search,
+                    next_items: items,
+                }),
+                keymap::explore::Action::FilterLinksByLabel => {
+                    if let Some(item) = selected_items.first() {
+                        Ok(Out {
+                            action: Action::FilterLinksByLabel(item.clone()),
+                            next_items: items,
+                        })
+                    } else {
+                        Err(anyhow::anyhow!("no item selected"))
+                    }
+                }
+                keymap::explore::Action::CycleListOrder => Ok(Out {
+                    action: Action::CycleListOrder,
+                    next_items: items,
+                }),
+                keymap::explore::Action::UndoLastChange => Ok(Out {
+                    action: Action::Undo,
+                    next_items: items,
+                }),
+                keymap::explore::Action::RedoLastChange => Ok(Out {
+                    action: Action::Redo,
+                    next_items: items,
+                }),
+                keymap::explore::Action::EditAliases => {
+                    if let Some(item) = selected_items.first() {
+                        Ok(Out {
+                            action: Action::EditAliases(item.clone()),
+                            next_items: items,
+                        })
+                    } else {
+                        Err(anyhow::anyhow!("no item selected"))
+                    }
+                }
+                keymap::explore::Action::FilterByProperty => Ok(Out {
+                    action: Action::FilterByProperty,
+                    next_items: items,
+                }),
+                keymap::explore::Action::MergeNote => {
+                    if let Some(item) = selected_items.first() {
+                        Ok(Out {
+                            action: Action::Merge(item.clone()),
+                            next_items: items,
+                        })
+                    } else {
+                        Err(anyhow::anyhow!("no item selected"))
+                    }
+                }
+                keymap::explore::Action::SplitNote => {
+                    if let Some(item) = selected_items.first() {
+                        Ok(Out {
+                            action: Action::Split(item.clone()),
+                            next_items: items,
+                        })
+                    } else {
+                        Err(anyhow::anyhow!("no item selected"))
+                    }
+                }
+                keymap::explore::Action::ShowPath => {
+                    if let Some(item) = selected_items.first() {
+                        Ok(Out {
+                            action: Action::Path(item.clone()),
+                            next_items: items,
+                        })
+                    } else {
+                        Err(anyhow::anyhow!("no item selected"))
+                    }
+                }
+                keymap::explore::Action::ShowOrphans => Ok(Out {
+                    action: Action::Orphans,
+                    next_items: items,
+                }),
+                keymap::explore::Action::ShowLeaves => Ok(Out {
+                    action: Action::Leaves,
+                    next_items: items,
+                }),
+                keymap::explore::Action::ShowUnreachable => {
+                    if let Some(item) = selected_items.first() {
+                        Ok(Out {
+                            action: Action::Unreachable(item.clone()),
+                            next_items: items,
+                        })
+                    } else {
+                        Err(anyhow::anyhow!("no item selected"))
+                    }
+                }
+                keymap::explore::Action::ShowComponent => {
+                    if let Some(item) = selected_items.first() {
+                        Ok(Out {
+                            action: Action::Component(item.clone()),
+                            next_items: items,
+                        })
+                    } else {
+                        Err(anyhow::anyhow!("no item selected"))
+                    }
+                }
                 keymap::explore::Action::CheckmarkNote => {
                     if let Some(item) = selected_items.first() {
                         Ok(Out {
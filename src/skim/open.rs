@@ -7,13 +7,13 @@ use skim::{
 
 use crate::{
     config::{color::ColorScheme, ExternalCommands, SurfParsing},
-    database::SqliteAsyncHandle,
+    database::DatabaseAsyncHandle,
     highlight::MarkdownStatic,
     note::{DynResources, Note, PreviewType},
 };
 
 pub(crate) struct Iteration {
-    db: SqliteAsyncHandle,
+    db: DatabaseAsyncHandle,
     items: Option<Vec<Note>>,
     multi: bool,
     preview_type: PreviewType,
@@ -32,7 +32,7 @@ impl Iteration {
     pub(crate) fn new(
         hint: String,
         items: Vec<Note>,
-        db: SqliteAsyncHandle,
+        db: DatabaseAsyncHandle,
         multi: bool,
         preview_type: PreviewType,
         external_commands: ExternalCommands,
@@ -75,6 +75,7 @@ impl Iteration {
                     surf_parsing,
                     preview_type: self.preview_type,
                     preview_result: None,
+                    lazy_preview: None,
                 });
                 note.prepare_preview(
                     &db_double,
@@ -7,7 +7,7 @@ use skim::{
 
 use crate::{
     config::{color::ColorScheme, keymap, ExternalCommands, SurfParsing},
-    database::SqliteAsyncHandle,
+    database::DatabaseAsyncHandle,
     highlight::MarkdownStatic,
     note::{DynResources, Note, PreviewType},
 };
@@ -19,10 +19,11 @@ pub enum Action {
     MoveTopmost(Note),
     SwapWithAbove(Note),
     SwapWithBelow(Note),
+    SelectStack,
 }
 
 pub(crate) struct Iteration {
-    db: SqliteAsyncHandle,
+    db: DatabaseAsyncHandle,
     input_items_from_explore: Vec<Note>,
     items: Option<Vec<Note>>,
     multi: bool,
@@ -45,7 +46,7 @@ impl Iteration {
         hint: String,
         input_items_from_explore: Vec<Note>,
         items: Vec<Note>,
-        db: SqliteAsyncHandle,
+        db: DatabaseAsyncHandle,
         multi: bool,
         preview_type: PreviewType,
         external_commands: ExternalCommands,
@@ -94,6 +95,7 @@ impl Iteration {
                     surf_parsing,
                     preview_type: self.preview_type,
                     preview_result: None,
+                    lazy_preview: None,
                 });
                 note.prepare_preview(
                     &db_double,
@@ -217,6 +219,7 @@ impl Iteration {
                         Err(anyhow::anyhow!("no item selected"))
                     }
                 }
+                keymap::stack::Action::SelectStack => Ok(Action::SelectStack),
                 keymap::stack::Action::DeselectAll => {
                     unreachable!("deselect_all must be unreachable");
                 }
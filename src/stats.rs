@@ -0,0 +1,204 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use serde::Serialize;
+
+use crate::{
+    config::{color::ColorScheme, SurfParsing},
+    database::DatabaseAsyncHandle,
+    graph::max_depth,
+    highlight::MarkdownStatic,
+    link::Link,
+    note::Note,
+    parse_cache::ParsedBody,
+};
+
+/// number of most linked-to notes, which are reported
+const MOST_LINKED: usize = 5;
+
+#[derive(Serialize)]
+pub struct Fan {
+    pub average: f64,
+    pub max: usize,
+}
+
+#[derive(Serialize)]
+pub struct LinkedNote {
+    pub name: String,
+    pub linked_by: usize,
+}
+
+#[derive(Serialize)]
+pub struct Tasks {
+    pub open: usize,
+    pub done: usize,
+}
+
+#[derive(Serialize)]
+pub struct Stats {
+    pub root: Option<String>,
+    pub direction: &'static str,
+    pub notes: usize,
+    pub tags: usize,
+    pub links: usize,
+    pub fan_in: Fan,
+    pub fan_out: Fan,
+    pub most_linked: Vec<LinkedNote>,
+    /// links from root to the farthest of notes, reachable from it
+    pub depth: Option<usize>,
+    pub destinations: BTreeMap<&'static str, usize>,
+    pub tasks: Tasks,
+}
+
+impl Stats {
+    /// statistics of all notes, or of notes reachable from `root` (itself included),
+    /// following links backwards, if not `straight`
+    pub async fn collect(
+        db: DatabaseAsyncHandle,
+        root: Option<&Note>,
+        straight: bool,
+        surf_parsing: &SurfParsing,
+        md_static: MarkdownStatic,
+        color_scheme: ColorScheme,
+    ) -> Result<Self, anyhow::Error> {
+        let notes = match root {
+            Some(root) => {
+                root.reachable_notes(db.clone(), md_static, color_scheme, straight, true)
+                    .await?
+            }
+            None => db.lock().await.list(md_static, color_scheme).await?,
+        };
+        let included = notes.iter().map(Note::name).collect::<HashSet<_>>();
+
+        let edges = db
+            .lock()
+            .await
+            .all_labelled_links()
+            .await?
+            .into_iter()
+            .filter(|link| included.contains(&link.from) && included.contains(&link.to))
+            .map(|link| match straight {
+                true => (link.from, link.to),
+                false => (link.to, link.from),
+            })
+            .collect::<Vec<_>>();
+
+        let mut fan_in: HashMap<&str, usize> = HashMap::new();
+        let mut fan_out: HashMap<&str, usize> = HashMap::new();
+        for (from, to) in &edges {
+            *fan_out.entry(from).or_default() += 1;
+            *fan_in.entry(to).or_default() += 1;
+        }
+        let fan = |counts: &HashMap<&str, usize>| Fan {
+            average: match notes.len() {
+                0 => 0.0,
+                len => edges.len() as f64 / len as f64,
+            },
+            max: counts.values().copied().max().unwrap_or(0),
+        };
+
+        let mut most_linked = fan_in
+            .iter()
+            .map(|(name, linked_by)| LinkedNote {
+                name: name.to_string(),
+                linked_by: *linked_by,
+            })
+            .collect::<Vec<_>>();
+        most_linked.sort_by(|a, b| b.linked_by.cmp(&a.linked_by).then(a.name.cmp(&b.name)));
+        most_linked.truncate(MOST_LINKED);
+
+        let mut destinations = BTreeMap::new();
+        let mut tasks = Tasks { open: 0, done: 0 };
+        for note in &notes {
+            let body = ParsedBody::of(note, surf_parsing, &db).await?;
+            for link in Link::resolve(note, &body.links, surf_parsing, color_scheme) {
+                *destinations.entry(link.link.kind()).or_default() += 1;
+            }
+            for task in &body.task_items {
+                match task.completed {
+                    true => tasks.done += 1,
+                    false => tasks.open += 1,
+                }
+            }
+        }
+
+        let tags = notes
+            .iter()
+            .filter(|note| note.file_path().is_none())
+            .count();
+        Ok(Self {
+            root: root.map(Note::name),
+            direction: if straight { "down" } else { "up" },
+            notes: notes.len() - tags,
+            tags,
+            links: edges.len(),
+            fan_in: fan(&fan_in),
+            fan_out: fan(&fan_out),
+            most_linked,
+            depth: root.map(|root| max_depth(&edges, &root.name())),
+            destinations,
+            tasks,
+        })
+    }
+
+    pub fn table(&self) -> String {
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_width(80)
+            .set_header(vec![
+                Cell::new("Metric").fg(Color::Blue),
+                Cell::new("Value").fg(Color::Blue),
+            ]);
+        let mut rows = vec![
+            ("notes".to_string(), self.notes.to_string()),
+            ("tags".to_string(), self.tags.to_string()),
+            ("links".to_string(), self.links.to_string()),
+            (
+                "fan-in avg / max".to_string(),
+                format!("{:.2} / {}", self.fan_in.average, self.fan_in.max),
+            ),
+            (
+                "fan-out avg / max".to_string(),
+                format!("{:.2} / {}", self.fan_out.average, self.fan_out.max),
+            ),
+        ];
+        if let Some(depth) = self.depth {
+            rows.push(("depth".to_string(), depth.to_string()));
+        }
+        for (kind, count) in &self.destinations {
+            rows.push((format!("destinations: {}", kind), count.to_string()));
+        }
+        rows.push(("tasks open".to_string(), self.tasks.open.to_string()));
+        rows.push(("tasks done".to_string(), self.tasks.done.to_string()));
+        for (metric, value) in rows {
+            table.add_row(vec![
+                Cell::new(metric).fg(Color::DarkMagenta),
+                Cell::new(value),
+            ]);
+        }
+
+        let mut string = format!("{}\n", table);
+        if !self.most_linked.is_empty() {
+            let mut table = Table::new();
+            table
+                .load_preset(UTF8_FULL)
+                .set_content_arrangement(ContentArrangement::Dynamic)
+                .set_width(80)
+                .set_header(vec![
+                    Cell::new("Most linked").fg(Color::Blue),
+                    Cell::new("Linked by").fg(Color::Blue),
+                ]);
+            for note in &self.most_linked {
+                table.add_row(vec![
+                    Cell::new(&note.name),
+                    Cell::new(note.linked_by.to_string()),
+                ]);
+            }
+            string.push_str(&format!("\n{}\n", table));
+        }
+        string
+    }
+}
@@ -2,12 +2,15 @@ use std::{fs, path::PathBuf};
 
 use colored::Colorize;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use syntect::easy::HighlightLines;
 
 use crate::{
     config::SurfParsing,
+    database::DatabaseAsyncHandle,
     highlight::{highlight, MarkdownStatic},
     note::Note,
+    parse_cache::ParsedBody,
 };
 mod skim_item;
 
@@ -25,35 +28,72 @@ pub struct TaskItem {
     pub next_index: Option<usize>,
 }
 
-impl TaskItem {
-    fn parse_capture(
-        value: (PathBuf, regex::Captures<'_>, usize),
-        highlighter: &mut HighlightLines,
-        md_static: MarkdownStatic,
-    ) -> Self {
-        let title = value.1.name("task_text").unwrap();
-        let checkmark = value.1.name("checkmark").unwrap();
+/// task item, as found in note's body, before its title is highlighted
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ParsedTaskItem {
+    pub title: String,
+    pub completed: bool,
+    pub nested_level: usize,
+    pub checkmark_offsets_in_string: std::ops::Range<usize>,
+}
+
+impl ParsedTaskItem {
+    fn parse_capture(capture: regex::Captures<'_>) -> Self {
+        let title = capture.name("task_text").unwrap();
+        let checkmark = capture.name("checkmark").unwrap();
         let completed = checkmark.as_str() == "x";
-        let whitespace = value.1.name("whitespace").unwrap().as_str();
+        let whitespace = capture.name("whitespace").unwrap().as_str();
         let nested_level = whitespace.len() / 2;
         let checkmark_offsets_in_string = checkmark.start()..checkmark.end();
 
-        let title_markdown = format!(
-            "{} {}",
-            highlight(title.as_str(), highlighter, md_static),
-            " ".truecolor(0, 0, 0)
-        );
         Self {
-            file_name: value.0,
-            nested_level,
-            completed,
             title: title.as_str().to_string(),
+            completed,
+            nested_level,
             checkmark_offsets_in_string,
-            self_index: value.2,
-            next_index: None,
-            title_markdown,
         }
     }
+
+    pub fn parse(input: &str, regex: &Regex) -> Vec<Self> {
+        regex
+            .captures_iter(input)
+            .map(Self::parse_capture)
+            .collect()
+    }
+}
+
+impl TaskItem {
+    /// task items of file, with titles of `parsed` highlighted
+    #[allow(clippy::ptr_arg)]
+    pub fn resolve(
+        file_name: &PathBuf,
+        parsed: &[ParsedTaskItem],
+        highlighter: &mut HighlightLines,
+        md_static: MarkdownStatic,
+    ) -> Vec<Self> {
+        parsed
+            .iter()
+            .enumerate()
+            .map(|(index, task)| {
+                let title_markdown = format!(
+                    "{} {}",
+                    highlight(&task.title, highlighter, md_static),
+                    " ".truecolor(0, 0, 0)
+                );
+                Self {
+                    file_name: file_name.clone(),
+                    nested_level: task.nested_level,
+                    completed: task.completed,
+                    title: task.title.clone(),
+                    checkmark_offsets_in_string: task.checkmark_offsets_in_string.clone(),
+                    self_index: index,
+                    next_index: None,
+                    title_markdown,
+                }
+            })
+            .collect()
+    }
+    #[cfg(test)]
     #[allow(clippy::ptr_arg)]
     fn parse_string(
         file_name: &PathBuf,
@@ -62,34 +102,24 @@ impl TaskItem {
         highlighter: &mut HighlightLines,
         md_static: MarkdownStatic,
     ) -> Vec<Self> {
-        let mut result = vec![];
-
-        for (index, capture) in regex.captures_iter(input).enumerate() {
-            result.push(Self::parse_capture(
-                (file_name.clone(), capture, index),
-                highlighter,
-                md_static,
-            ));
-        }
-        result
+        let parsed = ParsedTaskItem::parse(input, regex);
+        Self::resolve(file_name, &parsed, highlighter, md_static)
     }
-    pub fn parse(
+    pub async fn parse(
         note: &Note,
         surf: &SurfParsing,
-        highlighter: &mut HighlightLines,
         md_static: MarkdownStatic,
-    ) -> std::io::Result<Vec<Self>> {
+        db: &DatabaseAsyncHandle,
+    ) -> sqlx::Result<Vec<Self>> {
         if let Some(file_path) = note.file_path() {
-            let file_content = fs::read_to_string(file_path)?;
-            let result = Self::parse_string(
+            let body = ParsedBody::of(note, surf, db).await?;
+            let mut highlighter = HighlightLines::new(md_static.1, md_static.2);
+            Ok(Self::resolve(
                 file_path,
-                &file_content,
-                &surf.task_item_regex.0,
-                highlighter,
+                &body.task_items,
+                &mut highlighter,
                 md_static,
-            );
-
-            Ok(result)
+            ))
         } else {
             Ok(vec![])
         }
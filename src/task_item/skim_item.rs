@@ -59,7 +59,7 @@ impl TaskTreeWrapper {
 
 impl SkimItem for TaskTreeWrapper {
     /// The string to be used for matching (without color)
-    fn text(&self) -> Cow<str> {
+    fn text(&self) -> Cow<'_, str> {
         let input = match self.data.0.root {
             NoteTaskItemTerm::Note(..) => unreachable!("note"),
             NoteTaskItemTerm::Cycle(..) => unreachable!("cycle"),
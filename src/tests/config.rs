@@ -0,0 +1,45 @@
+use kdl::KdlDocument;
+
+use crate::config::{Config, Storage};
+
+fn parse(text: &str) -> miette::Result<Config> {
+    let doc: KdlDocument = text.parse().unwrap();
+    doc.get("world").unwrap().try_into()
+}
+
+/// configs, written before keys were added to sample config, take those from it
+#[test]
+fn test_config_without_added_keys() {
+    let added = [
+        "wikilink-regex",
+        "sync-wikilinks-on-open",
+        "storage ",
+        "label r#",
+        "full_text_search",
+        "show_component",
+        "select_stack",
+    ];
+    let sample = include_str!("../../config.kdl");
+    let older = sample
+        .lines()
+        .filter(|line| !added.iter().any(|key| line.trim_start().starts_with(key)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert_eq!(sample.lines().count(), older.lines().count() + added.len());
+
+    let config = parse(&older).unwrap();
+    assert_eq!(config.storage, Storage::Sqlite);
+    assert!(!config.surf_parsing.sync_wikilinks_on_open.0);
+    assert!(config.surf_parsing.wikilink_regex.0.is_match("[[name]]"));
+    assert_eq!(config.keymap.explore.full_text_search.combo, "alt-g");
+    assert_eq!(config.keymap.explore.show_component.combo, "alt-i");
+    assert_eq!(config.keymap.stack.select_stack.combo, "ctrl-s");
+
+    let missing = parse(&older.replace("task-item-regex", "task-item")).unwrap_err();
+    let described = format!("{:?}", missing);
+    assert!(
+        described.contains("no `world.surf-parsing.task-item-regex` in config"),
+        "{}",
+        described
+    );
+}
@@ -0,0 +1,418 @@
+use std::{fs, path::PathBuf};
+
+use crate::{
+    database::{DatabaseAsyncHandle, Files, LinkRecord},
+    note::Note,
+};
+
+use super::Fixture;
+
+#[tokio::test]
+async fn test_link_unlink_cycle() {
+    let fixture = Fixture::new();
+    for (backend, db) in fixture.backends().await {
+        fixture.tags(&db, &["a", "b", "c"]).await;
+        for (from, to) in [("a", "b"), ("b", "c"), ("c", "a")] {
+            db.lock()
+                .await
+                .insert_link(from, to, true, None)
+                .await
+                .unwrap();
+        }
+        assert_eq!(fixture.links_from(&db, "a").await, ["b"], "{}", backend);
+        assert_eq!(fixture.links_to(&db, "a").await, ["c"], "{}", backend);
+
+        let duplicate = db.lock().await.insert_link("a", "b", true, None).await;
+        assert!(duplicate.is_err(), "{}", backend);
+        let missing = db
+            .lock()
+            .await
+            .insert_link("a", "nowhere", true, None)
+            .await;
+        assert!(missing.is_err(), "{}", backend);
+
+        // reverse direction links `to` -> `from`
+        db.lock()
+            .await
+            .insert_link("a", "b", false, Some("part-of"))
+            .await
+            .unwrap();
+        let labelled = db
+            .lock()
+            .await
+            .find_labelled_links_from("b", fixture.md_static, fixture.color_scheme(), true)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(note, label)| (note.name(), label))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            labelled,
+            [
+                ("c".to_string(), None),
+                ("a".to_string(), Some("part-of".to_string()))
+            ],
+            "{}",
+            backend
+        );
+        assert_eq!(fixture.links_to(&db, "a").await, ["c", "b"], "{}", backend);
+
+        db.lock().await.remove_link("b", "c", true).await.unwrap();
+        assert_eq!(fixture.links_from(&db, "b").await, ["a"], "{}", backend);
+        // unlinking missing link is a no-op, which isn't journaled
+        db.lock().await.remove_link("b", "c", true).await.unwrap();
+        let journal = db.lock().await.journal(1).await.unwrap();
+        assert_eq!(
+            journal[0].operation.to_string(),
+            "unlink `b` -> `c`",
+            "{}",
+            backend
+        );
+
+        db.lock().await.undo().await.unwrap();
+        assert_eq!(
+            fixture.links_from(&db, "b").await,
+            ["c", "a"],
+            "{}",
+            backend
+        );
+        db.lock().await.redo().await.unwrap();
+        assert_eq!(fixture.links_from(&db, "b").await, ["a"], "{}", backend);
+    }
+}
+
+#[tokio::test]
+async fn test_rename_cascades() {
+    let fixture = Fixture::new();
+    for (backend, db) in fixture.backends().await {
+        fixture.tags(&db, &["a", "b", "c"]).await;
+        {
+            let mut lock = db.lock().await;
+            lock.insert_link("a", "b", true, None).await.unwrap();
+            lock.insert_link("c", "b", true, None).await.unwrap();
+            lock.insert_link("b", "a", true, Some("see-also"))
+                .await
+                .unwrap();
+            lock.insert_link("b", "b", true, None).await.unwrap();
+            lock.push_note_to_stack("GLOBAL", "b").await.unwrap();
+            lock.set_aliases("b", &["bee".to_string()]).await.unwrap();
+            lock.set_property("b", "status", Some("draft"))
+                .await
+                .unwrap();
+        }
+
+        let b = fixture.note(&db, "b").await;
+        let taken = db.lock().await.rename_note(&b, "c", &[]).await;
+        assert!(taken.is_err(), "{}", backend);
+        db.lock().await.rename_note(&b, "beta", &[]).await.unwrap();
+
+        assert_eq!(fixture.links_from(&db, "a").await, ["beta"], "{}", backend);
+        assert_eq!(fixture.links_from(&db, "c").await, ["beta"], "{}", backend);
+        assert_eq!(
+            fixture.links_from(&db, "beta").await,
+            ["beta", "a"],
+            "{}",
+            backend
+        );
+        assert_eq!(
+            fixture.links_to(&db, "beta").await,
+            ["c", "beta", "a"],
+            "{}",
+            backend
+        );
+        assert_eq!(fixture.stack(&db, "GLOBAL").await, ["beta"], "{}", backend);
+        assert_eq!(fixture.note(&db, "bee").await.name(), "beta", "{}", backend);
+        let properties = db.lock().await.properties("beta").await.unwrap();
+        assert_eq!(properties["status"], "draft", "{}", backend);
+        let names = super::names(
+            &db.lock()
+                .await
+                .list(fixture.md_static, fixture.color_scheme())
+                .await
+                .unwrap(),
+        );
+        assert_eq!(names, ["c", "beta", "a"], "{}", backend);
+
+        db.lock().await.undo().await.unwrap();
+        assert_eq!(fixture.links_from(&db, "a").await, ["b"], "{}", backend);
+        assert_eq!(fixture.stack(&db, "GLOBAL").await, ["b"], "{}", backend);
+    }
+}
+
+#[tokio::test]
+async fn test_synced_link_journal() {
+    let fixture = Fixture::new();
+    for (backend, db) in fixture.backends().await {
+        fixture.tags(&db, &["a", "b"]).await;
+        db.lock().await.insert_synced_link("a", "b").await.unwrap();
+        let synced = || async { db.lock().await.find_synced_links_from("a").await.unwrap() };
+        assert_eq!(synced().await, ["b"], "{}", backend);
+
+        db.lock().await.undo().await.unwrap();
+        assert!(fixture.links_from(&db, "a").await.is_empty(), "{}", backend);
+        db.lock().await.redo().await.unwrap();
+        assert_eq!(synced().await, ["b"], "{}", backend);
+
+        // pruning a synced link is undone into a synced link again
+        db.lock().await.remove_link("a", "b", true).await.unwrap();
+        assert!(synced().await.is_empty(), "{}", backend);
+        let journal = db.lock().await.journal(1).await.unwrap();
+        assert_eq!(
+            journal[0].operation.to_string(),
+            "unlink synced `a` -> `b`",
+            "{}",
+            backend
+        );
+        db.lock().await.undo().await.unwrap();
+        assert_eq!(synced().await, ["b"], "{}", backend);
+    }
+}
+
+#[tokio::test]
+async fn test_remove_note_cascades() {
+    let fixture = Fixture::new();
+    for (backend, db) in fixture.backends().await {
+        fixture.tags(&db, &["a", "b", "c"]).await;
+        {
+            let mut lock = db.lock().await;
+            lock.insert_link("a", "b", true, Some("part-of"))
+                .await
+                .unwrap();
+            lock.insert_link("b", "c", true, None).await.unwrap();
+            for note in ["a", "b", "c"] {
+                lock.push_note_to_stack("GLOBAL", note).await.unwrap();
+            }
+        }
+
+        let b = fixture.note(&db, "b").await;
+        db.lock().await.remove_note(&b).await.unwrap();
+        assert!(fixture.links_from(&db, "a").await.is_empty(), "{}", backend);
+        assert!(fixture.links_to(&db, "c").await.is_empty(), "{}", backend);
+        assert_eq!(
+            fixture.stack(&db, "GLOBAL").await,
+            ["c", "a"],
+            "{}",
+            backend
+        );
+        let trash = db.lock().await.list_trash().await.unwrap();
+        assert_eq!(trash.len(), 1, "{}", backend);
+        assert_eq!(trash[0].snapshot.links.len(), 2, "{}", backend);
+
+        db.lock().await.undo().await.unwrap();
+        assert_eq!(fixture.links_from(&db, "a").await, ["b"], "{}", backend);
+        assert_eq!(fixture.links_from(&db, "b").await, ["c"], "{}", backend);
+        assert_eq!(
+            fixture.stack(&db, "GLOBAL").await,
+            ["c", "b", "a"],
+            "{}",
+            backend
+        );
+        assert!(
+            db.lock().await.list_trash().await.unwrap().is_empty(),
+            "{}",
+            backend
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_stack_reorders() {
+    let fixture = Fixture::new();
+    for (backend, db) in fixture.backends().await {
+        fixture.tags(&db, &["a", "b", "c"]).await;
+        for note in ["a", "b", "c"] {
+            db.lock()
+                .await
+                .push_note_to_stack("GLOBAL", note)
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            fixture.stack(&db, "GLOBAL").await,
+            ["c", "b", "a"],
+            "{}",
+            backend
+        );
+        let pushed_twice = db.lock().await.push_note_to_stack("GLOBAL", "a").await;
+        assert!(pushed_twice.is_err(), "{}", backend);
+
+        db.lock()
+            .await
+            .swap_with_above("GLOBAL", "c")
+            .await
+            .unwrap();
+        assert_eq!(
+            fixture.stack(&db, "GLOBAL").await,
+            ["b", "c", "a"],
+            "{}",
+            backend
+        );
+        // bottommost note has nothing to swap with
+        db.lock()
+            .await
+            .swap_with_above("GLOBAL", "a")
+            .await
+            .unwrap();
+        assert_eq!(
+            fixture.stack(&db, "GLOBAL").await,
+            ["b", "c", "a"],
+            "{}",
+            backend
+        );
+        db.lock()
+            .await
+            .swap_with_below("GLOBAL", "c")
+            .await
+            .unwrap();
+        assert_eq!(
+            fixture.stack(&db, "GLOBAL").await,
+            ["c", "b", "a"],
+            "{}",
+            backend
+        );
+        // topmost note has nothing to swap with
+        db.lock()
+            .await
+            .swap_with_below("GLOBAL", "c")
+            .await
+            .unwrap();
+        assert_eq!(
+            fixture.stack(&db, "GLOBAL").await,
+            ["c", "b", "a"],
+            "{}",
+            backend
+        );
+        db.lock()
+            .await
+            .move_to_topmost("GLOBAL", "a")
+            .await
+            .unwrap();
+        assert_eq!(
+            fixture.stack(&db, "GLOBAL").await,
+            ["a", "c", "b"],
+            "{}",
+            backend
+        );
+        db.lock()
+            .await
+            .pop_note_from_stack("GLOBAL", "c")
+            .await
+            .unwrap();
+        assert_eq!(
+            fixture.stack(&db, "GLOBAL").await,
+            ["a", "b"],
+            "{}",
+            backend
+        );
+
+        let missing = db.lock().await.pop_note_from_stack("GLOBAL", "c").await;
+        assert!(missing.is_err(), "{}", backend);
+        let missing = db.lock().await.swap_with_above("GLOBAL", "c").await;
+        assert!(missing.is_err(), "{}", backend);
+
+        // no-op swaps aren't journaled
+        db.lock().await.undo().await.unwrap();
+        assert_eq!(
+            fixture.stack(&db, "GLOBAL").await,
+            ["a", "c", "b"],
+            "{}",
+            backend
+        );
+        db.lock().await.undo().await.unwrap();
+        assert_eq!(
+            fixture.stack(&db, "GLOBAL").await,
+            ["c", "b", "a"],
+            "{}",
+            backend
+        );
+        db.lock().await.undo().await.unwrap();
+        assert_eq!(
+            fixture.stack(&db, "GLOBAL").await,
+            ["b", "c", "a"],
+            "{}",
+            backend
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_stacks_lifecycle() {
+    let fixture = Fixture::new();
+    for (backend, db) in fixture.backends().await {
+        fixture.tags(&db, &["a", "b"]).await;
+        {
+            let mut lock = db.lock().await;
+            lock.create_stack("work").await.unwrap();
+            assert!(lock.create_stack("work").await.is_err(), "{}", backend);
+            lock.push_note_to_stack("work", "a").await.unwrap();
+            lock.push_note_to_stack("work", "b").await.unwrap();
+            lock.rename_stack("work", "projects").await.unwrap();
+            assert_eq!(
+                lock.list_stacks().await.unwrap(),
+                ["GLOBAL", "projects"],
+                "{}",
+                backend
+            );
+        }
+        assert_eq!(
+            fixture.stack(&db, "projects").await,
+            ["b", "a"],
+            "{}",
+            backend
+        );
+
+        db.lock().await.remove_stack("projects").await.unwrap();
+        assert_eq!(
+            db.lock().await.list_stacks().await.unwrap(),
+            ["GLOBAL"],
+            "{}",
+            backend
+        );
+        db.lock().await.undo().await.unwrap();
+        assert_eq!(
+            fixture.stack(&db, "projects").await,
+            ["b", "a"],
+            "{}",
+            backend
+        );
+    }
+}
+
+/// body file of a linked note can be deleted by hand, which plain files storage takes for
+/// removal of the note, bypassing journal
+#[tokio::test]
+async fn test_undo_remove_skips_links_to_missing_notes() {
+    let fixture = Fixture::new();
+    let root = fixture.dir("notes");
+    let storage = root.join(".mds");
+
+    let db: DatabaseAsyncHandle = Files::new(true, &storage).unwrap();
+    fs::write(root.join("b.md"), "# b\n").unwrap();
+    let b = Note::new(
+        "b".to_string(),
+        Some(PathBuf::from("./b.md")),
+        fixture.color_scheme(),
+    );
+    db.lock().await.save(&b).await.unwrap();
+    fixture.tags(&db, &["a", "c"]).await;
+    {
+        let mut lock = db.lock().await;
+        lock.insert_link("a", "b", true, None).await.unwrap();
+        lock.insert_link("a", "c", true, None).await.unwrap();
+    }
+    let a = fixture.note(&db, "a").await;
+    db.lock().await.remove_note(&a).await.unwrap();
+    fs::remove_file(root.join("b.md")).unwrap();
+
+    let db: DatabaseAsyncHandle = Files::new(false, &storage).unwrap();
+    let (_, report) = db.lock().await.undo().await.unwrap().unwrap();
+    assert_eq!(
+        report.skipped_links,
+        [LinkRecord {
+            from: "a".to_string(),
+            to: "b".to_string(),
+            label: None,
+        }]
+    );
+    assert_eq!(fixture.links_from(&db, "a").await, ["c"]);
+}
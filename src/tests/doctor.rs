@@ -0,0 +1,55 @@
+use std::{fs, path::PathBuf};
+
+use crate::{
+    commands::doctor,
+    database::{DatabaseAsyncHandle, Files},
+    front_matter,
+    note::{parse_heading_name, Note},
+};
+
+use super::Fixture;
+
+/// plain files storage keeps notes in front matter of bodies, which doctor must leave intact
+#[tokio::test]
+async fn test_doctor_fix_keeps_front_matter() {
+    let fixture = Fixture::new();
+    let root = fixture.dir("notes");
+    let storage = root.join(".mds");
+
+    let db: DatabaseAsyncHandle = Files::new(true, &storage).unwrap();
+    fs::write(root.join("a.md"), "# stale\n\ntext\n").unwrap();
+    fs::write(root.join("b.md"), "# b\n").unwrap();
+    let a = Note::new(
+        "a".to_string(),
+        Some(PathBuf::from("./a.md")),
+        fixture.color_scheme(),
+    );
+    db.lock().await.save(&a).await.unwrap();
+    fixture.tags(&db, &["t"]).await;
+    {
+        let mut lock = db.lock().await;
+        lock.insert_link("a", "t", true, Some("part-of"))
+            .await
+            .unwrap();
+        lock.set_property("a", "status", Some("draft"))
+            .await
+            .unwrap();
+    }
+
+    doctor::exec(db, true, &root, fixture.md_static, fixture.color_scheme())
+        .await
+        .unwrap();
+
+    let content = fs::read_to_string(root.join("a.md")).unwrap();
+    assert!(content.starts_with("---\n"), "{}", content);
+    let body = front_matter::read_body(root.join("a.md")).unwrap();
+    assert_eq!(parse_heading_name(&body).as_deref(), Some("a"));
+    assert!(body.ends_with("\n\ntext\n"), "{}", body);
+
+    let db: DatabaseAsyncHandle = Files::new(false, &storage).unwrap();
+    assert_eq!(fixture.links_from(&db, "a").await, ["t"]);
+    let properties = db.lock().await.properties("a").await.unwrap();
+    assert_eq!(properties.get("status").map(String::as_str), Some("draft"));
+    let adopted = fixture.note(&db, "b").await;
+    assert_eq!(adopted.file_path(), Some(&PathBuf::from("./b.md")));
+}
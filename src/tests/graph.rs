@@ -0,0 +1,307 @@
+use std::collections::{BTreeSet, HashSet};
+
+use crate::graph::{
+    cyclic_components, elementary_cycles, shortest_path, simple_paths, sinks, sources, unreachable,
+};
+
+use super::{strip_colors, Fixture};
+
+#[tokio::test]
+async fn test_reachable_notes() {
+    let fixture = Fixture::new();
+    for (backend, db) in fixture.backends().await {
+        fixture.tags(&db, &["a", "b", "c", "d", "e"]).await;
+        for (from, to) in [("a", "b"), ("b", "c"), ("c", "a"), ("e", "a")] {
+            db.lock()
+                .await
+                .insert_link(from, to, true, None)
+                .await
+                .unwrap();
+        }
+        let a = fixture.note(&db, "a").await;
+        let reachable = |notes: Vec<crate::note::Note>| {
+            notes
+                .iter()
+                .map(|note| note.name())
+                .collect::<BTreeSet<_>>()
+        };
+        let color_scheme = fixture.color_scheme();
+
+        let down = a
+            .reachable_notes(db.clone(), fixture.md_static, color_scheme, true, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            reachable(down),
+            BTreeSet::from(["b".into(), "c".into()]),
+            "{}",
+            backend
+        );
+
+        let up = a
+            .reachable_notes(db.clone(), fixture.md_static, color_scheme, false, true)
+            .await
+            .unwrap();
+        assert_eq!(
+            reachable(up),
+            BTreeSet::from(["a".into(), "b".into(), "c".into(), "e".into()]),
+            "{}",
+            backend
+        );
+
+        let near = a
+            .reachable_notes_within(
+                db.clone(),
+                fixture.md_static,
+                color_scheme,
+                true,
+                true,
+                Some(1),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            reachable(near),
+            BTreeSet::from(["a".into(), "b".into()]),
+            "{}",
+            backend
+        );
+
+        let d = fixture.note(&db, "d").await;
+        let isolated = d
+            .reachable_notes(db.clone(), fixture.md_static, color_scheme, true, false)
+            .await
+            .unwrap();
+        assert!(isolated.is_empty(), "{}", backend);
+    }
+}
+
+#[tokio::test]
+async fn test_construct_link_term_tree() {
+    let fixture = Fixture::new();
+    for (backend, db) in fixture.backends().await {
+        fixture.tags(&db, &["root", "a", "b", "c"]).await;
+        for (from, to, label) in [
+            ("root", "a", Some("part-of")),
+            ("root", "b", None),
+            ("a", "b", None),
+            ("a", "c", Some("see-also")),
+            ("b", "root", None),
+        ] {
+            db.lock()
+                .await
+                .insert_link(from, to, true, label)
+                .await
+                .unwrap();
+        }
+        let root = fixture.note(&db, "root").await;
+
+        let (tree, reachable) = root
+            .construct_link_term_tree(
+                0,
+                1,
+                HashSet::new(),
+                fixture.config.surf_parsing.clone(),
+                db.clone(),
+                fixture.md_static,
+                fixture.color_scheme(),
+            )
+            .await
+            .unwrap();
+        let expected = "\
+root
+├── [part-of] a
+│   ├── b
+│   │   └── ⟳ root
+│   └── [see-also] c
+└── ⟳ b
+";
+        assert_eq!(strip_colors(&tree.to_string()), expected, "{}", backend);
+        assert_eq!(reachable.len(), 4, "{}", backend);
+
+        let (tree, _reachable) = root
+            .construct_link_term_tree_up(
+                0,
+                1,
+                HashSet::new(),
+                fixture.config.surf_parsing.clone(),
+                db.clone(),
+                fixture.md_static,
+                fixture.color_scheme(),
+            )
+            .await
+            .unwrap();
+        let tree = strip_colors(&tree.to_string());
+        assert!(tree.ends_with("root\n"), "{}: {}", backend, tree);
+        assert!(tree.contains("⟳ root"), "{}: {}", backend, tree);
+    }
+}
+
+#[test]
+fn test_paths() {
+    let edges = [("a", "b"), ("b", "c"), ("a", "c"), ("c", "d"), ("d", "a")]
+        .iter()
+        .map(|(from, to)| (from.to_string(), to.to_string()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        shortest_path(&edges, "a", "d"),
+        Some(vec!["a".to_string(), "c".into(), "d".into()])
+    );
+    assert_eq!(shortest_path(&edges, "a", "a"), Some(vec!["a".to_string()]));
+    assert_eq!(shortest_path(&edges, "d", "e"), None);
+
+    let paths = simple_paths(&edges, "a", "d", 3);
+    assert_eq!(
+        paths,
+        [vec!["a", "c", "d"], vec!["a", "b", "c", "d"]]
+            .map(|path| path.iter().map(|name| name.to_string()).collect::<Vec<_>>())
+    );
+    assert_eq!(simple_paths(&edges, "a", "d", 2).len(), 1);
+    assert!(simple_paths(&edges, "a", "d", 1).is_empty());
+}
+
+#[test]
+fn test_orphans_leaves_unreachable() {
+    let nodes = ["root", "a", "b", "c", "d"]
+        .map(|name| name.to_string())
+        .to_vec();
+    let edges = [
+        ("root", "a"),
+        ("a", "b"),
+        ("b", "a"),
+        ("c", "c"),
+        ("d", "b"),
+    ]
+    .iter()
+    .map(|(from, to)| (from.to_string(), to.to_string()))
+    .collect::<Vec<_>>();
+
+    // a link to itself doesn't make a note linked
+    assert_eq!(sources(&nodes, &edges), ["root", "c", "d"]);
+    assert_eq!(sinks(&nodes, &edges), ["c"]);
+    assert_eq!(unreachable(&nodes, &edges, "root"), ["c", "d"]);
+    assert_eq!(unreachable(&nodes, &edges, "a"), ["root", "c", "d"]);
+}
+
+#[test]
+fn test_cycles() {
+    let nodes = ["a", "b", "c", "d", "e"]
+        .map(|name| name.to_string())
+        .to_vec();
+    let edges = [
+        ("a", "b"),
+        ("b", "c"),
+        ("c", "a"),
+        ("b", "a"),
+        ("c", "d"),
+        ("e", "e"),
+    ]
+    .iter()
+    .map(|(from, to)| (from.to_string(), to.to_string()))
+    .collect::<Vec<_>>();
+
+    assert_eq!(
+        cyclic_components(&nodes, &edges),
+        [vec!["a", "b", "c"], vec!["e"]]
+    );
+    assert_eq!(
+        elementary_cycles(&nodes, &edges, 10),
+        (
+            vec![
+                vec!["e".to_string()],
+                vec!["a".into(), "b".into()],
+                vec!["a".into(), "b".into(), "c".into()]
+            ],
+            false
+        )
+    );
+    let (cycles, truncated) = elementary_cycles(&nodes, &edges, 2);
+    assert_eq!((cycles.len(), truncated), (2, true));
+}
+
+#[tokio::test]
+async fn test_reachable_subgraph() {
+    let fixture = Fixture::new();
+    for (backend, db) in fixture.backends().await {
+        fixture.tags(&db, &["a", "b", "c", "d", "e"]).await;
+        for (from, to, label) in [
+            ("a", "b", Some("part-of")),
+            ("a", "c", None),
+            ("b", "d", None),
+            ("c", "d", None),
+            ("d", "a", None),
+            ("e", "a", None),
+        ] {
+            db.lock()
+                .await
+                .insert_link(from, to, true, label)
+                .await
+                .unwrap();
+        }
+        let (md_static, color_scheme) = (fixture.md_static, fixture.color_scheme());
+        let subgraph = |straight, depth| {
+            let db = db.clone();
+            async move {
+                db.lock()
+                    .await
+                    .reachable_subgraph("a", md_static, color_scheme, straight, depth)
+                    .await
+                    .unwrap()
+            }
+        };
+        let names = |subgraph: &crate::database::Subgraph| {
+            subgraph.notes.keys().cloned().collect::<BTreeSet<_>>()
+        };
+
+        let down = subgraph(true, None).await;
+        assert_eq!(
+            names(&down),
+            BTreeSet::from(["a".into(), "b".into(), "c".into(), "d".into()]),
+            "{}",
+            backend
+        );
+        // ordered as `find_labelled_links_from`
+        assert_eq!(
+            down.links["a"],
+            [
+                ("c".to_string(), None),
+                ("b".into(), Some("part-of".into()))
+            ],
+            "{}",
+            backend
+        );
+        assert_eq!(down.links["d"], [("a".to_string(), None)], "{}", backend);
+
+        let near = subgraph(true, Some(1)).await;
+        assert_eq!(
+            names(&near),
+            BTreeSet::from(["a".into(), "b".into(), "c".into()]),
+            "{}",
+            backend
+        );
+        // links of the farthest notes aren't followed
+        assert!(near.labelled_links_from("b").is_empty(), "{}", backend);
+
+        let up = subgraph(false, Some(1)).await;
+        assert_eq!(
+            names(&up),
+            BTreeSet::from(["a".into(), "d".into(), "e".into()]),
+            "{}",
+            backend
+        );
+        assert_eq!(
+            up.links["a"],
+            [("e".to_string(), None), ("d".into(), None)],
+            "{}",
+            backend
+        );
+
+        let missing = db
+            .lock()
+            .await
+            .reachable_subgraph("z", fixture.md_static, fixture.color_scheme(), true, None)
+            .await;
+        assert!(missing.is_err(), "{}", backend);
+    }
+}
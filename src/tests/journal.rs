@@ -0,0 +1,175 @@
+//! mutations, undone and redone, leave notes, links, stacks and bodies as they were
+use std::{fs, path::Path};
+
+use crate::{
+    database::{BodyRewrite, DatabaseAsyncHandle},
+    front_matter,
+    note::Note,
+};
+
+use super::Fixture;
+
+async fn body_note(fixture: &Fixture, db: &DatabaseAsyncHandle, file_path: &Path, body: &str) {
+    fs::write(file_path, body).unwrap();
+    let name = file_path.file_stem().unwrap().to_string_lossy().to_string();
+    let note = Note::new(name, Some(file_path.to_path_buf()), fixture.color_scheme());
+    db.lock().await.save(&note).await.unwrap();
+}
+
+fn read_body(file_path: &Path) -> String {
+    front_matter::read_body(file_path).unwrap()
+}
+
+#[tokio::test]
+async fn test_merge_round_trip() {
+    let fixture = Fixture::new();
+    for (backend, db) in fixture.backends().await {
+        let root = fixture.dir(backend);
+        let (source, target) = (root.join("s.md"), root.join("t.md"));
+        body_note(&fixture, &db, &source, "# s\n\nsource text\n").await;
+        body_note(&fixture, &db, &target, "# t\n\ntarget text\n").await;
+        fixture.tags(&db, &["x"]).await;
+        {
+            let mut lock = db.lock().await;
+            lock.insert_link("s", "x", true, Some("part-of"))
+                .await
+                .unwrap();
+            lock.insert_link("x", "s", true, None).await.unwrap();
+            lock.push_note_to_stack("GLOBAL", "s").await.unwrap();
+            lock.set_aliases("s", &["ess".to_string()]).await.unwrap();
+            lock.set_property("s", "status", Some("draft"))
+                .await
+                .unwrap();
+        }
+        let before = fixture.graph(&db).await;
+
+        let rewrite = BodyRewrite {
+            filename: target.to_string_lossy().to_string(),
+            before: read_body(&target),
+            after: "# t\n\ntarget text\n\nsource text\n".to_string(),
+        };
+        front_matter::write_body(&target, &rewrite.after).unwrap();
+        db.lock()
+            .await
+            .merge_notes("s", "t", Some(&rewrite))
+            .await
+            .unwrap();
+        let merged = fixture.graph(&db).await;
+        assert_eq!(fixture.links_from(&db, "t").await, ["x"], "{}", backend);
+        assert_eq!(fixture.note(&db, "ess").await.name(), "t", "{}", backend);
+
+        db.lock().await.undo().await.unwrap();
+        assert_eq!(fixture.graph(&db).await, before, "{}", backend);
+        assert_eq!(read_body(&source), "# s\n\nsource text\n", "{}", backend);
+        assert_eq!(read_body(&target), rewrite.before, "{}", backend);
+
+        db.lock().await.redo().await.unwrap();
+        assert_eq!(fixture.graph(&db).await, merged, "{}", backend);
+        assert!(!source.exists(), "{}", backend);
+        assert_eq!(read_body(&target), rewrite.after, "{}", backend);
+
+        // text of a body, edited since, is kept and reported
+        front_matter::write_body(&target, "edited\n").unwrap();
+        let (_, report) = db.lock().await.undo().await.unwrap().unwrap();
+        assert_eq!(report.edited_files, [rewrite.filename], "{}", backend);
+        assert_eq!(read_body(&target), "edited\n", "{}", backend);
+        assert_eq!(fixture.graph(&db).await, before, "{}", backend);
+    }
+}
+
+#[tokio::test]
+async fn test_split_round_trip() {
+    let fixture = Fixture::new();
+    for (backend, db) in fixture.backends().await {
+        let root = fixture.dir(backend);
+        let (parent, child) = (root.join("p.md"), root.join("c.md"));
+        let body = "# p\n\n## c\n\nsection\n";
+        body_note(&fixture, &db, &parent, body).await;
+        let before = fixture.graph(&db).await;
+
+        fs::write(&child, "# c\n\nsection\n").unwrap();
+        let rewrite = BodyRewrite {
+            filename: parent.to_string_lossy().to_string(),
+            before: body.to_string(),
+            after: format!("# p\n\n[c]({})\n", child.display()),
+        };
+        front_matter::write_body(&parent, &rewrite.after).unwrap();
+        let children = [Note::new(
+            "c".to_string(),
+            Some(child.clone()),
+            fixture.color_scheme(),
+        )];
+        db.lock()
+            .await
+            .split_note("p", &children, Some(&rewrite))
+            .await
+            .unwrap();
+        let split = fixture.graph(&db).await;
+        assert_eq!(fixture.links_from(&db, "p").await, ["c"], "{}", backend);
+
+        db.lock().await.undo().await.unwrap();
+        assert_eq!(fixture.graph(&db).await, before, "{}", backend);
+        assert!(!child.exists(), "{}", backend);
+        assert_eq!(read_body(&parent), body, "{}", backend);
+
+        db.lock().await.redo().await.unwrap();
+        assert_eq!(fixture.graph(&db).await, split, "{}", backend);
+        assert_eq!(read_body(&child), "# c\n\nsection\n", "{}", backend);
+        assert_eq!(read_body(&parent), rewrite.after, "{}", backend);
+    }
+}
+
+#[tokio::test]
+async fn test_remove_round_trip() {
+    let fixture = Fixture::new();
+    for (backend, db) in fixture.backends().await {
+        let root = fixture.dir(backend);
+        let file_path = root.join("a.md");
+        body_note(&fixture, &db, &file_path, "# a\n\ntext\n").await;
+        fixture.tags(&db, &["b"]).await;
+        {
+            let mut lock = db.lock().await;
+            lock.insert_link("a", "b", true, Some("part-of"))
+                .await
+                .unwrap();
+            lock.insert_link("b", "a", true, None).await.unwrap();
+            lock.push_note_to_stack("GLOBAL", "b").await.unwrap();
+            lock.push_note_to_stack("GLOBAL", "a").await.unwrap();
+        }
+        let before = fixture.graph(&db).await;
+
+        let a = fixture.note(&db, "a").await;
+        db.lock().await.remove_note(&a).await.unwrap();
+        let removed = fixture.graph(&db).await;
+        assert!(!file_path.exists(), "{}", backend);
+
+        db.lock().await.undo().await.unwrap();
+        assert_eq!(fixture.graph(&db).await, before, "{}", backend);
+        assert_eq!(read_body(&file_path), "# a\n\ntext\n", "{}", backend);
+
+        db.lock().await.redo().await.unwrap();
+        assert_eq!(fixture.graph(&db).await, removed, "{}", backend);
+        assert!(!file_path.exists(), "{}", backend);
+
+        // trash restore is journaled as creation of the note
+        let trash = db.lock().await.list_trash().await.unwrap();
+        let skipped = db
+            .lock()
+            .await
+            .restore_from_trash(trash[0].id)
+            .await
+            .unwrap();
+        assert!(skipped.is_empty(), "{}", backend);
+        let restored = fixture.graph(&db).await;
+        assert_eq!(fixture.links_from(&db, "a").await, ["b"], "{}", backend);
+        assert_eq!(read_body(&file_path), "# a\n\ntext\n", "{}", backend);
+
+        db.lock().await.undo().await.unwrap();
+        assert_eq!(fixture.graph(&db).await, removed, "{}", backend);
+        assert!(!file_path.exists(), "{}", backend);
+
+        db.lock().await.redo().await.unwrap();
+        assert_eq!(fixture.graph(&db).await, restored, "{}", backend);
+        assert_eq!(read_body(&file_path), "# a\n\ntext\n", "{}", backend);
+    }
+}
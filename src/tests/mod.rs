@@ -0,0 +1,134 @@
+//! tests of commands' building blocks against in-memory, sqlite and plain files databases,
+//! without skim
+use std::{fs, path::PathBuf, sync::Arc};
+
+use async_std::sync::Mutex;
+use kdl::KdlDocument;
+use regex::Regex;
+use tempfile::TempDir;
+
+use crate::{
+    config::{color::ColorScheme, Config},
+    database::{DatabaseAsyncHandle, Dump, Files, Memory, Sqlite},
+    highlight::{static_markdown_syntax, MarkdownStatic},
+    note::Note,
+};
+
+mod config;
+mod database;
+mod doctor;
+mod graph;
+mod journal;
+mod parse_cache;
+mod stats;
+
+pub(crate) struct Fixture {
+    pub config: Config,
+    pub md_static: MarkdownStatic,
+    /// removed along with everything in it, once a test is over
+    temp: TempDir,
+}
+
+impl Fixture {
+    /// sample config from repository root
+    pub fn new() -> Self {
+        let doc: KdlDocument = include_str!("../../config.kdl").parse().unwrap();
+        let config = doc.get("world").unwrap().try_into().unwrap();
+        Self {
+            config,
+            md_static: static_markdown_syntax(None),
+            temp: TempDir::new().unwrap(),
+        }
+    }
+
+    /// new dir under fixture's temporary one
+    pub fn dir(&self, name: &str) -> PathBuf {
+        let dir = self.temp.path().join(name);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    pub fn color_scheme(&self) -> ColorScheme {
+        self.config.color.elements
+    }
+
+    /// sqlite database in a temporary file, an in-memory one and plain files storage in a
+    /// temporary dir, every test runs against all of them to hold the others to behaviour
+    /// of the first
+    pub async fn backends(&self) -> Vec<(&'static str, DatabaseAsyncHandle)> {
+        // notes' work dir of each backend, where bodies get trashed, is named after it
+        let sqlite: DatabaseAsyncHandle = Sqlite::new(true, self.dir("sqlite").join(".sqlite"))
+            .await
+            .unwrap();
+        let memory: DatabaseAsyncHandle = Arc::new(Mutex::new(Memory::new(self.dir("memory"))));
+        let files: DatabaseAsyncHandle = Files::new(true, self.dir("files").join(".mds")).unwrap();
+        vec![("sqlite", sqlite), ("memory", memory), ("files", files)]
+    }
+
+    pub async fn tags(&self, db: &DatabaseAsyncHandle, names: &[&str]) {
+        for name in names {
+            let note = Note::new(name.to_string(), None, self.color_scheme());
+            db.lock().await.save(&note).await.unwrap();
+        }
+    }
+
+    pub async fn note(&self, db: &DatabaseAsyncHandle, name: &str) -> Note {
+        db.lock()
+            .await
+            .get(name, self.md_static, self.color_scheme())
+            .await
+            .unwrap()
+    }
+
+    pub async fn links_from(&self, db: &DatabaseAsyncHandle, from: &str) -> Vec<String> {
+        let links = db
+            .lock()
+            .await
+            .find_links_from(from, self.md_static, self.color_scheme(), true)
+            .await
+            .unwrap();
+        names(&links)
+    }
+
+    pub async fn links_to(&self, db: &DatabaseAsyncHandle, to: &str) -> Vec<String> {
+        let links = db
+            .lock()
+            .await
+            .find_links_to(to, self.md_static, self.color_scheme(), true)
+            .await
+            .unwrap();
+        names(&links)
+    }
+
+    /// notes, links and stacks without timestamps, which undo and redo don't bring back
+    pub async fn graph(&self, db: &DatabaseAsyncHandle) -> Dump {
+        let mut dump = db.lock().await.dump().await.unwrap();
+        for note in dump.notes.iter_mut() {
+            note.created_at = None;
+            note.updated_at = None;
+            note.opened_at = None;
+        }
+        dump
+    }
+
+    pub async fn stack(&self, db: &DatabaseAsyncHandle, stack: &str) -> Vec<String> {
+        let notes = db
+            .lock()
+            .await
+            .select_from_stack(stack, self.md_static, self.color_scheme())
+            .await
+            .unwrap();
+        names(&notes)
+    }
+}
+
+pub(crate) fn names(notes: &[Note]) -> Vec<String> {
+    notes.iter().map(Note::name).collect()
+}
+
+pub(crate) fn strip_colors(text: &str) -> String {
+    Regex::new("\u{1b}\\[[0-9;]*m")
+        .unwrap()
+        .replace_all(text, "")
+        .to_string()
+}
@@ -0,0 +1,59 @@
+use std::fs;
+
+use crate::{link::Link, note::Note, parse_cache::ParsedBody, task_item::TaskItem};
+
+use super::Fixture;
+
+#[tokio::test]
+async fn test_parse_cache() {
+    let fixture = Fixture::new();
+    let surf_parsing = &fixture.config.surf_parsing;
+    for (backend, db) in fixture.backends().await {
+        let path = fixture.dir(backend).join("body.md");
+        fs::write(&path, "[site](https://example.com)\n- [ ] task\n").unwrap();
+        let note = Note::new(
+            "body".to_string(),
+            Some(path.clone()),
+            fixture.color_scheme(),
+        );
+        db.lock().await.save(&note).await.unwrap();
+
+        let body = ParsedBody::of(&note, surf_parsing, &db).await.unwrap();
+        assert_eq!(
+            (body.links.len(), body.task_items.len()),
+            (1, 1),
+            "{}",
+            backend
+        );
+
+        // an unchanged file is served from cache, not re-parsed
+        let key = path.to_string_lossy().to_string();
+        let cache = db.lock().await.parse_cache();
+        let mut cached = cache.get(&key).await.unwrap().unwrap();
+        cached.body = cached
+            .body
+            .replace(r#""title":"task""#, r#""title":"cached task""#);
+        cache.save(&key, &cached).await.unwrap();
+        let tasks = TaskItem::parse(&note, surf_parsing, fixture.md_static, &db)
+            .await
+            .unwrap();
+        assert_eq!(tasks[0].title, "cached task", "{}", backend);
+
+        fs::write(&path, "- [x] task\n- [ ] another\n```bash\necho\n```\n").unwrap();
+        let tasks = TaskItem::parse(&note, surf_parsing, fixture.md_static, &db)
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 2, "{}", backend);
+        assert!(tasks[0].completed, "{}", backend);
+        let links = Link::parse(&note, surf_parsing, fixture.color_scheme(), &db)
+            .await
+            .unwrap();
+        assert_eq!(links.len(), 1, "{}", backend);
+        assert_eq!(links[0].link.kind(), "code_block", "{}", backend);
+
+        // cache doesn't outlive the file being body of the note
+        db.lock().await.set_filename("body", None).await.unwrap();
+        let cached = cache.get(&key).await.unwrap();
+        assert!(cached.is_none(), "{}", backend);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::stats::Stats;
+
+use super::Fixture;
+
+#[tokio::test]
+async fn test_stats() {
+    let fixture = Fixture::new();
+    for (backend, db) in fixture.backends().await {
+        fixture.tags(&db, &["root", "a", "b", "c", "d"]).await;
+        for (from, to) in [
+            ("root", "a"),
+            ("root", "b"),
+            ("a", "c"),
+            ("b", "c"),
+            ("d", "c"),
+        ] {
+            db.lock()
+                .await
+                .insert_link(from, to, true, None)
+                .await
+                .unwrap();
+        }
+        let surf_parsing = &fixture.config.surf_parsing;
+
+        let vault = Stats::collect(
+            db.clone(),
+            None,
+            true,
+            surf_parsing,
+            fixture.md_static,
+            fixture.color_scheme(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            (vault.notes, vault.tags, vault.links),
+            (0, 5, 5),
+            "{}",
+            backend
+        );
+        assert_eq!((vault.fan_in.max, vault.fan_out.max), (3, 2), "{}", backend);
+        assert_eq!(vault.fan_in.average, 1.0, "{}", backend);
+        assert_eq!(vault.most_linked[0].name, "c", "{}", backend);
+        assert_eq!(vault.depth, None, "{}", backend);
+
+        let root = fixture.note(&db, "root").await;
+        let subgraph = Stats::collect(
+            db.clone(),
+            Some(&root),
+            true,
+            surf_parsing,
+            fixture.md_static,
+            fixture.color_scheme(),
+        )
+        .await
+        .unwrap();
+        // `d` -> `c` is out of reach
+        assert_eq!((subgraph.tags, subgraph.links), (4, 4), "{}", backend);
+        assert_eq!(subgraph.fan_in.max, 2, "{}", backend);
+        assert_eq!(subgraph.depth, Some(2), "{}", backend);
+
+        let c = fixture.note(&db, "c").await;
+        let up = Stats::collect(
+            db.clone(),
+            Some(&c),
+            false,
+            surf_parsing,
+            fixture.md_static,
+            fixture.color_scheme(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            (up.tags, up.links, up.depth),
+            (5, 5, Some(2)),
+            "{}",
+            backend
+        );
+    }
+}